@@ -1,15 +1,7 @@
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
-use rype::{FontCollection, GlyphBitmap};
+use rype::FontCollection;
 use std::path::PathBuf;
-use std::fs::File;
-use std::io::Write;
-
-fn render_pgm(path: &str, bitmap: GlyphBitmap) {
-    let mut f = File::create(path).unwrap();
-    write!(f, "P5\n{} {}\n255\n", bitmap.width, bitmap.height).unwrap();
-    f.write(&bitmap.data).unwrap();
-}
 
 fn main() {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -23,6 +15,6 @@ fn main() {
         let gid = scaled_face.get_glyph_id(c as u32).unwrap();
         let glyph = scaled_face.get_glyph(gid).unwrap();
         let bitmap = glyph.render().unwrap();
-        render_pgm(&format!("{}.pgm", c), bitmap);
+        bitmap.save_png(format!("{}.png", c)).unwrap();
     }
 }
@@ -0,0 +1,59 @@
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+//! Render a line of text to a PNG image. Useful for bug reports.
+//!
+//! Usage: render_text <font file> <size in pixels> <text> [output.png]
+
+use rype::{FontCollection, MetricsSource};
+use std::env;
+use std::process::exit;
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    if args.len() < 4 {
+        eprintln!(
+            "usage: {} <font file> <size in pixels> <text> [output.png]",
+            args[0]
+        );
+        exit(1);
+    }
+    let size = args[2].parse::<f32>().expect("invalid size");
+    let output = args.get(4).map(|s| s.as_str()).unwrap_or("text.png");
+
+    let fc = FontCollection::new(&args[1]).unwrap();
+    let face = fc.get_face(0).unwrap();
+    let scaled_face = face.scale(size, size, 72, 72);
+    let metrics = scaled_face.line_metrics(MetricsSource::Auto);
+    let baseline = metrics.ascender.ceil() as i32;
+
+    // Characters are mapped to glyphs one by one, since there is no shaping yet
+    let mut glyphs = Vec::new();
+    let mut pen = 0.0f32;
+    for c in args[3].chars() {
+        let gid = scaled_face.get_glyph_id(c as u32).unwrap();
+        match scaled_face.get_glyph(gid).and_then(|glyph| glyph.render()) {
+            Ok(bitmap) => glyphs.push((pen.round() as i32, bitmap)),
+            Err(e) => eprintln!("skipping {:?}: {}", c, e),
+        }
+        pen += scaled_face.advance(gid).unwrap();
+    }
+
+    let width = pen.ceil() as u32 + 1;
+    let height = metrics.line_height().ceil() as u32 + 1;
+    let mut image = image::GrayImage::new(width, height);
+    for (x, bitmap) in glyphs {
+        let left = x + bitmap.left;
+        let top = baseline - bitmap.top;
+        for (y, row) in bitmap.rows().enumerate() {
+            for (x, &coverage) in row.iter().enumerate() {
+                let px = left + x as i32;
+                let py = top + y as i32;
+                if px >= 0 && py >= 0 && (px as u32) < width && (py as u32) < height {
+                    let pixel = image.get_pixel_mut(px as u32, py as u32);
+                    pixel.0[0] = pixel.0[0].max(coverage);
+                }
+            }
+        }
+    }
+    image.save(output).unwrap();
+}
@@ -0,0 +1,23 @@
+//! Look up codepoints, taken from the end of the input, in the character map
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rype::FontCollection;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+    let (font, codepoint) = data.split_at(data.len() - 4);
+    let codepoint = u32::from_be_bytes([codepoint[0], codepoint[1], codepoint[2], codepoint[3]]);
+    let fc = match FontCollection::new_from(font) {
+        Ok(fc) => fc,
+        Err(_) => return,
+    };
+    for face in fc.faces().filter_map(|face| face.ok()) {
+        if let Ok(id) = face.get_glyph_id(codepoint) {
+            let _ = face.glyph_name(id);
+            let _ = face.has_outline(id);
+            let _ = face.subset(&[codepoint], &[]);
+        }
+    }
+});
@@ -0,0 +1,22 @@
+//! Load faces, and query everything that doesn't need glyph IDs
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rype::{FontCollection, MetricsSource};
+
+fuzz_target!(|data: &[u8]| {
+    let fc = match FontCollection::new_from(data) {
+        Ok(fc) => fc,
+        Err(_) => return,
+    };
+    for face in fc.faces().filter_map(|face| face.ok()) {
+        let _ = face.validate();
+        let _ = face.is_monospace();
+        let _ = face.line_metrics(MetricsSource::Auto);
+        let _ = face.underline_metrics();
+        let _ = face.embedding_permissions();
+        let _ = face.variation_axes();
+        let _ = face.design_languages();
+        let _ = face.pdf_descriptor();
+        let _ = face.to_bytes();
+    }
+});
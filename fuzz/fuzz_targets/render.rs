@@ -0,0 +1,22 @@
+//! Render glyphs for printable ASCII
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rype::FontCollection;
+
+fuzz_target!(|data: &[u8]| {
+    let fc = match FontCollection::new_from(data) {
+        Ok(fc) => fc,
+        Err(_) => return,
+    };
+    for face in fc.faces().filter_map(|face| face.ok()) {
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        for codepoint in 0x20..0x7f {
+            if let Ok(glyph) = scaled
+                .get_glyph_id(codepoint)
+                .and_then(|id| scaled.get_glyph(id))
+            {
+                let _ = glyph.render();
+            }
+        }
+    }
+});
@@ -0,0 +1,171 @@
+/* C API for rype, from src/capi.rs. Regenerate with cbindgen, see cbindgen.toml */
+
+#ifndef RYPE_H
+#define RYPE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of a C API call
+ */
+typedef enum RypeStatus {
+  RYPE_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  RYPE_STATUS_NULL_POINTER = 1,
+  /**
+   * Font data is invalid or truncated
+   */
+  RYPE_STATUS_INVALID_FONT = 2,
+  /**
+   * Face index or glyph ID out of bounds
+   */
+  RYPE_STATUS_OUT_OF_BOUNDS = 3,
+  /**
+   * Font uses features rype doesn't support yet
+   */
+  RYPE_STATUS_UNSUPPORTED = 4,
+  /**
+   * Caller buffer is too small. The required size is still reported
+   */
+  RYPE_STATUS_BUFFER_TOO_SMALL = 5,
+} RypeStatus;
+
+/**
+ * Opaque handle to a font collection
+ */
+typedef struct RypeCollection RypeCollection;
+
+/**
+ * Opaque handle to a face. Borrows from its collection
+ */
+typedef struct RypeFace RypeFace;
+
+/**
+ * Opaque handle to a scaled face. Borrows from its face
+ */
+typedef struct RypeScaledFace RypeScaledFace;
+
+/**
+ * Horizontal metrics for a glyph, in font units
+ */
+typedef struct RypeHorizontalMetrics {
+  uint16_t advance;
+  int16_t left_side_bearing;
+} RypeHorizontalMetrics;
+
+/**
+ * Line metrics, in font units
+ */
+typedef struct RypeLineMetrics {
+  float ascender;
+  float descender;
+  float line_gap;
+} RypeLineMetrics;
+
+/**
+ * Size and placement of a rendered glyph bitmap. The bitmap has one byte of coverage per
+ * pixel, row by row, with no padding between rows
+ */
+typedef struct RypeBitmapInfo {
+  uintptr_t width;
+  uintptr_t height;
+  /**
+   * Offset in pixels from the pen position to the left edge of the bitmap
+   */
+  int32_t left;
+  /**
+   * Offset in pixels from the baseline up to the top edge of the bitmap
+   */
+  int32_t top;
+} RypeBitmapInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Load a font collection from `len` bytes at `data`. The data is copied, and needn't outlive
+ * the collection. On success, the handle is stored in `out`
+ */
+RypeStatus rype_collection_new(const uint8_t *data, uintptr_t len, RypeCollection **out);
+
+/**
+ * Free a font collection. Faces loaded from it must be freed first
+ */
+void rype_collection_free(RypeCollection *collection);
+
+/**
+ * Get number of faces in a font collection
+ */
+uintptr_t rype_collection_num_faces(const RypeCollection *collection);
+
+/**
+ * Load face at `index` in a collection. On success, the handle is stored in `out`
+ */
+RypeStatus rype_face_new(const RypeCollection *collection, uintptr_t index, RypeFace **out);
+
+/**
+ * Free a face. Scaled faces created from it must be freed first
+ */
+void rype_face_free(RypeFace *face);
+
+/**
+ * Get glyph ID for a Unicode codepoint
+ */
+RypeStatus rype_face_glyph_id(const RypeFace *face, uint32_t codepoint, uint32_t *out);
+
+/**
+ * Get horizontal metrics for a glyph
+ */
+RypeStatus rype_face_horizontal_metrics(const RypeFace *face,
+                                        uint32_t glyph_id,
+                                        RypeHorizontalMetrics *out);
+
+/**
+ * Get line metrics of a face
+ */
+RypeStatus rype_face_line_metrics(const RypeFace *face, RypeLineMetrics *out);
+
+/**
+ * Scale a face for rendering. On success, the handle is stored in `out`
+ */
+RypeStatus rype_scaled_face_new(const RypeFace *face,
+                                float point_width,
+                                float point_height,
+                                uint16_t dpi_x,
+                                uint16_t dpi_y,
+                                RypeScaledFace **out);
+
+/**
+ * Free a scaled face
+ */
+void rype_scaled_face_free(RypeScaledFace *scaled);
+
+/**
+ * Get horizontal advance of a glyph in pixels
+ */
+RypeStatus rype_scaled_face_advance(const RypeScaledFace *scaled, uint32_t glyph_id, float *out);
+
+/**
+ * Render a glyph into `buffer`, which holds `buffer_len` bytes. The bitmap's size and
+ * placement are stored in `info`. If `buffer` is NULL or too small for a non-empty bitmap,
+ * nothing is written to it and `RypeStatus::BufferTooSmall` is returned, so callers can query
+ * the size first
+ */
+RypeStatus rype_scaled_face_render(const RypeScaledFace *scaled,
+                                   uint32_t glyph_id,
+                                   RypeBitmapInfo *info,
+                                   uint8_t *buffer,
+                                   uintptr_t buffer_len);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* RYPE_H */
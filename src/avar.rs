@@ -0,0 +1,55 @@
+//! Axis variations table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16, get_u16, get_u16_unchecked};
+use super::var::f2dot14;
+
+/// Handle to avar table
+pub(super) struct Avar<'a>(&'a [u8]);
+
+impl<'a> Avar<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Avar<'_>> {
+        if data.len() < 8 {
+            return Err(Error::truncated(0, 8));
+        }
+        match get_u16_unchecked(data, 0) {
+            1 => Ok(Avar(data)),
+            _ => Err(Error::malformed("unsupported avar major version")),
+        }
+    }
+
+    /// Map normalized coordinates through the per-axis segment maps, in place
+    pub(super) fn map(&self, coords: &mut [f32]) {
+        let axis_count = get_u16_unchecked(self.0, 6) as usize;
+        let mut off = 8;
+        for axis in 0..axis_count {
+            let count = match get_u16(self.0, off) {
+                Ok(count) => count as usize,
+                Err(_) => return,
+            };
+            let map = |i: usize| -> Option<(f32, f32)> {
+                let from = get_i16(self.0, off + 2 + i * 4).ok()?;
+                let to = get_i16(self.0, off + 4 + i * 4).ok()?;
+                Some((f2dot14(from), f2dot14(to)))
+            };
+            if let Some(coord) = coords.get_mut(axis) {
+                // Linear interpolation between the map entries surrounding the coordinate
+                let mut prev: Option<(f32, f32)> = None;
+                for (from, to) in (0..count).map_while(map) {
+                    if *coord <= from {
+                        *coord = match prev {
+                            Some((prev_from, prev_to)) if from > prev_from => {
+                                prev_to + (to - prev_to) * (*coord - prev_from) / (from - prev_from)
+                            }
+                            _ => to,
+                        };
+                        break;
+                    }
+                    prev = Some((from, to));
+                }
+            }
+            off += 2 + count * 4;
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! C API. Collections, faces and scaled faces are exposed as opaque handles. A face borrows
+//! from its collection, and a scaled face from its face, so handles must be freed in the
+//! reverse order of creation
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::ptr;
+
+use super::error::Error;
+use super::types::{GlyphID, MetricsSource};
+use super::{Face, FontCollection, ScaledFace};
+
+/// Result of a C API call
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RypeStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// Font data is invalid or truncated
+    InvalidFont = 2,
+    /// Face index or glyph ID out of bounds
+    OutOfBounds = 3,
+    /// Font uses features rype doesn't support yet
+    Unsupported = 4,
+    /// Caller buffer is too small. The required size is still reported
+    BufferTooSmall = 5,
+}
+
+impl From<Error> for RypeStatus {
+    fn from(err: Error) -> RypeStatus {
+        match err {
+            Error::FaceIndexOutOfBounds | Error::GlyphIDOutOfBounds => RypeStatus::OutOfBounds,
+            Error::UnsupportedCmapFormat(_) | Error::Unimplemented(_) => RypeStatus::Unsupported,
+            _ => RypeStatus::InvalidFont,
+        }
+    }
+}
+
+/// Opaque handle to a font collection
+pub struct RypeCollection(FontCollection);
+
+/// Opaque handle to a face. Borrows from its collection
+pub struct RypeFace(Face<'static>);
+
+/// Opaque handle to a scaled face. Borrows from its face
+pub struct RypeScaledFace(ScaledFace<'static, 'static>);
+
+/// Horizontal metrics for a glyph, in font units
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RypeHorizontalMetrics {
+    pub advance: u16,
+    pub left_side_bearing: i16,
+}
+
+/// Line metrics, in font units
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RypeLineMetrics {
+    pub ascender: f32,
+    pub descender: f32,
+    pub line_gap: f32,
+}
+
+/// Size and placement of a rendered glyph bitmap. The bitmap has one byte of coverage per
+/// pixel, row by row, with no padding between rows
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RypeBitmapInfo {
+    pub width: usize,
+    pub height: usize,
+    /// Offset in pixels from the pen position to the left edge of the bitmap
+    pub left: i32,
+    /// Offset in pixels from the baseline up to the top edge of the bitmap
+    pub top: i32,
+}
+
+/// Store result of `f` through `out`
+unsafe fn write_out<T, F>(out: *mut T, f: F) -> RypeStatus
+where
+    F: FnOnce() -> super::error::Result<T>,
+{
+    if out.is_null() {
+        return RypeStatus::NullPointer;
+    }
+    match f() {
+        Ok(val) => {
+            ptr::write(out, val);
+            RypeStatus::Ok
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// Load a font collection from `len` bytes at `data`. The data is copied, and needn't outlive
+/// the collection. On success, the handle is stored in `out`
+///
+/// # Safety
+/// `data` must point to `len` readable bytes, and `out` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_collection_new(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut RypeCollection,
+) -> RypeStatus {
+    if data.is_null() {
+        return RypeStatus::NullPointer;
+    }
+    let data = std::slice::from_raw_parts(data, len);
+    write_out(out, || {
+        FontCollection::new_from(data).map(|fc| Box::into_raw(Box::new(RypeCollection(fc))))
+    })
+}
+
+/// Free a font collection. Faces loaded from it must be freed first
+///
+/// # Safety
+/// `collection` must be NULL or a handle from `rype_collection_new` that isn't yet freed
+#[no_mangle]
+pub unsafe extern "C" fn rype_collection_free(collection: *mut RypeCollection) {
+    if !collection.is_null() {
+        drop(Box::from_raw(collection));
+    }
+}
+
+/// Get number of faces in a font collection
+///
+/// # Safety
+/// `collection` must be a valid handle
+#[no_mangle]
+pub unsafe extern "C" fn rype_collection_num_faces(collection: *const RypeCollection) -> usize {
+    collection.as_ref().map_or(0, |fc| fc.0.num_faces())
+}
+
+/// Load face at `index` in a collection. On success, the handle is stored in `out`
+///
+/// # Safety
+/// `collection` must be a valid handle that outlives the face, and `out` must be valid for
+/// writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_face_new(
+    collection: *const RypeCollection,
+    index: usize,
+    out: *mut *mut RypeFace,
+) -> RypeStatus {
+    let collection: &'static RypeCollection = match collection.as_ref() {
+        Some(collection) => collection,
+        None => return RypeStatus::NullPointer,
+    };
+    write_out(out, || {
+        collection
+            .0
+            .get_face(index)
+            .map(|face| Box::into_raw(Box::new(RypeFace(face))))
+    })
+}
+
+/// Free a face. Scaled faces created from it must be freed first
+///
+/// # Safety
+/// `face` must be NULL or a handle from `rype_face_new` that isn't yet freed
+#[no_mangle]
+pub unsafe extern "C" fn rype_face_free(face: *mut RypeFace) {
+    if !face.is_null() {
+        drop(Box::from_raw(face));
+    }
+}
+
+/// Get glyph ID for a Unicode codepoint
+///
+/// # Safety
+/// `face` must be a valid handle, and `out` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_face_glyph_id(
+    face: *const RypeFace,
+    codepoint: u32,
+    out: *mut u32,
+) -> RypeStatus {
+    match face.as_ref() {
+        Some(face) => write_out(out, || face.0.get_glyph_id(codepoint).map(|id| id.0)),
+        None => RypeStatus::NullPointer,
+    }
+}
+
+/// Get horizontal metrics for a glyph
+///
+/// # Safety
+/// `face` must be a valid handle, and `out` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_face_horizontal_metrics(
+    face: *const RypeFace,
+    glyph_id: u32,
+    out: *mut RypeHorizontalMetrics,
+) -> RypeStatus {
+    match face.as_ref() {
+        Some(face) => write_out(out, || {
+            face.0
+                .horizontal_metrics(GlyphID(glyph_id))
+                .map(|metrics| RypeHorizontalMetrics {
+                    advance: metrics.advance,
+                    left_side_bearing: metrics.left_side_bearing,
+                })
+        }),
+        None => RypeStatus::NullPointer,
+    }
+}
+
+/// Get line metrics of a face
+///
+/// # Safety
+/// `face` must be a valid handle, and `out` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_face_line_metrics(
+    face: *const RypeFace,
+    out: *mut RypeLineMetrics,
+) -> RypeStatus {
+    match face.as_ref() {
+        Some(face) => write_out(out, || {
+            let metrics = face.0.line_metrics(MetricsSource::Auto);
+            Ok(RypeLineMetrics {
+                ascender: metrics.ascender,
+                descender: metrics.descender,
+                line_gap: metrics.line_gap,
+            })
+        }),
+        None => RypeStatus::NullPointer,
+    }
+}
+
+/// Scale a face for rendering. On success, the handle is stored in `out`
+///
+/// # Safety
+/// `face` must be a valid handle that outlives the scaled face, and `out` must be valid for
+/// writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_scaled_face_new(
+    face: *const RypeFace,
+    point_width: f32,
+    point_height: f32,
+    dpi_x: u16,
+    dpi_y: u16,
+    out: *mut *mut RypeScaledFace,
+) -> RypeStatus {
+    let face: &'static RypeFace = match face.as_ref() {
+        Some(face) => face,
+        None => return RypeStatus::NullPointer,
+    };
+    write_out(out, || {
+        let scaled = face.0.scale(point_width, point_height, dpi_x, dpi_y);
+        Ok(Box::into_raw(Box::new(RypeScaledFace(scaled))))
+    })
+}
+
+/// Free a scaled face
+///
+/// # Safety
+/// `scaled` must be NULL or a handle from `rype_scaled_face_new` that isn't yet freed
+#[no_mangle]
+pub unsafe extern "C" fn rype_scaled_face_free(scaled: *mut RypeScaledFace) {
+    if !scaled.is_null() {
+        drop(Box::from_raw(scaled));
+    }
+}
+
+/// Get horizontal advance of a glyph in pixels
+///
+/// # Safety
+/// `scaled` must be a valid handle, and `out` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn rype_scaled_face_advance(
+    scaled: *const RypeScaledFace,
+    glyph_id: u32,
+    out: *mut f32,
+) -> RypeStatus {
+    match scaled.as_ref() {
+        Some(scaled) => write_out(out, || scaled.0.advance(GlyphID(glyph_id))),
+        None => RypeStatus::NullPointer,
+    }
+}
+
+/// Render a glyph into `buffer`, which holds `buffer_len` bytes. The bitmap's size and
+/// placement are stored in `info`. If `buffer` is NULL or too small for a non-empty bitmap,
+/// nothing is written to it and `RypeStatus::BufferTooSmall` is returned, so callers can query
+/// the size first
+///
+/// # Safety
+/// `scaled` must be a valid handle, `info` must be valid for writes, and `buffer` must be NULL
+/// or point to `buffer_len` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn rype_scaled_face_render(
+    scaled: *const RypeScaledFace,
+    glyph_id: u32,
+    info: *mut RypeBitmapInfo,
+    buffer: *mut u8,
+    buffer_len: usize,
+) -> RypeStatus {
+    let scaled = match scaled.as_ref() {
+        Some(scaled) => scaled,
+        None => return RypeStatus::NullPointer,
+    };
+    if info.is_null() {
+        return RypeStatus::NullPointer;
+    }
+    let bitmap = match scaled
+        .0
+        .get_glyph(GlyphID(glyph_id))
+        .and_then(|g| g.render())
+    {
+        Ok(bitmap) => bitmap,
+        Err(err) => return err.into(),
+    };
+    ptr::write(
+        info,
+        RypeBitmapInfo {
+            width: bitmap.width,
+            height: bitmap.height,
+            left: bitmap.left,
+            top: bitmap.top,
+        },
+    );
+    // Rows are copied one by one, since the bitmap's rows may be padded
+    let len = bitmap.width * bitmap.height;
+    if len == 0 {
+        return RypeStatus::Ok;
+    } else if buffer.is_null() || buffer_len < len {
+        return RypeStatus::BufferTooSmall;
+    }
+    for (y, row) in bitmap.rows().enumerate() {
+        ptr::copy_nonoverlapping(row.as_ptr(), buffer.add(y * bitmap.width), row.len());
+    }
+    RypeStatus::Ok
+}
@@ -0,0 +1,111 @@
+//! Letter case transformation of text, done before mapping characters to glyphs
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::width::is_zero_width;
+
+/// Letter case to transform text to
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TextCase {
+    /// All letters uppercase
+    Uppercase,
+    /// All letters lowercase
+    Lowercase,
+    /// First letter of each word in titlecase, and the rest lowercase
+    Titlecase,
+}
+
+/// Characters whose titlecase form differs from their uppercase form: the Latin digraphs
+const TITLECASE: [(char, char); 12] = [
+    ('\u{1c4}', '\u{1c5}'),
+    ('\u{1c5}', '\u{1c5}'),
+    ('\u{1c6}', '\u{1c5}'),
+    ('\u{1c7}', '\u{1c8}'),
+    ('\u{1c8}', '\u{1c8}'),
+    ('\u{1c9}', '\u{1c8}'),
+    ('\u{1ca}', '\u{1cb}'),
+    ('\u{1cb}', '\u{1cb}'),
+    ('\u{1cc}', '\u{1cb}'),
+    ('\u{1f1}', '\u{1f2}'),
+    ('\u{1f2}', '\u{1f2}'),
+    ('\u{1f3}', '\u{1f2}'),
+];
+
+/// Transform letter case of text, using mappings for language `lang` (a BCP 47 tag, e.g. "tr"
+/// or "az-Latn"). This should be done before mapping characters to glyphs, since the number
+/// of characters can change (e.g. "ß" uppercases to "SS"). Turkish and Azerbaijani map dotted
+/// and dotless i separately, and other languages use the default Unicode mappings. Words start
+/// at letters and digits, and continue through apostrophes and combining marks
+pub fn transform_case(text: &str, case: TextCase, lang: Option<&str>) -> String {
+    let turkic = lang
+        .and_then(|lang| lang.split(['-', '_']).next())
+        .is_some_and(|primary| {
+            primary.eq_ignore_ascii_case("tr") || primary.eq_ignore_ascii_case("az")
+        });
+    match case {
+        TextCase::Uppercase => uppercase(text, turkic),
+        TextCase::Lowercase => lowercase(text, turkic),
+        TextCase::Titlecase => {
+            let mut out = String::with_capacity(text.len());
+            let mut rest = text;
+            while let Some(start) = rest.find(char::is_alphanumeric) {
+                out.push_str(&rest[..start]);
+                let word = &rest[start..];
+                let mut chars = word.char_indices().skip(1);
+                let end = chars
+                    .find(|&(_, c)| !is_word_char(c))
+                    .map_or(word.len(), |(idx, _)| idx);
+                let mut chars = word[..end].chars();
+                if let Some(first) = chars.next() {
+                    titlecase(first, turkic, &mut out);
+                }
+                out.push_str(&lowercase(chars.as_str(), turkic));
+                rest = &word[end..];
+            }
+            out.push_str(rest);
+            out
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '\'' || c == '\u{2019}' || (is_zero_width(c) && !c.is_control())
+}
+
+fn titlecase(c: char, turkic: bool, out: &mut String) {
+    if turkic && c == 'i' {
+        out.push('\u{130}');
+    } else if let Some(&(_, title)) = TITLECASE.iter().find(|&&(from, _)| from == c) {
+        out.push(title);
+    } else {
+        out.extend(c.to_uppercase());
+    }
+}
+
+fn uppercase(text: &str, turkic: bool) -> String {
+    if turkic {
+        text.replace('i', "\u{130}").to_uppercase()
+    } else {
+        text.to_uppercase()
+    }
+}
+
+fn lowercase(text: &str, turkic: bool) -> String {
+    if !turkic {
+        return text.to_lowercase();
+    }
+    let mut mapped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // I with a combining dot above is a decomposed dotted capital I
+            'I' if chars.peek() == Some(&'\u{307}') => {
+                chars.next();
+                mapped.push('i');
+            }
+            'I' => mapped.push('\u{131}'),
+            '\u{130}' => mapped.push('i'),
+            _ => mapped.push(c),
+        }
+    }
+    mapped.to_lowercase()
+}
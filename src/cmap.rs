@@ -36,13 +36,13 @@ pub(super) struct Cmap<'a> {
 }
 
 impl<'a> Cmap<'a> {
-    pub(super) fn load(data: &[u8]) -> Result<Cmap> {
+    pub(super) fn load(data: &[u8]) -> Result<Cmap<'_>> {
         if data.len() < 4 {
-            return Err(Error::Invalid);
+            return Err(Error::truncated(0, 4));
         }
         let num_tables = get_u16_unchecked(data, 2) as usize;
         if data.len() < 4 + num_tables * 8 {
-            return Err(Error::Invalid);
+            return Err(Error::truncated(0, 4 + num_tables * 8));
         }
         let mut subtables = Vec::with_capacity(num_tables);
         let mut active = None;
@@ -52,7 +52,7 @@ impl<'a> Cmap<'a> {
             let encoding_id = get_u16_unchecked(data, enc_rec_off + 2);
             let offset = get_u32_unchecked(data, enc_rec_off + 4) as usize;
             if offset >= data.len() {
-                return Err(Error::Invalid);
+                return Err(Error::truncated(offset, 1));
             }
             let subtable = Subtable {
                 platform_id: platform_id,
@@ -75,22 +75,101 @@ impl<'a> Cmap<'a> {
         })
     }
 
-    pub(super) fn subtables(&self) -> std::slice::Iter<Subtable> {
+    pub(super) fn subtables(&self) -> std::slice::Iter<'_, Subtable<'_>> {
         self.subtables.iter()
     }
 
+    pub(super) fn active_subtable(&self) -> Option<&Subtable<'_>> {
+        self.active.as_ref()
+    }
+
     pub(super) fn set_active_subtable(&mut self, subtable: &Subtable<'a>) {
         self.active = Some(subtable.clone())
     }
 
-    // TODO: We only handle formats 4 and 12 for now
+    /// Get all (codepoint, glyph ID) mappings in the active subtable, sorted by codepoint.
+    /// Codepoints mapped to glyph 0 are skipped. Each range is clipped to start after all
+    /// earlier ones, so that overlapping ranges in a malformed subtable expand to at most one
+    /// mapping per codepoint
+    pub(super) fn mappings(&self) -> Result<Vec<(u32, GlyphID)>> {
+        let active = self.active.as_ref().ok_or(Error::NoCharmap)?;
+        let mut mappings = Vec::new();
+        let mut next = 0;
+        match active.format()? {
+            4 => {
+                let segcnt_2 = get_u16(active.data, 6)? as usize;
+                if active.data.len() < 16 + segcnt_2 * 4 {
+                    return Err(Error::truncated(0, 16 + segcnt_2 * 4));
+                }
+                for off in (0..segcnt_2).step_by(2) {
+                    let end = get_u16_unchecked(active.data, 14 + off) as u32;
+                    let start = get_u16_unchecked(active.data, 16 + segcnt_2 + off) as u32;
+                    let delta = get_u16_unchecked(active.data, 16 + segcnt_2 * 2 + off) as u32;
+                    let range = get_u16_unchecked(active.data, 16 + segcnt_2 * 3 + off) as u32;
+                    for codepoint in start.max(next)..=end.min(0xfffe) {
+                        let glyph = if range == 0 {
+                            (codepoint + delta) & 0xffff
+                        } else {
+                            let gloff = (range + (codepoint - start) * 2) as usize
+                                + 16
+                                + segcnt_2 * 3
+                                + off;
+                            match get_u16(active.data, gloff)? as u32 {
+                                0 => 0,
+                                glyph => (glyph + delta) & 0xffff,
+                            }
+                        };
+                        if glyph != 0 {
+                            mappings.push((codepoint, GlyphID(glyph)));
+                        }
+                    }
+                    next = next.max(end + 1);
+                }
+            }
+            12 => {
+                let num_groups = get_u32(active.data, 12)? as usize;
+                if active.data.len() < 16 + 12 * num_groups {
+                    return Err(Error::truncated(0, 16 + 12 * num_groups));
+                }
+                for off in (0..num_groups).map(|i| 16 + i * 12) {
+                    let start = get_u32_unchecked(active.data, off);
+                    let end = get_u32_unchecked(active.data, off + 4);
+                    let glyph = get_u32_unchecked(active.data, off + 8);
+                    if end < start || end > 0x10ffff {
+                        return Err(Error::malformed("invalid character range"));
+                    }
+                    for codepoint in start.max(next)..=end {
+                        let glyph = (codepoint - start).wrapping_add(glyph);
+                        if glyph != 0 {
+                            mappings.push((codepoint, GlyphID(glyph)));
+                        }
+                    }
+                    next = next.max(end + 1);
+                }
+            }
+            format => return Err(Error::UnsupportedCmapFormat(format)),
+        }
+        mappings.sort_by_key(|&(codepoint, _)| codepoint);
+        mappings.dedup_by_key(|&mut (codepoint, _)| codepoint);
+        Ok(mappings)
+    }
+
+    /// Get glyph ID for codepoint. Codepoints the character map doesn't cover give `.notdef`
     pub(super) fn get_glyph_id(&self, codepoint: u32) -> Result<GlyphID> {
+        self.lookup(codepoint)
+            .map(|id| id.unwrap_or(GlyphID::NOTDEF))
+    }
+
+    /// Get glyph ID for codepoint, or `None` if the character map doesn't cover it. A codepoint
+    /// can still be explicitly mapped to `.notdef`
+    // TODO: We only handle formats 4 and 12 for now
+    pub(super) fn lookup(&self, codepoint: u32) -> Result<Option<GlyphID>> {
         if let Some(active) = &self.active {
             match active.format() {
                 Ok(4) => {
                     let segcnt_2 = get_u16(active.data, 6)? as usize;
                     if active.data.len() < 16 + segcnt_2 * 4 {
-                        return Err(Error::Invalid);
+                        return Err(Error::truncated(0, 16 + segcnt_2 * 4));
                     }
                     for off in (0..segcnt_2).step_by(2) {
                         let end = get_u16_unchecked(active.data, 14 + off) as u32;
@@ -98,29 +177,33 @@ impl<'a> Cmap<'a> {
                             continue;
                         }
                         let start = get_u16_unchecked(active.data, 16 + segcnt_2 + off) as u32;
-                        if codepoint < start {
+                        // The required last segment, covering only 0xFFFF, marks the end of
+                        // the table rather than mapping a character
+                        if codepoint < start || start == 0xffff {
                             break;
                         }
                         let delta = get_u16_unchecked(active.data, 16 + segcnt_2 * 2 + off) as u32;
                         let range = get_u16_unchecked(active.data, 16 + segcnt_2 * 3 + off) as u32;
                         if range == 0 {
-                            return Ok(GlyphID((codepoint + delta) & 0xffff));
+                            return Ok(Some(GlyphID((codepoint + delta) & 0xffff)));
                         } else {
                             let gloff = (range + (codepoint - start) * 2) as usize
                                 + 16
                                 + segcnt_2 * 3
                                 + off;
-                            return Ok(GlyphID(
-                                (get_u16(active.data, gloff)? as u32 + delta) & 0xffff,
-                            ));
+                            // 0 in the glyph ID array means the codepoint isn't mapped
+                            return match get_u16(active.data, gloff)? as u32 {
+                                0 => Ok(None),
+                                id => Ok(Some(GlyphID((id + delta) & 0xffff))),
+                            };
                         }
                     }
-                    Ok(GlyphID(0))
+                    Ok(None)
                 }
                 Ok(12) => {
                     let num_groups = get_u32(active.data, 12)? as usize;
                     if active.data.len() < 16 + 12 * num_groups {
-                        return Err(Error::Invalid);
+                        return Err(Error::truncated(0, 16 + 12 * num_groups));
                     }
                     let mut off = 16;
                     for _ in 0..num_groups {
@@ -134,12 +217,12 @@ impl<'a> Cmap<'a> {
                             continue;
                         }
                         let glyph = get_u32_unchecked(active.data, off + 8);
-                        return Ok(GlyphID(codepoint - start + glyph));
+                        return Ok(Some(GlyphID((codepoint - start).wrapping_add(glyph))));
                     }
-                    return Ok(GlyphID(0));
+                    return Ok(None);
                 }
-                Err(_) => Err(Error::Invalid),
-                _ => Ok(GlyphID(0)),
+                Ok(format) => Err(Error::UnsupportedCmapFormat(format)),
+                Err(err) => Err(err),
             }
         } else {
             Err(Error::NoCharmap)
@@ -0,0 +1,82 @@
+//! Color table, with glyphs drawn as stacks of colored layers
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::cpal::Color;
+use super::error::*;
+use super::types::{get_u16, get_u16_unchecked, get_u32, GlyphID};
+
+/// Layer of a color glyph, drawn as the outline of another glyph filled with one color
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ColorLayer {
+    /// Glyph whose outline the layer is filled in
+    pub glyph_id: GlyphID,
+    /// Fill color, or `None` for the text foreground color
+    pub color: Option<Color>,
+}
+
+/// Handle to COLR table. Only version 0 layers are read
+pub(super) struct Colr<'a> {
+    data: &'a [u8],
+    num_base_glyphs: usize,
+    base_glyphs_off: usize,
+    layers_off: usize,
+    num_layers: usize,
+}
+
+impl<'a> Colr<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<Colr<'a>> {
+        let num_base_glyphs = get_u16(data, 2)? as usize;
+        let base_glyphs_off = get_u32(data, 4)? as usize;
+        let layers_off = get_u32(data, 8)? as usize;
+        let num_layers = get_u16(data, 12)? as usize;
+        let end = |off: usize, count: usize, size: usize| {
+            count
+                .checked_mul(size)
+                .and_then(|len| off.checked_add(len))
+                .ok_or(Error::malformed("record array offset overflows"))
+        };
+        if data.len() < end(base_glyphs_off, num_base_glyphs, 6)? {
+            return Err(Error::truncated(base_glyphs_off, num_base_glyphs * 6));
+        }
+        if data.len() < end(layers_off, num_layers, 4)? {
+            return Err(Error::truncated(layers_off, num_layers * 4));
+        }
+        Ok(Colr {
+            data,
+            num_base_glyphs,
+            base_glyphs_off,
+            layers_off,
+            num_layers,
+        })
+    }
+
+    /// Get layers of glyph `id` from bottom to top, as glyph IDs and palette entry indices.
+    /// The index 0xffff stands for the foreground color. Returns an empty list if the glyph
+    /// has no color layers
+    pub(super) fn layers(&self, id: GlyphID) -> Vec<(GlyphID, u16)> {
+        let (mut lo, mut hi) = (0, self.num_base_glyphs);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let off = self.base_glyphs_off + mid * 6;
+            let glyph = get_u16_unchecked(self.data, off) as u32;
+            if glyph < id.0 {
+                lo = mid + 1;
+            } else if glyph > id.0 {
+                hi = mid;
+            } else {
+                let first = get_u16_unchecked(self.data, off + 2) as usize;
+                let count = get_u16_unchecked(self.data, off + 4) as usize;
+                return (first..(first + count).min(self.num_layers))
+                    .map(|i| {
+                        let off = self.layers_off + i * 4;
+                        (
+                            GlyphID(get_u16_unchecked(self.data, off) as u32),
+                            get_u16_unchecked(self.data, off + 2),
+                        )
+                    })
+                    .collect();
+            }
+        }
+        Vec::new()
+    }
+}
@@ -0,0 +1,47 @@
+//! Choosing faces from a fallback list to cover text
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::ops::Range;
+
+use super::types::GlyphID;
+use super::width::is_zero_width;
+use super::Face;
+
+/// A run of text served by one face of a fallback list
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FontRun {
+    /// Byte range of the run in the text
+    pub text: Range<usize>,
+    /// Index of the face serving the run, or `None` if no face covers its characters
+    pub face: Option<usize>,
+}
+
+/// Split text into runs by the face that serves each character: the first face in `faces`
+/// whose character map has a glyph for it. Combining marks stay with the face of the character
+/// before them if it covers them, and control characters, which aren't drawn, always do. No
+/// shaping is done, so this is the coverage a renderer falling back character by character
+/// would get. Runs with no face show where glyphs are missing
+pub fn font_runs(faces: &[&Face], text: &str) -> Vec<FontRun> {
+    let covers = |face: &Face, c: char| match face.lookup_glyph_id(c as u32) {
+        Ok(Some(id)) => id != GlyphID::NOTDEF,
+        _ => false,
+    };
+    let mut runs: Vec<FontRun> = Vec::new();
+    for (idx, c) in text.char_indices() {
+        let end = idx + c.len_utf8();
+        let last = runs.last().map(|run| run.face);
+        let face = match last {
+            Some(last) if c.is_control() => last,
+            Some(Some(last)) if is_zero_width(c) && covers(faces[last], c) => Some(last),
+            _ => faces.iter().position(|face| covers(face, c)),
+        };
+        match runs.last_mut() {
+            Some(run) if run.face == face => run.text.end = end,
+            _ => runs.push(FontRun {
+                text: idx..end,
+                face,
+            }),
+        }
+    }
+    runs
+}
@@ -0,0 +1,121 @@
+//! Color palette table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_u16, get_u16_unchecked, get_u32};
+
+/// Palette type flag for palettes usable with a light background
+const USABLE_WITH_LIGHT_BACKGROUND: u32 = 1;
+/// Palette type flag for palettes usable with a dark background
+const USABLE_WITH_DARK_BACKGROUND: u32 = 2;
+
+/// Color from a CPAL palette, in sRGB, with alpha not premultiplied
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Color {
+    /// Get relative luminance of the color from 0 (black) to 1 (white), with Rec. 709
+    /// weights applied to the linearized sRGB channels. Alpha is ignored
+    pub fn luminance(&self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+}
+
+/// Handle to CPAL table
+pub(super) struct Cpal<'a> {
+    data: &'a [u8],
+    num_entries: u16,
+    num_palettes: u16,
+    num_records: u16,
+    records_off: usize,
+}
+
+impl<'a> Cpal<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<Cpal<'a>> {
+        let num_palettes = get_u16(data, 4)?;
+        let records_off = get_u32(data, 8)? as usize;
+        if data.len() < 12 + num_palettes as usize * 2 {
+            return Err(Error::truncated(0, 12 + num_palettes as usize * 2));
+        }
+        Ok(Cpal {
+            data,
+            num_entries: get_u16(data, 2)?,
+            num_palettes,
+            num_records: get_u16(data, 6)?,
+            records_off,
+        })
+    }
+
+    /// Get number of palettes
+    pub(super) fn num_palettes(&self) -> u16 {
+        self.num_palettes
+    }
+
+    /// Get palette to use by default with background color `background`. Palettes whose type
+    /// flags mark them usable with a light or dark background, whichever the background's
+    /// luminance is closer to, are preferred, then palettes not marked usable only with the
+    /// other kind. Falls back to palette 0 for version 0 tables
+    pub(super) fn default_palette(&self, background: Color) -> u16 {
+        let (usable, other) = if background.luminance() < 0.5 {
+            (USABLE_WITH_DARK_BACKGROUND, USABLE_WITH_LIGHT_BACKGROUND)
+        } else {
+            (USABLE_WITH_LIGHT_BACKGROUND, USABLE_WITH_DARK_BACKGROUND)
+        };
+        let types = (0..self.num_palettes)
+            .map(|palette| (palette, self.palette_type(palette)))
+            .collect::<Vec<_>>();
+        types
+            .iter()
+            .find(|&&(_, flags)| flags & usable != 0)
+            .or_else(|| types.iter().find(|&&(_, flags)| flags & other == 0))
+            .map_or(0, |&(palette, _)| palette)
+    }
+
+    /// Get type flags of palette `palette`, which are only present from version 1
+    fn palette_type(&self, palette: u16) -> u32 {
+        let types_off = 12 + self.num_palettes as usize * 2;
+        if get_u16_unchecked(self.data, 0) == 0 {
+            return 0;
+        }
+        match get_u32(self.data, types_off) {
+            Ok(0) | Err(_) => 0,
+            Ok(off) => (palette as usize * 4)
+                .checked_add(off as usize)
+                .and_then(|off| get_u32(self.data, off).ok())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Get color of entry `entry` in palette `palette`
+    pub(super) fn color(&self, palette: u16, entry: u16) -> Option<Color> {
+        if palette >= self.num_palettes || entry >= self.num_entries {
+            return None;
+        }
+        let first = get_u16_unchecked(self.data, 12 + palette as usize * 2);
+        let record = first as usize + entry as usize;
+        if record >= self.num_records as usize {
+            return None;
+        }
+        let off = record.checked_mul(4)?.checked_add(self.records_off)?;
+        let bgra = self.data.get(off..off.checked_add(4)?)?;
+        Some(Color {
+            red: bgra[2],
+            green: bgra[1],
+            blue: bgra[0],
+            alpha: bgra[3],
+        })
+    }
+}
@@ -0,0 +1,42 @@
+//! Mac OS resource-fork font suitcases (*.dfont)
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_u16, get_u32, get_u8, Tag};
+
+/// Get (start, length) of all `sfnt` resources within a resource fork
+pub(super) fn sfnt_resources(data: &[u8]) -> Result<Vec<(usize, usize)>> {
+    let data_off = get_u32(data, 0)? as usize;
+    let map_off = get_u32(data, 4)? as usize;
+    let type_list_off = map_off
+        .checked_add(get_u16(data, map_off.checked_add(24).ok_or_else(overflow)?)? as usize)
+        .ok_or_else(overflow)?;
+    let num_types = get_u16(data, type_list_off)? as usize + 1;
+    let mut ret = Vec::new();
+    for i in 0..num_types {
+        let type_off = type_list_off + 2 + i * 8;
+        if Tag(get_u32(data, type_off)?) != Tag::new(b"sfnt") {
+            continue;
+        }
+        let num_resources = get_u16(data, type_off + 4)? as usize + 1;
+        let ref_list_off = type_list_off + get_u16(data, type_off + 6)? as usize;
+        for j in 0..num_resources {
+            // Resource data offset is a 24-bit value following an 8-bit attribute field
+            let ref_off = ref_list_off + j * 12;
+            let res_off = ((get_u8(data, ref_off + 5)? as usize) << 16)
+                | get_u16(data, ref_off + 6)? as usize;
+            let len_off = data_off.checked_add(res_off).ok_or_else(overflow)?;
+            let len = get_u32(data, len_off)? as usize;
+            let start = len_off + 4;
+            if start.checked_add(len).is_none_or(|end| end > data.len()) {
+                return Err(Error::truncated(start, len));
+            }
+            ret.push((start, len));
+        }
+    }
+    Ok(ret)
+}
+
+fn overflow() -> Error {
+    Error::malformed("resource offset overflows")
+}
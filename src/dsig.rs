@@ -0,0 +1,65 @@
+//! Digital signature table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_u16_unchecked, get_u32_unchecked};
+
+/// Handle to DSIG table
+pub(super) struct Dsig<'a>(&'a [u8]);
+
+/// A signature record of the DSIG table
+pub(super) struct Signature {
+    pub(super) format: u32,
+    pub(super) length: u32,
+    /// Whether the signature block lies within the table and its signature fits in the block
+    pub(super) valid_block: bool,
+}
+
+impl<'a> Dsig<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Dsig<'_>> {
+        if data.len() < 8 {
+            return Err(Error::truncated(0, 8));
+        }
+        let num_signatures = get_u16_unchecked(data, 4) as usize;
+        if data.len() < 8 + num_signatures * 12 {
+            Err(Error::truncated(0, 8 + num_signatures * 12))
+        } else {
+            Ok(Dsig(data))
+        }
+    }
+
+    pub(super) fn version(&self) -> u32 {
+        get_u32_unchecked(self.0, 0)
+    }
+
+    pub(super) fn flags(&self) -> u16 {
+        get_u16_unchecked(self.0, 6)
+    }
+
+    /// Get signature records
+    pub(super) fn signatures(&self) -> Vec<Signature> {
+        let num_signatures = get_u16_unchecked(self.0, 4) as usize;
+        (0..num_signatures)
+            .map(|i| 8 + i * 12)
+            .map(|off| {
+                let format = get_u32_unchecked(self.0, off);
+                let length = get_u32_unchecked(self.0, off + 4);
+                let block_off = get_u32_unchecked(self.0, off + 8) as usize;
+                // Format 1 blocks have two reserved u16s and the signature length, followed by
+                // the PKCS#7 signature
+                let valid_block = self
+                    .0
+                    .get(block_off..block_off.saturating_add(length as usize))
+                    .filter(|block| block.len() >= 8)
+                    .is_some_and(|block| {
+                        format != 1 || get_u32_unchecked(block, 4) as usize <= block.len() - 8
+                    });
+                Signature {
+                    format,
+                    length,
+                    valid_block,
+                }
+            })
+            .collect()
+    }
+}
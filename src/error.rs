@@ -1,26 +1,93 @@
 //! Errors within `rype`
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
+use crate::types::Tag;
+
 /// `rype`'s Result type
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Errors within `rype`
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// IO errors (reading files, etc.)
     Io(std::io::Error),
-    /// Invalid font file
-    Invalid,
+    /// Font data ends early. `needed` bytes were needed at `offset`, which is relative to the
+    /// start of the structure being read. `table` is the table being read, if known
+    Truncated {
+        table: Option<Tag>,
+        offset: usize,
+        needed: usize,
+    },
+    /// A table the face can't be used without is missing
+    MissingTable(Tag),
+    /// Font data has an invalid value. `table` is the table being read, if known
+    Malformed {
+        table: Option<Tag>,
+        reason: &'static str,
+    },
+    /// Character mapping uses a cmap subtable format that isn't supported
+    UnsupportedCmapFormat(u16),
+    /// Data is not in a known font file format
+    UnknownFormat,
     /// Face index out of bounds
     FaceIndexOutOfBounds,
     /// Glyph ID > number of available glyphs in the font
     GlyphIDOutOfBounds,
+    /// A font being written would have more than 65535 glyphs
+    TooManyGlyphs,
     /// No charmap loaded
     NoCharmap,
+    /// Face has no vertical metrics
+    NoVerticalMetrics,
+    /// Invalid OpenType tag string
+    InvalidTag,
     /// Support not yet implemented
     Unimplemented(String),
 }
 
+impl Error {
+    /// Error for data that ends before `needed` bytes at `offset`
+    pub(super) fn truncated(offset: usize, needed: usize) -> Error {
+        Error::Truncated {
+            table: None,
+            offset,
+            needed,
+        }
+    }
+
+    /// Error for data with an invalid value
+    pub(super) fn malformed(reason: &'static str) -> Error {
+        Error::Malformed {
+            table: None,
+            reason,
+        }
+    }
+
+    /// Record the table being read when the error happened, if not already known
+    pub(super) fn in_table(self, tag: Tag) -> Error {
+        match self {
+            Error::Truncated {
+                table: None,
+                offset,
+                needed,
+            } => Error::Truncated {
+                table: Some(tag),
+                offset,
+                needed,
+            },
+            Error::Malformed {
+                table: None,
+                reason,
+            } => Error::Malformed {
+                table: Some(tag),
+                reason,
+            },
+            err => err,
+        }
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Error {
         Error::Io(e)
@@ -31,28 +98,46 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Error::Io(ref e) => write!(f, "IO error: {}", e),
-            Error::Invalid => write!(f, "invalid font file"),
+            Error::Truncated {
+                table,
+                offset,
+                needed,
+            } => {
+                write!(
+                    f,
+                    "font data ends early: needed {} bytes at offset {}",
+                    needed, offset
+                )?;
+                if let Some(tag) = table {
+                    write!(f, " in '{}' table", tag)?;
+                }
+                Ok(())
+            }
+            Error::MissingTable(tag) => write!(f, "missing '{}' table", tag),
+            Error::Malformed { table, reason } => {
+                write!(f, "invalid font data: {}", reason)?;
+                if let Some(tag) = table {
+                    write!(f, " in '{}' table", tag)?;
+                }
+                Ok(())
+            }
+            Error::UnsupportedCmapFormat(format) => {
+                write!(f, "unsupported cmap subtable format {}", format)
+            }
+            Error::UnknownFormat => write!(f, "unknown font file format"),
             Error::FaceIndexOutOfBounds => write!(f, "face index out of bounds"),
             Error::GlyphIDOutOfBounds => write!(f, "glyph ID out of bounds"),
+            Error::TooManyGlyphs => write!(f, "too many glyphs"),
             Error::NoCharmap => write!(f, "no charmap loaded"),
+            Error::NoVerticalMetrics => write!(f, "no vertical metrics"),
+            Error::InvalidTag => write!(f, "invalid tag"),
             Error::Unimplemented(ref s) => write!(f, "unimplemented: {}", s),
         }
     }
 }
 
 impl std::error::Error for Error {
-    fn description(&self) -> &str {
-        match self {
-            Error::Io(ref e) => e.description(),
-            Error::Invalid => "invalid font file",
-            Error::FaceIndexOutOfBounds => "face index out of bounds",
-            Error::GlyphIDOutOfBounds => "glyph ID out of bounds",
-            Error::NoCharmap => "no charmap loaded",
-            Error::Unimplemented(_) => "unimplemented: {}",
-        }
-    }
-
-    fn cause(&self) -> Option<&dyn std::error::Error> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::Io(ref e) => Some(e),
             _ => None,
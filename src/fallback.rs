@@ -0,0 +1,50 @@
+//! Fallbacks for what faces leave out: positioning of combining marks for faces without GPOS
+//! mark attachment, and advances of space characters for faces without glyphs for them
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::geom::{Point, Rect};
+
+/// Get offset in font units from the base glyph's origin at which to draw a mark with
+/// `mark` bounds, so it is centered above or below `base`. Marks lying mostly above the
+/// baseline go above, with a gap of 1/16 em, and others below. Returns the offset and the
+/// bounds of the base and mark together, for stacking further marks
+pub(super) fn mark_offset(base: Rect, mark: Rect, units_per_em: f32) -> (Point, Rect) {
+    let gap = units_per_em / 16.0;
+    let dx = base.center().x - mark.center().x;
+    let dy = if mark.center().y > 0.0 {
+        base.max.y + gap - mark.min.y
+    } else {
+        base.min.y - gap - mark.max.y
+    };
+    (Point::new(dx, dy), base.union(&mark.translated(dx, dy)))
+}
+
+/// Synthesized advance of a space character
+pub(super) struct SpaceWidth {
+    /// Character whose advance to use, if the face covers it
+    pub(super) like: Option<char>,
+    /// Fraction of the em to use otherwise
+    pub(super) em: f32,
+}
+
+/// Get synthesized advance of space character `c`, or `None` if `c` isn't one. Fixed-width
+/// spaces get their Unicode-defined fraction of the em, and the rest a typical one
+pub(super) fn space_width(c: char) -> Option<SpaceWidth> {
+    let (like, em) = match c {
+        ' ' => (None, 0.25),
+        '\u{a0}' => (Some(' '), 0.25),
+        '\u{2000}' | '\u{2002}' => (None, 0.5),
+        '\u{2001}' | '\u{2003}' | '\u{3000}' => (None, 1.0),
+        '\u{2004}' => (None, 1.0 / 3.0),
+        '\u{2005}' => (None, 0.25),
+        '\u{2006}' => (None, 1.0 / 6.0),
+        '\u{2007}' => (Some('0'), 0.5),
+        '\u{2008}' => (Some('.'), 0.25),
+        '\u{2009}' => (None, 0.2),
+        '\u{200a}' => (None, 0.1),
+        '\u{202f}' => (Some('\u{2009}'), 0.2),
+        '\u{205f}' => (None, 4.0 / 18.0),
+        _ => return None,
+    };
+    Some(SpaceWidth { like, em })
+}
@@ -0,0 +1,82 @@
+//! Font variations table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_u16_unchecked, get_u32_unchecked, Tag};
+
+/// A design-variation axis of a variable font
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VariationAxis {
+    /// Axis tag (e.g. `wght`, `opsz`)
+    pub tag: Tag,
+    pub min_value: f32,
+    pub default_value: f32,
+    pub max_value: f32,
+    /// Whether the axis should be hidden from user interfaces
+    pub hidden: bool,
+    /// Name ID for the display name of the axis
+    pub name_id: u16,
+}
+
+/// Handle to fvar table
+pub(super) struct Fvar<'a>(&'a [u8]);
+
+impl<'a> Fvar<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Fvar<'_>> {
+        if data.len() < 16 {
+            return Err(Error::truncated(0, 16));
+        }
+        let axes_off = get_u16_unchecked(data, 4) as usize;
+        let axis_count = get_u16_unchecked(data, 8) as usize;
+        let axis_size = get_u16_unchecked(data, 10) as usize;
+        if axis_size < 20 {
+            Err(Error::malformed("variation axis records are too small"))
+        } else if data.len() < axes_off + axis_count * axis_size {
+            Err(Error::truncated(axes_off, axis_count * axis_size))
+        } else {
+            Ok(Fvar(data))
+        }
+    }
+
+    pub(super) fn axes(&self) -> Vec<VariationAxis> {
+        let axes_off = get_u16_unchecked(self.0, 4) as usize;
+        let axis_count = get_u16_unchecked(self.0, 8) as usize;
+        let axis_size = get_u16_unchecked(self.0, 10) as usize;
+        let fixed = |off| get_u32_unchecked(self.0, off) as i32 as f32 / 65536.0;
+        (0..axis_count)
+            .map(|i| axes_off + i * axis_size)
+            .map(|off| VariationAxis {
+                tag: Tag(get_u32_unchecked(self.0, off)),
+                min_value: fixed(off + 4),
+                default_value: fixed(off + 8),
+                max_value: fixed(off + 12),
+                hidden: get_u16_unchecked(self.0, off + 16) & 1 != 0,
+                name_id: get_u16_unchecked(self.0, off + 18),
+            })
+            .collect()
+    }
+
+    /// Normalize user-space axis values to coordinates from -1 to 1, in axis order. Axes not
+    /// in `settings` are at their default, and unknown axes are ignored
+    pub(super) fn normalize(&self, settings: &[(Tag, f32)]) -> Vec<f32> {
+        self.axes()
+            .iter()
+            .map(|axis| {
+                let value = match settings.iter().rev().find(|&&(tag, _)| tag == axis.tag) {
+                    Some(&(_, value)) => value.max(axis.min_value).min(axis.max_value),
+                    None => return 0.0,
+                };
+                let coord = if value < axis.default_value && axis.default_value > axis.min_value {
+                    (value - axis.default_value) / (axis.default_value - axis.min_value)
+                } else if value > axis.default_value && axis.max_value > axis.default_value {
+                    (value - axis.default_value) / (axis.max_value - axis.default_value)
+                } else {
+                    0.0
+                };
+                // Coordinates are F2DOT14 values
+                (coord * 16384.0).round() / 16384.0
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,168 @@
+//! Glyph definition table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::otl::{class_def, class_def_entries, coverage_index, device_delta, variation_index};
+use super::types::{get_i16, get_u16, get_u16_unchecked, get_u32, subtable, GlyphID};
+use super::var::ItemVariationStore;
+
+/// Class of a glyph, for layout processing
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum GlyphClass {
+    /// Single character, spacing glyph
+    Base,
+    /// Multiple character, spacing glyph
+    Ligature,
+    /// Non-spacing combining glyph
+    Mark,
+    /// Part of a single character, spacing glyph
+    Component,
+    /// Glyph not assigned a class, or face without class definitions
+    Unknown,
+}
+
+/// Position of a caret between ligature components
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum CaretValue {
+    /// X coordinate in font units, and Device table adjustment in pixels
+    Coordinate(f32, i16),
+    /// Index of the glyph outline point whose x coordinate is the caret position
+    Point(u16),
+}
+
+/// Handle to GDEF table
+pub(super) struct Gdef<'a>(&'a [u8]);
+
+impl<'a> Gdef<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Gdef<'_>> {
+        if data.len() < 12 {
+            return Err(Error::truncated(0, 12));
+        }
+        match get_u16_unchecked(data, 0) {
+            1 => Ok(Gdef(data)),
+            _ => Err(Error::malformed("unsupported GDEF major version")),
+        }
+    }
+
+    /// Get item variation store, present from version 1.3
+    pub(super) fn variation_store(&self) -> Option<ItemVariationStore<'a>> {
+        if get_u16_unchecked(self.0, 2) < 3 {
+            return None;
+        }
+        match get_u32(self.0, 14).ok()? as usize {
+            0 => None,
+            off => ItemVariationStore::load(self.0.get(off..)?).ok(),
+        }
+    }
+
+    /// Get class of glyph from the glyph class definition table
+    pub(super) fn glyph_class(&self, id: GlyphID) -> GlyphClass {
+        glyph_class(subtable(self.0, 4).map_or(0, |data| class_def(data, id)))
+    }
+
+    /// Get glyphs assigned `class` in the glyph class definition table, in glyph order
+    pub(super) fn glyphs_with_class(&self, class: GlyphClass) -> Vec<GlyphID> {
+        subtable(self.0, 4)
+            .map(|data| {
+                class_def_entries(data)
+                    .filter(|&(_, value)| glyph_class(value) == class)
+                    .map(|(id, _)| id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Check if a lookup with `flag` and mark filtering set `mark_set` skips glyph `id`. Marks
+    /// are skipped if ignored outright, if they aren't in the filtering set, or if they don't
+    /// have the mark attachment class from the high byte of the flag
+    pub(super) fn ignores(&self, flag: u16, mark_set: Option<u16>, id: GlyphID) -> bool {
+        match self.glyph_class(id) {
+            GlyphClass::Base => flag & 0x0002 != 0,
+            GlyphClass::Ligature => flag & 0x0004 != 0,
+            GlyphClass::Mark if flag & 0x0008 != 0 => true,
+            GlyphClass::Mark => match (mark_set, flag >> 8) {
+                (Some(set), _) => !self.in_mark_glyph_set(set, id),
+                (None, 0) => false,
+                (None, class) => self.mark_attach_class(id) != class,
+            },
+            _ => false,
+        }
+    }
+
+    /// Get mark attachment class of glyph. Glyphs not assigned a class are in class 0
+    fn mark_attach_class(&self, id: GlyphID) -> u16 {
+        subtable(self.0, 10).map_or(0, |data| class_def(data, id))
+    }
+
+    /// Check if glyph is in the mark glyph set at `idx`, present from version 1.2
+    fn in_mark_glyph_set(&self, idx: u16, id: GlyphID) -> bool {
+        if get_u16_unchecked(self.0, 2) < 2 {
+            return false;
+        }
+        subtable(self.0, 12)
+            .filter(|sets| get_u16(sets, 0).ok() == Some(1))
+            .filter(|sets| get_u16(sets, 2).is_ok_and(|count| idx < count))
+            .and_then(|sets| {
+                let off = get_u32(sets, 4 + idx as usize * 4).ok()? as usize;
+                sets.get(off..)
+            })
+            .is_some_and(|coverage| coverage_index(coverage, id).is_some())
+    }
+
+    /// Get caret positions of a ligature glyph from the ligature caret list, in caret order.
+    /// Coordinates are adjusted for `ppem` and normalized variation `coords`
+    pub(super) fn ligature_carets(
+        &self,
+        id: GlyphID,
+        ppem: u16,
+        coords: &[f32],
+    ) -> Vec<CaretValue> {
+        let list = match subtable(self.0, 8) {
+            Some(list) => list,
+            None => return Vec::new(),
+        };
+        let lig_glyph = subtable(list, 0)
+            .and_then(|coverage| coverage_index(coverage, id))
+            .and_then(|idx| subtable(list, 4 + idx * 2));
+        let lig_glyph = match lig_glyph {
+            Some(lig_glyph) => lig_glyph,
+            None => return Vec::new(),
+        };
+        let store = Some(coords)
+            .filter(|coords| !coords.is_empty())
+            .and_then(|_| self.variation_store());
+        let count = get_u16(lig_glyph, 0).unwrap_or(0) as usize;
+        (0..count)
+            .filter_map(|i| subtable(lig_glyph, 2 + i * 2))
+            .filter_map(|caret| match get_u16(caret, 0).ok()? {
+                1 => Some(CaretValue::Coordinate(get_i16(caret, 2).ok()? as f32, 0)),
+                2 => Some(CaretValue::Point(get_u16(caret, 2).ok()?)),
+                3 => {
+                    let mut units = get_i16(caret, 2).ok()? as f32;
+                    let mut pixels = 0;
+                    if let Some(device) = subtable(caret, 4) {
+                        match (variation_index(device), store) {
+                            (Some((outer, inner)), Some(store)) => {
+                                units += store.delta(outer, inner, coords)
+                            }
+                            (Some(_), None) => (),
+                            (None, _) => pixels = device_delta(device, ppem),
+                        }
+                    }
+                    Some(CaretValue::Coordinate(units, pixels))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn glyph_class(value: u16) -> GlyphClass {
+    match value {
+        1 => GlyphClass::Base,
+        2 => GlyphClass::Ligature,
+        3 => GlyphClass::Mark,
+        4 => GlyphClass::Component,
+        _ => GlyphClass::Unknown,
+    }
+}
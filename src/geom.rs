@@ -0,0 +1,112 @@
+//! Points and rectangles
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+/// A point, or a vector between two points
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    /// Horizontal coordinate
+    pub x: f32,
+    /// Vertical coordinate
+    pub y: f32,
+}
+
+impl Point {
+    /// Create point from its coordinates
+    pub fn new(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    /// Linearly interpolate from `self` (at t = 0) to `other` (at t = 1)
+    pub fn lerp(self, other: Point, t: f32) -> Point {
+        Point {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+}
+
+impl From<(f32, f32)> for Point {
+    fn from((x, y): (f32, f32)) -> Point {
+        Point::new(x, y)
+    }
+}
+
+impl From<Point> for (f32, f32) {
+    fn from(p: Point) -> (f32, f32) {
+        (p.x, p.y)
+    }
+}
+
+/// An axis-aligned rectangle, given by its minimum and maximum corners
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    /// Corner with the smallest coordinates
+    pub min: Point,
+    /// Corner with the largest coordinates
+    pub max: Point,
+}
+
+impl Rect {
+    /// Create rectangle from its minimum (x0, y0) and maximum (x1, y1) corners
+    pub fn new(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect {
+        Rect {
+            min: Point::new(x0, y0),
+            max: Point::new(x1, y1),
+        }
+    }
+
+    /// Get horizontal extent
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    /// Get vertical extent
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    /// Get center of rectangle
+    pub fn center(&self) -> Point {
+        self.min.lerp(self.max, 0.5)
+    }
+
+    /// Check if rectangle has no area
+    pub fn is_empty(&self) -> bool {
+        self.max.x <= self.min.x || self.max.y <= self.min.y
+    }
+
+    /// Check if point lies inside the rectangle or on its edge
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+    }
+
+    /// Get smallest rectangle containing both `self` and `other`
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            self.min.x.min(other.min.x),
+            self.min.y.min(other.min.y),
+            self.max.x.max(other.max.x),
+            self.max.y.max(other.max.y),
+        )
+    }
+
+    /// Get smallest rectangle containing `self` and `p`
+    pub fn include(&self, p: Point) -> Rect {
+        Rect::new(
+            self.min.x.min(p.x),
+            self.min.y.min(p.y),
+            self.max.x.max(p.x),
+            self.max.y.max(p.y),
+        )
+    }
+
+    /// Get rectangle moved by (dx, dy)
+    pub fn translated(&self, dx: f32, dy: f32) -> Rect {
+        Rect::new(
+            self.min.x + dx,
+            self.min.y + dy,
+            self.max.x + dx,
+            self.max.y + dy,
+        )
+    }
+}
@@ -2,9 +2,10 @@
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
 use super::error::*;
-use super::types::{get_i16, get_i16_unchecked, get_u16, get_u16_unchecked, get_u8, Affine};
+use super::geom::{Point, Rect};
+use super::path::PathOp;
+use super::types::{get_i16, get_i16_unchecked, get_u16, get_u8, Affine, Antialiasing, GlyphID};
 use super::GlyphBitmap;
-use rster::{PathOp, Point};
 
 pub(super) struct Glyf<'a>(pub(super) &'a [u8]);
 
@@ -15,17 +16,29 @@ impl<'a> std::fmt::Debug for Glyf<'a> {
 }
 
 impl<'a> Glyf<'a> {
-    pub(super) fn glyph(&self, offset: usize) -> Result<TTGlyph<'a>> {
-        if offset + 10 > self.0.len() {
-            return Err(Error::Invalid);
+    /// Get data at `range` in the table, as given by the loca table
+    pub(super) fn data(&self, range: std::ops::Range<usize>) -> Result<&'a [u8]> {
+        self.0
+            .get(range.clone())
+            .ok_or(Error::truncated(range.start, range.len()))
+    }
+
+    /// Get glyph whose data is at `range` in the table, as given by the loca table
+    pub(super) fn glyph(&self, range: std::ops::Range<usize>) -> Result<TTGlyph<'a>> {
+        let data = self.data(range)?;
+        if data.is_empty() {
+            return Ok(TTGlyph::Empty);
+        }
+        if data.len() < 10 {
+            return Err(Error::truncated(0, 10));
         }
-        let num_contours = get_i16_unchecked(self.0, offset + 0);
-        let xmin = get_i16_unchecked(self.0, offset + 2);
-        let ymin = get_i16_unchecked(self.0, offset + 4);
-        let xmax = get_i16_unchecked(self.0, offset + 6);
-        let ymax = get_i16_unchecked(self.0, offset + 8);
+        let num_contours = get_i16_unchecked(data, 0);
+        let xmin = get_i16_unchecked(data, 2);
+        let ymin = get_i16_unchecked(data, 4);
+        let xmax = get_i16_unchecked(data, 6);
+        let ymax = get_i16_unchecked(data, 8);
         if num_contours < 0 {
-            Ok(TTGlyph::Composite(&self.0[offset..]))
+            Ok(TTGlyph::Composite(data))
         } else {
             Ok(TTGlyph::Simple(SimpleGlyph {
                 num_contours: num_contours as u16,
@@ -33,13 +46,15 @@ impl<'a> Glyf<'a> {
                 ymin: ymin,
                 xmax: xmax,
                 ymax: ymax,
-                data: &self.0[offset + 10..],
+                data: &data[10..],
             }))
         }
     }
 }
 
 pub(super) enum TTGlyph<'a> {
+    /// Glyph without an outline, like the space
+    Empty,
     Simple(SimpleGlyph<'a>),
     Composite(&'a [u8]),
 }
@@ -67,10 +82,10 @@ impl<'a> Iterator for Points<'a> {
             if self.flags_remaining > 0 {
                 self.flags_remaining -= 1;
             } else {
-                self.flag = self.data[self.flag_off];
+                self.flag = get_u8(self.data, self.flag_off).ok()?;
                 self.flag_off += 1;
                 if self.flag & 0x08 != 0 {
-                    self.flags_remaining = self.data[self.flag_off] as usize;
+                    self.flags_remaining = get_u8(self.data, self.flag_off).ok()? as usize;
                     self.flag_off += 1;
                 }
             }
@@ -105,15 +120,66 @@ impl<'a> Iterator for Points<'a> {
                 _ => (),
             }
             self.points_remaining -= 1;
-            let new_pt = self.affine.apply_point(&self.last_point);
-            Some((
-                self.flag & 0x01 > 0,
-                self.affine.apply_point(&self.last_point),
-            ))
+            let (x, y) = self.affine.apply(self.last_point.x, self.last_point.y);
+            Some((self.flag & 0x01 > 0, Point::new(x, y)))
         }
     }
 }
 
+/// A point of a TrueType glyph outline, in font units with y increasing upwards
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GlyphPoint {
+    /// Horizontal coordinate
+    pub x: i16,
+    /// Vertical coordinate
+    pub y: i16,
+    /// Whether the point is on the curve, rather than a quadratic control point
+    pub on_curve: bool,
+    /// Whether the point is the last of its contour
+    pub contour_end: bool,
+    /// Raw flags of the point, with the repeat flag (bit 3) cleared
+    pub flags: u8,
+}
+
+/// A component of a composite glyph
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GlyphComponent {
+    /// Glyph drawn by the component
+    pub glyph_id: GlyphID,
+    /// Raw component flags, e.g. `0x0100` (WE_HAVE_INSTRUCTIONS) or `0x0200`
+    /// (USE_MY_METRICS)
+    pub flags: u16,
+}
+
+/// Iterator over the points of a glyph outline, in the order they are stored
+#[derive(Debug)]
+pub struct GlyphPoints<'a> {
+    points: Option<Points<'a>>,
+    /// Remaining contour end point indices
+    end_pts: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Iterator for GlyphPoints<'a> {
+    type Item = GlyphPoint;
+
+    fn next(&mut self) -> Option<GlyphPoint> {
+        let (on_curve, p) = self.points.as_mut()?.next()?;
+        let contour_end = get_u16(self.end_pts, 0).ok() == Some(self.index as u16);
+        if contour_end {
+            self.end_pts = &self.end_pts[2..];
+        }
+        self.index += 1;
+        Some(GlyphPoint {
+            x: p.x as i16,
+            y: p.y as i16,
+            on_curve,
+            contour_end,
+            flags: self.points.as_ref()?.flag & !0x08,
+        })
+    }
+}
+
 #[derive(Debug)]
 struct ContourSizes<'a> {
     last: isize,
@@ -127,7 +193,11 @@ impl<'a> Iterator for ContourSizes<'a> {
         if self.data.len() == 0 {
             None
         } else {
-            let cur = get_u16_unchecked(self.data, 0) as isize;
+            let cur = get_u16(self.data, 0).ok()? as isize;
+            // Contour end points must increase
+            if cur <= self.last {
+                return None;
+            }
             let diff = cur - self.last;
             self.data = &self.data[2..];
             self.last = cur;
@@ -156,7 +226,7 @@ impl<'a> Iterator for PathIter<'a> {
                 // TODO: These are errors
                 _ => None,
             };
-            self.contour_start.map(|p| PathOp::Move(p))
+            self.contour_start.map(|p| PathOp::MoveTo(p.x, p.y))
         } else {
             match self.last_offcurve {
                 None => {
@@ -164,24 +234,26 @@ impl<'a> Iterator for PathIter<'a> {
                         let (on_curve, p0) = self.points.next()?;
                         self.cur_contour_size -= 1;
                         if on_curve {
-                            Some(PathOp::Line(p0))
+                            Some(PathOp::LineTo(p0.x, p0.y))
                         } else if self.cur_contour_size > 0 {
                             let (on_curve, p1) = self.points.next()?;
                             self.cur_contour_size -= 1;
                             if on_curve {
-                                Some(PathOp::QuadBez(p0, p1))
+                                Some(PathOp::QuadTo(p0.into(), p1.into()))
                             } else {
-                                let pmid = Point::linterp(0.5, p0, p1);
+                                let pmid = p0.lerp(p1, 0.5);
                                 self.last_offcurve = Some(p1);
-                                Some(PathOp::QuadBez(p0, pmid))
+                                Some(PathOp::QuadTo(p0.into(), pmid.into()))
                             }
                         } else {
-                            let ret = Some(PathOp::QuadBez(p0, self.contour_start.unwrap()));
+                            let ret = self
+                                .contour_start
+                                .map(|p| PathOp::QuadTo(p0.into(), p.into()));
                             self.contour_start = None;
                             ret
                         }
                     } else {
-                        let ret = Some(PathOp::Line(self.contour_start.unwrap()));
+                        let ret = self.contour_start.map(|p| PathOp::LineTo(p.x, p.y));
                         self.contour_start = None;
                         ret
                     }
@@ -192,14 +264,16 @@ impl<'a> Iterator for PathIter<'a> {
                         self.cur_contour_size -= 1;
                         if on_curve {
                             self.last_offcurve = None;
-                            Some(PathOp::QuadBez(p0, p1))
+                            Some(PathOp::QuadTo(p0.into(), p1.into()))
                         } else {
-                            let pmid = Point::linterp(0.5, p0, p1);
+                            let pmid = p0.lerp(p1, 0.5);
                             self.last_offcurve = Some(p1);
-                            Some(PathOp::QuadBez(p0, pmid))
+                            Some(PathOp::QuadTo(p0.into(), pmid.into()))
                         }
                     } else {
-                        let ret = Some(PathOp::QuadBez(p0, self.contour_start.unwrap()));
+                        let ret = self
+                            .contour_start
+                            .map(|p| PathOp::QuadTo(p0.into(), p.into()));
                         self.last_offcurve = None;
                         self.contour_start = None;
                         ret
@@ -220,64 +294,281 @@ pub(super) struct SimpleGlyph<'a> {
 }
 
 impl<'a> TTGlyph<'a> {
-    /// Draw glyph with given scaling
-    pub(super) fn render(&self, scale_x: f32, scale_y: f32) -> Result<GlyphBitmap> {
+    /// Get bounding box in font units from the glyph header. Glyphs without an outline have
+    /// none
+    pub(super) fn bounds(&self) -> Option<Rect> {
+        let (xmin, ymin, xmax, ymax) = match self {
+            TTGlyph::Empty => return None,
+            TTGlyph::Simple(ref s) => (s.xmin, s.ymin, s.xmax, s.ymax),
+            TTGlyph::Composite(data) => (
+                get_i16_unchecked(data, 2),
+                get_i16_unchecked(data, 4),
+                get_i16_unchecked(data, 6),
+                get_i16_unchecked(data, 8),
+            ),
+        };
+        Some(Rect::new(
+            xmin as f32,
+            ymin as f32,
+            xmax as f32,
+            ymax as f32,
+        ))
+    }
+
+    /// Draw glyph with given scaling. The bitmap is placed relative to the glyph origin, which
+    /// is `lsb` to the left of the bounding box. Without `lsb`, the origin is taken to be at
+    /// x = 0 in font units
+    pub(super) fn render(
+        &self,
+        scale_x: f32,
+        scale_y: f32,
+        lsb: Option<i16>,
+        antialiasing: Antialiasing,
+    ) -> Result<GlyphBitmap> {
+        let empty = GlyphBitmap {
+            width: 0,
+            height: 0,
+            left: 0,
+            top: 0,
+            stride: 0,
+            data: Box::new([]),
+        };
         match self {
+            TTGlyph::Empty => Ok(empty),
+            TTGlyph::Simple(ref s) if s.num_contours == 0 => Ok(empty),
             TTGlyph::Simple(ref s) => {
-                // Get offsets for flags, x, y
-                let num_contours = s.num_contours as usize;
-                let num_points = get_u16(s.data, (num_contours - 1) * 2)? as usize + 1;
-                let num_insn = get_u16(s.data, num_contours * 2)? as usize;
-                let flag_off = num_contours * 2 + 2 + num_insn;
-                let (x_off, y_off) = get_ttglyph_offsets(s.data, num_points, flag_off)?;
-
-                // Prepare for rendering glyph
-                let width = ((s.xmax - s.xmin) as f32 * scale_x).ceil() as usize + 2;
-                let height = ((s.ymax - s.ymin) as f32 * scale_y).ceil() as usize + 2;
+                // Prepare for rendering glyph. Bitmap edges are snapped to whole pixels from
+                // the origin (phantom point 1), so the outline keeps its sub-pixel position
+                let origin_x = lsb.map_or(0, |lsb| s.xmin as i32 - lsb as i32);
+                let left = ((s.xmin as i32 - origin_x) as f32 * scale_x).floor();
+                let right = ((s.xmax as i32 - origin_x) as f32 * scale_x).ceil();
+                let top = (s.ymax as f32 * scale_y).ceil();
+                let bottom = (s.ymin as f32 * scale_y).floor();
+                let width = (right - left).max(0.0) as usize + 2;
+                let height = (top - bottom).max(0.0) as usize + 2;
                 let affine =
-                    Affine::translation(-s.xmin as f32, -s.ymax as f32).scaled(scale_x, -scale_y);
-                let mut rster = rster::Rster::new(width, height);
+                    Affine::translation(-(origin_x as f32) - left / scale_x, -top / scale_y)
+                        .scaled(scale_x, -scale_y);
 
-                // Get iterator over contours
-                let contour_sizes = ContourSizes {
-                    last: -1,
-                    data: &s.data[..(num_contours * 2)],
-                };
-                // Get iterator over points
-                let points = Points {
-                    points_remaining: num_points,
-                    flags_remaining: 0,
-                    flag: 0,
-                    last_point: Point::new(0.0, 0.0),
-                    flag_off: flag_off,
-                    x_off: x_off,
-                    y_off: y_off,
-                    affine: affine.clone(),
-                    data: s.data,
+                // Draw path, at twice the resolution for high quality anti-aliasing
+                let data = match antialiasing {
+                    Antialiasing::High => {
+                        let path_iter = s.path(affine.scaled(2.0, 2.0))?;
+                        let mut rster = rster::Rster::new(width * 2, height * 2);
+                        rster.draw_path(path_iter.map(PathOp::to_rster));
+                        downsample(&rster.accumulate(), width, height)
+                    }
+                    _ => {
+                        let path_iter = s.path(affine)?;
+                        let mut rster = rster::Rster::new(width, height);
+                        rster.draw_path(path_iter.map(PathOp::to_rster));
+                        rster.accumulate()
+                    }
                 };
-                // Get iterator over path
-                let path_iter = PathIter {
-                    contour_start: None,
-                    last_offcurve: None,
-                    points: points,
-                    cur_contour_size: 0,
-                    contour_sizes: contour_sizes,
+                let data = match antialiasing {
+                    Antialiasing::None => data
+                        .iter()
+                        .map(|&c| if c >= 128 { 255 } else { 0 })
+                        .collect(),
+                    _ => data,
                 };
-                // Draw path
-                rster.draw_path(path_iter);
                 Ok(GlyphBitmap {
                     width: width,
                     height: height,
-                    data: rster.accumulate(),
+                    left: left as i32,
+                    top: top as i32,
+                    stride: width,
+                    data,
+                })
+            }
+            TTGlyph::Composite(_data) => Err(Error::Unimplemented("composite glyphs".to_owned())),
+        }
+    }
+
+    /// Get iterator over the points of the glyph outline
+    pub(super) fn points(&self) -> Result<GlyphPoints<'a>> {
+        match self {
+            TTGlyph::Simple(ref s) if s.num_contours > 0 => {
+                let (_, points) = s.points(Affine::ident())?;
+                Ok(GlyphPoints {
+                    points: Some(points),
+                    end_pts: &s.data[..s.num_contours as usize * 2],
+                    index: 0,
                 })
             }
-            TTGlyph::Composite(ref data) => {
-                Err(Error::Unimplemented("composite glyphs".to_owned()))
+            TTGlyph::Composite(_) => Err(Error::Unimplemented("composite glyphs".to_owned())),
+            _ => Ok(GlyphPoints {
+                points: None,
+                end_pts: &[],
+                index: 0,
+            }),
+        }
+    }
+
+    /// Get TrueType instructions of the glyph
+    pub(super) fn instructions(&self) -> Result<&'a [u8]> {
+        let (len_off, data) = match self {
+            TTGlyph::Empty => return Ok(&[]),
+            TTGlyph::Simple(ref s) => (s.num_contours as usize * 2, s.data),
+            TTGlyph::Composite(data) => {
+                let mut components = Components::new(data);
+                let mut have_instructions = false;
+                for (off, _) in components.by_ref() {
+                    have_instructions |= get_u16(data, off - 2)? & 0x0100 != 0;
+                }
+                if !have_instructions {
+                    return Ok(&[]);
+                }
+                (components.end(), *data)
+            }
+        };
+        let len = get_u16(data, len_off)? as usize;
+        data.get(len_off + 2..len_off + 2 + len)
+            .ok_or(Error::truncated(len_off + 2, len))
+    }
+
+    /// Get components of a composite glyph. Other glyphs have none
+    pub(super) fn components(&self) -> Result<Vec<GlyphComponent>> {
+        match self {
+            TTGlyph::Composite(data) => Components::new(data)
+                .map(|(off, glyph_id)| {
+                    Ok(GlyphComponent {
+                        glyph_id,
+                        flags: get_u16(data, off - 2)?,
+                    })
+                })
+                .collect(),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Append glyph outline, transformed from font units by `affine`, to `ops`
+    pub(super) fn outline(&self, affine: Affine, ops: &mut Vec<PathOp>) -> Result<()> {
+        match self {
+            TTGlyph::Empty => Ok(()),
+            TTGlyph::Simple(ref s) if s.num_contours == 0 => Ok(()),
+            TTGlyph::Simple(ref s) => {
+                ops.extend(s.path(affine)?);
+                Ok(())
             }
+            TTGlyph::Composite(_) => Err(Error::Unimplemented("composite glyphs".to_owned())),
         }
     }
 }
 
+/// Average 2x2 blocks of coverage rendered at twice the resolution of a `width` by `height`
+/// bitmap
+fn downsample(data: &[u8], width: usize, height: usize) -> Box<[u8]> {
+    let mut ret = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row0 = &data[y * 2 * width * 2..];
+        let row1 = &row0[width * 2..];
+        for x in 0..width {
+            let sum = row0[x * 2] as u32
+                + row0[x * 2 + 1] as u32
+                + row1[x * 2] as u32
+                + row1[x * 2 + 1] as u32;
+            ret.push(((sum + 2) / 4) as u8);
+        }
+    }
+    ret.into_boxed_slice()
+}
+
+impl<'a> SimpleGlyph<'a> {
+    /// Get iterator over the glyph's path, transformed from font units by `affine`. The glyph
+    /// must have at least one contour
+    fn path(&self, affine: Affine) -> Result<PathIter<'a>> {
+        let (contour_sizes, points) = self.points(affine)?;
+        Ok(PathIter {
+            contour_start: None,
+            last_offcurve: None,
+            points,
+            cur_contour_size: 0,
+            contour_sizes,
+        })
+    }
+
+    /// Get iterators over the sizes of the glyph's contours, and its points transformed from
+    /// font units by `affine`. The glyph must have at least one contour
+    fn points(&self, affine: Affine) -> Result<(ContourSizes<'a>, Points<'a>)> {
+        // Get offsets for flags, x, y
+        let num_contours = self.num_contours as usize;
+        let num_points = get_u16(self.data, (num_contours - 1) * 2)? as usize + 1;
+        let num_insn = get_u16(self.data, num_contours * 2)? as usize;
+        let flag_off = num_contours * 2 + 2 + num_insn;
+        let (x_off, y_off) = get_ttglyph_offsets(self.data, num_points, flag_off)?;
+
+        // Get iterator over contours
+        let contour_sizes = ContourSizes {
+            last: -1,
+            data: &self.data[..(num_contours * 2)],
+        };
+        // Get iterator over points
+        let points = Points {
+            points_remaining: num_points,
+            flags_remaining: 0,
+            flag: 0,
+            last_point: Point::new(0.0, 0.0),
+            flag_off,
+            x_off,
+            y_off,
+            affine,
+            data: self.data,
+        };
+        Ok((contour_sizes, points))
+    }
+}
+
+/// Iterator over the components of a composite glyph. Yields the offset of each component's
+/// glyph index within the glyph data, and the glyph index
+pub(super) struct Components<'a> {
+    data: &'a [u8],
+    off: Option<usize>,
+    end: usize,
+}
+
+impl<'a> Components<'a> {
+    /// `data` is the glyph data, starting at the glyph header
+    pub(super) fn new(data: &'a [u8]) -> Components<'a> {
+        Components {
+            data,
+            off: Some(10),
+            end: 10,
+        }
+    }
+
+    /// Offset just past the components read so far. Once all components are read, this is
+    /// where the glyph's instructions start
+    pub(super) fn end(&self) -> usize {
+        self.end
+    }
+}
+
+impl<'a> Iterator for Components<'a> {
+    type Item = (usize, GlyphID);
+
+    fn next(&mut self) -> Option<(usize, GlyphID)> {
+        let off = self.off?;
+        let flags = get_u16(self.data, off).ok()?;
+        let glyph = get_u16(self.data, off + 2).ok()?;
+        let mut len = if flags & 0x0001 != 0 { 8 } else { 6 };
+        if flags & 0x0008 != 0 {
+            len += 2;
+        } else if flags & 0x0040 != 0 {
+            len += 4;
+        } else if flags & 0x0080 != 0 {
+            len += 8;
+        }
+        self.end = off + len;
+        self.off = if flags & 0x0020 != 0 {
+            Some(off + len)
+        } else {
+            None
+        };
+        Some((off + 2, GlyphID(glyph as u32)))
+    }
+}
+
 fn get_ttglyph_offsets(
     data: &[u8],
     mut points_remaining: usize,
@@ -299,7 +590,7 @@ fn get_ttglyph_offsets(
             0x02 | 0x12 => x_size += repeat_count,
             _ => (),
         }
-        points_remaining -= repeat_count;
+        points_remaining = points_remaining.saturating_sub(repeat_count);
     }
     let x_off = flags_off + flags_size;
     let y_off = x_off + x_size;
@@ -309,6 +600,7 @@ fn get_ttglyph_offsets(
 impl<'a> std::fmt::Debug for TTGlyph<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
+            TTGlyph::Empty => write!(f, "TTGlyph::Empty"),
             TTGlyph::Simple(_) => write!(f, "TTGlyph::Simple"),
             TTGlyph::Composite(_) => write!(f, "TTGlyph::Composite"),
         }
@@ -0,0 +1,431 @@
+//! Glyph positioning table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::gdef::Gdef;
+use super::otl::{
+    class_def, class_def_entries, coverage_glyphs, coverage_index, device_delta, lookup_flags,
+    variation_index,
+};
+use super::types::{get_i16, get_u16, get_u32, subtable, GlyphID, Tag};
+use super::var::ItemVariationStore;
+
+/// Class-by-class kerning values of a class-based pair adjustment subtable
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KernClassMatrix {
+    /// (glyph, class) for each first glyph covered by the subtable, in glyph order
+    pub left_classes: Vec<(GlyphID, u16)>,
+    /// (glyph, class) for second glyphs assigned a non-zero class, in glyph order. All other
+    /// glyphs are in class 0
+    pub right_classes: Vec<(GlyphID, u16)>,
+    /// Number of first glyph classes
+    pub left_count: u16,
+    /// Number of second glyph classes
+    pub right_count: u16,
+    /// Kerning values in font units, in rows of `right_count` values for each first glyph class
+    pub values: Vec<i16>,
+}
+
+impl KernClassMatrix {
+    /// Get kerning between a pair of classes. Classes out of range give 0
+    pub fn value(&self, left_class: u16, right_class: u16) -> i16 {
+        if left_class >= self.left_count || right_class >= self.right_count {
+            return 0;
+        }
+        self.values[left_class as usize * self.right_count as usize + right_class as usize]
+    }
+}
+
+/// Positioning adjustments of a glyph from GPOS, in font units. Placements offset where the
+/// glyph is drawn, while advances change the pen movement after it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlyphAdjustment {
+    /// Horizontal offset of the glyph
+    pub x_placement: f32,
+    /// Vertical offset of the glyph, positive upwards
+    pub y_placement: f32,
+    /// Change to horizontal advance
+    pub x_advance: f32,
+    /// Change to vertical advance
+    pub y_advance: f32,
+}
+
+impl GlyphAdjustment {
+    fn add(&mut self, other: GlyphAdjustment) {
+        self.x_placement += other.x_placement;
+        self.y_placement += other.y_placement;
+        self.x_advance += other.x_advance;
+        self.y_advance += other.y_advance;
+    }
+}
+
+/// Size and variable font instance values are adjusted for
+pub(super) struct Instance<'a, 'c> {
+    /// Size for Device table adjustments. 0 for no adjustments
+    pub(super) ppem: u16,
+    /// Variation store from GDEF, for VariationIndex tables
+    pub(super) store: Option<ItemVariationStore<'a>>,
+    /// Normalized variation coordinates
+    pub(super) coords: &'c [f32],
+}
+
+/// Handle to GPOS table
+pub(super) struct Gpos<'a> {
+    features: &'a [u8],
+    lookups: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Gpos<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Gpos")
+    }
+}
+
+impl<'a> Gpos<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<Gpos<'a>> {
+        let features = subtable(data, 6).ok_or(Error::malformed("missing feature list"))?;
+        let lookups = subtable(data, 8).ok_or(Error::malformed("missing lookup list"))?;
+        let len = 2 + get_u16(lookups, 0)? as usize * 2;
+        if lookups.len() < len {
+            return Err(Error::truncated(data.len() - lookups.len(), len));
+        }
+        let len = 2 + get_u16(features, 0)? as usize * 6;
+        if features.len() < len {
+            Err(Error::truncated(data.len() - features.len(), len))
+        } else {
+            Ok(Gpos { features, lookups })
+        }
+    }
+
+    /// Get indices of lookups referenced by features with `tag`, for any script and language,
+    /// in ascending order
+    fn feature_lookups(&self, tag: Tag) -> Vec<usize> {
+        let count = get_u16(self.features, 0).unwrap_or(0) as usize;
+        let mut indices = (0..count)
+            .map(|i| 2 + i * 6)
+            .filter(|&rec| get_u32(self.features, rec).ok() == Some(tag.0))
+            .filter_map(|rec| subtable(self.features, rec + 4))
+            .flat_map(|feature| {
+                let count = get_u16(feature, 2).unwrap_or(0) as usize;
+                (0..count).filter_map(move |i| get_u16(feature, 4 + i * 2).ok())
+            })
+            .map(|idx| idx as usize)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Iterate over (lookup type, subtable data) for subtables of lookup at `idx`, resolving
+    /// extension subtables
+    fn subtables(&self, idx: usize) -> impl Iterator<Item = (u16, &'a [u8])> + 'a {
+        let lookup = subtable(self.lookups, 2 + idx * 2).unwrap_or(&[]);
+        let typ = get_u16(lookup, 0).unwrap_or(0);
+        let count = get_u16(lookup, 4).unwrap_or(0) as usize;
+        (0..count).filter_map(move |i| {
+            let data = subtable(lookup, 6 + i * 2)?;
+            if typ != 9 {
+                return Some((typ, data));
+            }
+            let typ = get_u16(data, 2).ok()?;
+            let off = get_u32(data, 4).ok()? as usize;
+            data.get(off..).map(|data| (typ, data))
+        })
+    }
+
+    /// Get horizontal kerning between `left` and `right` from pair adjustment lookups of the
+    /// `kern` feature, as (adjustment in font units, Device table adjustment in pixels) for
+    /// `instance`. Returns `None` if no lookup covers the pair. Within a lookup, the first
+    /// subtable covering the pair applies
+    pub(super) fn kerning(
+        &self,
+        left: GlyphID,
+        right: GlyphID,
+        instance: &Instance,
+    ) -> Option<(f32, i16)> {
+        self.feature_lookups(Tag::new(b"kern"))
+            .into_iter()
+            .filter_map(|idx| {
+                self.subtables(idx)
+                    .filter(|&(typ, _)| typ == 2)
+                    .find_map(|(_, data)| pair_adjustment(data, left, right, instance))
+            })
+            .fold(None, |sum, (units, pixels)| {
+                let (sum_units, sum_pixels) = sum.unwrap_or((0.0, 0i16));
+                Some((sum_units + units, sum_pixels.wrapping_add(pixels)))
+            })
+    }
+
+    /// Get adjustments for `glyphs` from lookups at `indices`, applied in order. Single and pair
+    /// adjustments are supported, and other lookup types are skipped. Device table adjustments
+    /// are not applied. Glyphs skipped by a lookup's flags, according to their classes in
+    /// `gdef`, are not adjusted, and pairs are formed across them
+    pub(super) fn apply(
+        &self,
+        indices: &[usize],
+        glyphs: &[GlyphID],
+        instance: &Instance,
+        gdef: Option<&Gdef>,
+    ) -> Vec<GlyphAdjustment> {
+        let mut adjustments = vec![GlyphAdjustment::default(); glyphs.len()];
+        for &idx in indices {
+            let (flag, mark_set) = lookup_flags(self.lookups, idx);
+            let ignored = |id| gdef.is_some_and(|gdef| gdef.ignores(flag, mark_set, id));
+            let mut i = 0;
+            while i < glyphs.len() {
+                if ignored(glyphs[i]) {
+                    i += 1;
+                    continue;
+                }
+                let next = self.subtables(idx).find_map(|(typ, data)| match typ {
+                    1 => {
+                        adjustments[i].add(single_adjustment(data, glyphs[i], instance)?);
+                        Some(i + 1)
+                    }
+                    2 => {
+                        let j = (i + 1..glyphs.len()).find(|&j| !ignored(glyphs[j]))?;
+                        let (first, second) =
+                            pair_adjustments(data, glyphs[i], glyphs[j], instance)?;
+                        adjustments[i].add(first);
+                        match second {
+                            // The second glyph is skipped if it was adjusted
+                            Some(second) => {
+                                adjustments[j].add(second);
+                                Some(j + 1)
+                            }
+                            None => Some(j),
+                        }
+                    }
+                    _ => None,
+                });
+                i = next.unwrap_or(i + 1);
+            }
+        }
+        adjustments
+    }
+
+    /// Get kerning matrices of class-based pair adjustment subtables of the `kern` feature, in
+    /// lookup order, with values for `instance`, for a face with `num_glyphs` glyphs. Device
+    /// table adjustments are not applied
+    pub(super) fn kerning_class_matrices(
+        &self,
+        instance: &Instance,
+        num_glyphs: u32,
+    ) -> Vec<KernClassMatrix> {
+        self.feature_lookups(Tag::new(b"kern"))
+            .into_iter()
+            .flat_map(|idx| self.subtables(idx))
+            .filter(|&(typ, _)| typ == 2)
+            .filter_map(|(_, data)| class_matrix(data, instance, num_glyphs))
+            .collect()
+    }
+}
+
+/// Get size of ValueRecord with `format`, in bytes
+fn value_record_size(format: u16) -> usize {
+    (format & 0xff).count_ones() as usize * 2
+}
+
+/// Get XAdvance, adjusted by its XAdvDevice table for `instance`, from the ValueRecord at `off`
+/// in `data` with `format`. Device table offsets are from the start of `subtable`
+fn x_advance(
+    subtable: &[u8],
+    data: &[u8],
+    off: usize,
+    format: u16,
+    instance: &Instance,
+) -> Option<(f32, i16)> {
+    let mut units = if format & 0x0004 == 0 {
+        0.0
+    } else {
+        get_i16(data, off + value_record_size(format & 0x0003)).ok()? as f32
+    };
+    let mut pixels = 0;
+    if format & 0x0040 != 0 {
+        let device_off = get_u16(data, off + value_record_size(format & 0x003f)).ok()? as usize;
+        if let Some(device) = subtable.get(device_off..).filter(|_| device_off != 0) {
+            match (variation_index(device), instance.store) {
+                (Some((outer, inner)), Some(store)) => {
+                    units += store.delta(outer, inner, instance.coords)
+                }
+                (Some(_), None) => (),
+                (None, _) => pixels = device_delta(device, instance.ppem),
+            }
+        }
+    }
+    Some((units, pixels))
+}
+
+/// Get all adjustments, with variation deltas for `instance`, from the ValueRecord at `off` in
+/// `data` with `format`. Device table offsets are from the start of `subtable`
+fn value_record(
+    subtable: &[u8],
+    data: &[u8],
+    off: usize,
+    format: u16,
+    instance: &Instance,
+) -> Option<GlyphAdjustment> {
+    let mut values = [0.0; 4];
+    let mut off = off;
+    for (i, value) in values.iter_mut().enumerate() {
+        if format & (1 << i) != 0 {
+            *value = get_i16(data, off).ok()? as f32;
+            off += 2;
+        }
+    }
+    for (i, value) in values.iter_mut().enumerate() {
+        if format & (0x10 << i) == 0 {
+            continue;
+        }
+        let device_off = get_u16(data, off).ok()? as usize;
+        off += 2;
+        let device = subtable.get(device_off..).filter(|_| device_off != 0);
+        if let (Some((outer, inner)), Some(store)) =
+            (device.and_then(variation_index), instance.store)
+        {
+            *value += store.delta(outer, inner, instance.coords);
+        }
+    }
+    Some(GlyphAdjustment {
+        x_placement: values[0],
+        y_placement: values[1],
+        x_advance: values[2],
+        y_advance: values[3],
+    })
+}
+
+/// Get adjustment of a glyph from a single adjustment subtable, if it covers the glyph
+fn single_adjustment(data: &[u8], id: GlyphID, instance: &Instance) -> Option<GlyphAdjustment> {
+    let idx = coverage_index(subtable(data, 2)?, id)?;
+    let format = get_u16(data, 4).ok()?;
+    match get_u16(data, 0).ok()? {
+        1 => value_record(data, data, 6, format, instance),
+        2 => value_record(
+            data,
+            data,
+            8 + idx * value_record_size(format),
+            format,
+            instance,
+        ),
+        _ => None,
+    }
+}
+
+/// Get adjustments of the first glyph, and of the second glyph if its value format is non-zero,
+/// from a pair adjustment subtable, if it covers the pair
+fn pair_adjustments(
+    data: &[u8],
+    left: GlyphID,
+    right: GlyphID,
+    instance: &Instance,
+) -> Option<(GlyphAdjustment, Option<GlyphAdjustment>)> {
+    let (record, off) = pair_record(data, left, right)?;
+    let format1 = get_u16(data, 4).ok()?;
+    let format2 = get_u16(data, 6).ok()?;
+    let first = value_record(data, record, off, format1, instance)?;
+    let second = match format2 {
+        0 => None,
+        _ => {
+            let off = off + value_record_size(format1);
+            Some(value_record(data, record, off, format2, instance)?)
+        }
+    };
+    Some((first, second))
+}
+
+/// Get XAdvance adjustment of the first glyph of a pair from a pair adjustment subtable, if it
+/// covers the pair
+fn pair_adjustment(
+    data: &[u8],
+    left: GlyphID,
+    right: GlyphID,
+    instance: &Instance,
+) -> Option<(f32, i16)> {
+    let (record, off) = pair_record(data, left, right)?;
+    x_advance(data, record, off, get_u16(data, 4).ok()?, instance)
+}
+
+/// Find the (value 1, value 2) record for a pair in a pair adjustment subtable, as the data
+/// containing it and its offset
+fn pair_record(data: &[u8], left: GlyphID, right: GlyphID) -> Option<(&[u8], usize)> {
+    let coverage = subtable(data, 2)?;
+    let idx = coverage_index(coverage, left)?;
+    let format1 = get_u16(data, 4).ok()?;
+    let format2 = get_u16(data, 6).ok()?;
+    let record_size = value_record_size(format1) + value_record_size(format2);
+    match get_u16(data, 0).ok()? {
+        1 => {
+            // Sets of (second glyph, value 1, value 2) records, sorted by second glyph
+            let set = subtable(data, 10 + idx * 2)?;
+            let count = get_u16(set, 0).ok()? as usize;
+            let stride = 2 + record_size;
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let rec_off = 2 + mid * stride;
+                let glyph = get_u16(set, rec_off).ok()? as u32;
+                if glyph == right.0 {
+                    return Some((set, rec_off + 2));
+                } else if glyph < right.0 {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            None
+        }
+        2 => {
+            // Matrix of (value 1, value 2) records, indexed by class of each glyph
+            let class1 = class_def(subtable(data, 8)?, left) as usize;
+            let class2 = class_def(subtable(data, 10)?, right) as usize;
+            let class1_count = get_u16(data, 12).ok()? as usize;
+            let class2_count = get_u16(data, 14).ok()? as usize;
+            if class1 >= class1_count || class2 >= class2_count {
+                return None;
+            }
+            Some((data, 16 + (class1 * class2_count + class2) * record_size))
+        }
+        _ => None,
+    }
+}
+
+/// Extract kerning matrix from a pair adjustment subtable, if it is class-based and has
+/// horizontal advances. Glyphs from `num_glyphs` on are left out of the class lists
+fn class_matrix(data: &[u8], instance: &Instance, num_glyphs: u32) -> Option<KernClassMatrix> {
+    if get_u16(data, 0).ok()? != 2 {
+        return None;
+    }
+    let coverage = subtable(data, 2)?;
+    let format1 = get_u16(data, 4).ok()?;
+    let format2 = get_u16(data, 6).ok()?;
+    let record_size = value_record_size(format1) + value_record_size(format2);
+    let class_def1 = subtable(data, 8)?;
+    let class_def2 = subtable(data, 10)?;
+    let left_count = get_u16(data, 12).ok()?;
+    let right_count = get_u16(data, 14).ok()?;
+    let num_records = left_count as usize * right_count as usize;
+    if format1 & 0x0044 == 0 || data.len() < 16 + num_records * record_size {
+        return None;
+    }
+    let mut left_classes = coverage_glyphs(coverage)
+        .filter(|id| id.0 < num_glyphs)
+        .map(|id| (id, class_def(class_def1, id)))
+        .collect::<Vec<_>>();
+    left_classes.sort_unstable();
+    let right_classes = class_def_entries(class_def2)
+        .filter(|&(id, class)| id.0 < num_glyphs && class != 0)
+        .collect();
+    let values = (0..num_records)
+        .map(|i| {
+            x_advance(data, data, 16 + i * record_size, format1, instance)
+                .map(|(units, _)| units.round() as i16)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(KernClassMatrix {
+        left_classes,
+        right_classes,
+        left_count,
+        right_count,
+        values,
+    })
+}
@@ -0,0 +1,103 @@
+//! Layout of text on a grid of fixed-width cells, for terminal emulators
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::ops::Range;
+
+use super::error::*;
+use super::types::{GlyphID, Tag};
+use super::width::{
+    char_width, is_emoji_modifier, is_regional_indicator, is_variation_selector, EMOJI_SELECTOR,
+    ZWJ,
+};
+use super::ScaledFace;
+
+/// A character and the combining marks following it, or an emoji sequence, placed on the grid
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridCluster {
+    /// Byte range of the cluster in the text
+    pub text: Range<usize>,
+    /// First column the cluster occupies
+    pub column: usize,
+    /// Number of columns the cluster occupies: 2 for East Asian Wide and Fullwidth characters,
+    /// and 1 otherwise
+    pub width: usize,
+    /// Glyphs of the cluster, all drawn at the cluster's origin
+    pub glyph_ids: Vec<GlyphID>,
+    /// Whether the cluster is an emoji sequence the face has no combined glyph for, so its
+    /// glyphs are those of the separate components. Applications can use this to pick another
+    /// emoji face for the cluster
+    pub decomposed: bool,
+}
+
+/// Lay out a line of text on the grid, one character per cluster. Tabs advance to the next
+/// multiple of `tab_width` columns, and other control characters are skipped. Combining
+/// marks are added to the preceding cluster, and VARIATION SELECTOR-16 widens it to 2 columns.
+/// Emoji ZWJ sequences, emoji with skin tone modifiers, and pairs of regional indicators form
+/// single clusters, whose glyphs are combined by the face's `ccmp` and `liga` substitutions if
+/// it has them. Joiners and variation selectors the face has no glyph for are left out, rather
+/// than drawn as .notdef
+pub(super) fn layout(face: &ScaledFace, text: &str, tab_width: usize) -> Result<Vec<GridCluster>> {
+    let mut clusters: Vec<GridCluster> = Vec::new();
+    // Indices of clusters holding emoji sequences
+    let mut sequences = Vec::new();
+    let mut column = 0;
+    for (idx, c) in text.char_indices() {
+        let end = idx + c.len_utf8();
+        if c == '\t' {
+            let tab_width = tab_width.max(1);
+            column = (column / tab_width + 1) * tab_width;
+            continue;
+        }
+        let width = char_width(c);
+        if width == 0 {
+            let last = clusters.last_mut().filter(|last| last.text.end == idx);
+            if let (Some(last), false) = (last, c.is_control()) {
+                last.text.end = end;
+                let invisible = c == ZWJ || is_variation_selector(c);
+                match face.lookup_glyph_id(c as u32)? {
+                    Some(id) if id != GlyphID::NOTDEF => last.glyph_ids.push(id),
+                    _ if invisible => (),
+                    _ => last.glyph_ids.push(GlyphID::NOTDEF),
+                }
+                if c == EMOJI_SELECTOR && last.width == 1 {
+                    last.width = 2;
+                    column += 1;
+                }
+            }
+            continue;
+        }
+        let last = clusters.last_mut().filter(|last| last.text.end == idx);
+        if let Some(last) = last {
+            let joined = (text[..idx].ends_with(ZWJ) || is_emoji_modifier(c)) && last.width == 2;
+            let mut last_chars = text[last.text.clone()].chars();
+            let flag = is_regional_indicator(c)
+                && last_chars.next().is_some_and(is_regional_indicator)
+                && last_chars.next().is_none();
+            if joined || flag {
+                last.text.end = end;
+                last.glyph_ids.push(face.get_glyph_id(c as u32)?);
+                if sequences.last() != Some(&(clusters.len() - 1)) {
+                    sequences.push(clusters.len() - 1);
+                }
+                continue;
+            }
+        }
+        clusters.push(GridCluster {
+            text: idx..end,
+            column,
+            width,
+            glyph_ids: vec![face.get_glyph_id(c as u32)?],
+            decomposed: false,
+        });
+        column += width;
+    }
+    let features = [Tag::new(b"ccmp"), Tag::new(b"liga")];
+    for idx in sequences {
+        let cluster = &mut clusters[idx];
+        cluster.glyph_ids =
+            face.face
+                .apply_gsub(&cluster.glyph_ids, &features, Tag::new(b"DFLT"), None);
+        cluster.decomposed = cluster.glyph_ids.len() > 1;
+    }
+    Ok(clusters)
+}
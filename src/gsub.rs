@@ -0,0 +1,263 @@
+//! Glyph substitution table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::collections::BTreeSet;
+
+use super::error::*;
+use super::gdef::Gdef;
+use super::otl::{coverage_glyphs, coverage_index, lookup_flags};
+use super::types::{get_u16, get_u32, subtable, GlyphID};
+
+/// Substitution stops once the glyph sequence would grow past this multiple of its original
+/// length. Multiple substitution lookups applied one after another can otherwise grow it
+/// exponentially
+const MAX_LEN_FACTOR: usize = 64;
+
+/// Smallest length limit, so that short sequences can still grow
+const MIN_MAX_LEN: usize = 16384;
+
+/// Handle to GSUB table
+pub(super) struct Gsub<'a> {
+    lookups: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Gsub<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Gsub")
+    }
+}
+
+impl<'a> Gsub<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<Gsub<'a>> {
+        let lookups = subtable(data, 8).ok_or(Error::malformed("missing lookup list"))?;
+        let len = 2 + get_u16(lookups, 0)? as usize * 2;
+        if lookups.len() < len {
+            Err(Error::truncated(data.len() - lookups.len(), len))
+        } else {
+            Ok(Gsub { lookups })
+        }
+    }
+
+    /// Iterate over (lookup type, subtable data) for all lookup subtables, resolving extension
+    /// subtables
+    fn subtables(&self) -> impl Iterator<Item = (u16, &'a [u8])> + 'a {
+        let lookups = self.lookups;
+        let count = get_u16(lookups, 0).unwrap_or(0) as usize;
+        (0..count).flat_map(move |i| lookup_subtables(lookups, i))
+    }
+
+    /// Apply lookups at `indices` to `glyphs`, in order. Single, multiple, alternate and ligature
+    /// substitutions are supported, and other lookup types are skipped. Alternate substitution
+    /// always picks the first alternate. Glyphs skipped by a lookup's flags, according to their
+    /// classes in `gdef`, are left in place, so marks between ligature components end up after
+    /// the ligature. Substitution stops if the sequence would grow past `MAX_LEN_FACTOR` times
+    /// its original length, or `MIN_MAX_LEN` glyphs for short sequences
+    pub(super) fn apply(&self, indices: &[usize], glyphs: &mut Vec<GlyphID>, gdef: Option<&Gdef>) {
+        let max_len = glyphs.len().saturating_mul(MAX_LEN_FACTOR).max(MIN_MAX_LEN);
+        for &idx in indices {
+            let (flag, mark_set) = lookup_flags(self.lookups, idx);
+            let ignored = |id| gdef.is_some_and(|gdef| gdef.ignores(flag, mark_set, id));
+            let mut i = 0;
+            while i < glyphs.len() {
+                if ignored(glyphs[i]) {
+                    i += 1;
+                    continue;
+                }
+                let applied =
+                    lookup_subtables(self.lookups, idx).find_map(|(typ, data)| match typ {
+                        1 => single_subst(data, glyphs[i]).map(|id| (vec![0], vec![id])),
+                        2 => sequence_subst(data, glyphs[i]).map(|ids| (vec![0], ids)),
+                        3 => sequence_subst(data, glyphs[i])
+                            .and_then(|ids| ids.first().copied())
+                            .map(|id| (vec![0], vec![id])),
+                        4 => ligature_subst(data, &glyphs[i..], &ignored),
+                        _ => None,
+                    });
+                match applied {
+                    Some((matched, replacement)) => {
+                        if glyphs.len() - matched.len() + replacement.len() > max_len {
+                            return;
+                        }
+                        for &pos in matched[1..].iter().rev() {
+                            glyphs.remove(i + pos);
+                        }
+                        let len = replacement.len();
+                        glyphs.splice(i..i + 1, replacement);
+                        i += len;
+                    }
+                    None => i += 1,
+                }
+            }
+        }
+    }
+
+    /// Add all glyphs that could be substituted for glyphs in `glyphs`. This applies every
+    /// lookup regardless of features and context, so the result could include glyphs that are
+    /// never actually produced
+    pub(super) fn closure(&self, glyphs: &mut BTreeSet<GlyphID>) {
+        loop {
+            let count = glyphs.len();
+            for (typ, data) in self.subtables() {
+                let added = match typ {
+                    1 => single_closure(data, glyphs),
+                    2 | 3 => sequence_closure(data, glyphs),
+                    4 => ligature_closure(data, glyphs),
+                    8 => reverse_chain_closure(data, glyphs),
+                    _ => Vec::new(),
+                };
+                glyphs.extend(added);
+            }
+            if glyphs.len() == count {
+                break;
+            }
+        }
+    }
+}
+
+/// Iterate over (lookup type, subtable data) for subtables of lookup at `idx` in the lookup
+/// list, resolving extension subtables
+fn lookup_subtables(lookups: &[u8], idx: usize) -> impl Iterator<Item = (u16, &[u8])> {
+    let lookup = subtable(lookups, 2 + idx * 2).unwrap_or(&[]);
+    let typ = get_u16(lookup, 0).unwrap_or(0);
+    let count = get_u16(lookup, 4).unwrap_or(0) as usize;
+    (0..count).filter_map(move |i| {
+        let data = subtable(lookup, 6 + i * 2)?;
+        if typ != 7 {
+            return Some((typ, data));
+        }
+        let typ = get_u16(data, 2).ok()?;
+        let off = get_u32(data, 4).ok()? as usize;
+        data.get(off..).map(|data| (typ, data))
+    })
+}
+
+/// Iterate over (glyph, coverage index) for glyphs in `glyphs` covered by the Coverage table
+/// at 16-bit offset `off` in `data`
+fn covered<'a>(
+    data: &'a [u8],
+    off: usize,
+    glyphs: &'a BTreeSet<GlyphID>,
+) -> impl Iterator<Item = (GlyphID, usize)> + 'a {
+    let coverage = subtable(data, off).unwrap_or(&[]);
+    coverage_glyphs(coverage)
+        .enumerate()
+        .filter(move |(_, id)| glyphs.contains(id))
+        .map(|(idx, id)| (id, idx))
+}
+
+/// Read array of 16-bit glyph IDs with a count
+fn glyph_array(data: &[u8], off: usize) -> impl Iterator<Item = GlyphID> + '_ {
+    let count = get_u16(data, off).unwrap_or(0) as usize;
+    (0..count).filter_map(move |i| {
+        get_u16(data, off + 2 + i * 2)
+            .ok()
+            .map(|id| GlyphID(id as u32))
+    })
+}
+
+fn single_closure(data: &[u8], glyphs: &BTreeSet<GlyphID>) -> Vec<GlyphID> {
+    match get_u16(data, 0) {
+        Ok(1) => {
+            let delta = get_u16(data, 4).unwrap_or(0) as u32;
+            covered(data, 2, glyphs)
+                .map(|(id, _)| GlyphID((id.0 + delta) & 0xffff))
+                .collect()
+        }
+        Ok(2) => covered(data, 2, glyphs)
+            .filter_map(|(_, idx)| get_u16(data, 6 + idx * 2).ok())
+            .map(|id| GlyphID(id as u32))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Multiple and alternate substitution share the same layout
+fn sequence_closure(data: &[u8], glyphs: &BTreeSet<GlyphID>) -> Vec<GlyphID> {
+    covered(data, 2, glyphs)
+        .filter_map(|(_, idx)| subtable(data, 6 + idx * 2))
+        .flat_map(|sequence| glyph_array(sequence, 0))
+        .collect()
+}
+
+fn ligature_closure(data: &[u8], glyphs: &BTreeSet<GlyphID>) -> Vec<GlyphID> {
+    let mut added = Vec::new();
+    for (_, idx) in covered(data, 2, glyphs) {
+        let set = match subtable(data, 6 + idx * 2) {
+            Some(set) => set,
+            None => continue,
+        };
+        let count = get_u16(set, 0).unwrap_or(0) as usize;
+        for ligature in (0..count).filter_map(|i| subtable(set, 2 + i * 2)) {
+            let components = get_u16(ligature, 2).unwrap_or(0) as usize;
+            let all_present = (1..components).all(|i| {
+                get_u16(ligature, 2 + i * 2).is_ok_and(|id| glyphs.contains(&GlyphID(id as u32)))
+            });
+            if let (true, Ok(id)) = (all_present, get_u16(ligature, 0)) {
+                added.push(GlyphID(id as u32));
+            }
+        }
+    }
+    added
+}
+
+fn reverse_chain_closure(data: &[u8], glyphs: &BTreeSet<GlyphID>) -> Vec<GlyphID> {
+    let backtrack = get_u16(data, 4).unwrap_or(0) as usize;
+    let lookahead_off = 6 + backtrack * 2;
+    let lookahead = get_u16(data, lookahead_off).unwrap_or(0) as usize;
+    let substitutes_off = lookahead_off + 2 + lookahead * 2;
+    covered(data, 2, glyphs)
+        .filter_map(|(_, idx)| get_u16(data, substitutes_off + 2 + idx * 2).ok())
+        .map(|id| GlyphID(id as u32))
+        .collect()
+}
+
+fn single_subst(data: &[u8], id: GlyphID) -> Option<GlyphID> {
+    let idx = coverage_index(subtable(data, 2)?, id)?;
+    match get_u16(data, 0).ok()? {
+        1 => {
+            let delta = get_u16(data, 4).ok()? as u32;
+            Some(GlyphID((id.0 + delta) & 0xffff))
+        }
+        2 => get_u16(data, 6 + idx * 2).ok().map(|id| GlyphID(id as u32)),
+        _ => None,
+    }
+}
+
+/// Get the sequence a glyph is replaced with by multiple substitution, or its alternates for
+/// alternate substitution
+fn sequence_subst(data: &[u8], id: GlyphID) -> Option<Vec<GlyphID>> {
+    let idx = coverage_index(subtable(data, 2)?, id)?;
+    subtable(data, 6 + idx * 2).map(|sequence| glyph_array(sequence, 0).collect())
+}
+
+/// Get (positions of matched components, ligature glyph) for the first ligature matching the
+/// start of `glyphs`. Glyphs for which `ignored` returns true are skipped when matching
+fn ligature_subst(
+    data: &[u8],
+    glyphs: &[GlyphID],
+    ignored: &dyn Fn(GlyphID) -> bool,
+) -> Option<(Vec<usize>, Vec<GlyphID>)> {
+    let idx = coverage_index(subtable(data, 2)?, glyphs[0])?;
+    let set = subtable(data, 6 + idx * 2)?;
+    let count = get_u16(set, 0).ok()? as usize;
+    (0..count)
+        .filter_map(|i| subtable(set, 2 + i * 2))
+        .find_map(|ligature| {
+            let components = get_u16(ligature, 2).ok()? as usize;
+            let positions = std::iter::once(0)
+                .chain((1..glyphs.len()).filter(|&i| !ignored(glyphs[i])))
+                .take(components)
+                .collect::<Vec<_>>();
+            if components == 0 || positions.len() < components {
+                return None;
+            }
+            let matches = positions.iter().enumerate().skip(1).all(|(i, &pos)| {
+                get_u16(ligature, 2 + i * 2).is_ok_and(|id| glyphs[pos].0 == id as u32)
+            });
+            if !matches {
+                return None;
+            }
+            let id = get_u16(ligature, 0).ok()?;
+            Some((positions, vec![GlyphID(id as u32)]))
+        })
+}
@@ -0,0 +1,42 @@
+//! Horizontal device metrics table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16_unchecked, get_u32_unchecked, GlyphID};
+
+/// Handle to hdmx table
+pub(super) struct Hdmx<'a> {
+    num_glyphs: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Hdmx<'a> {
+    pub(super) fn load(data: &[u8], num_glyphs: usize) -> Result<Hdmx<'_>> {
+        if data.len() < 8 {
+            return Err(Error::truncated(0, 8));
+        }
+        let num_records = get_i16_unchecked(data, 2).max(0) as usize;
+        let record_size = get_u32_unchecked(data, 4) as usize;
+        if record_size < 2 + num_glyphs {
+            Err(Error::malformed("device records are too small"))
+        } else if data.len() < 8 + num_records * record_size {
+            Err(Error::truncated(8, num_records * record_size))
+        } else {
+            Ok(Hdmx { num_glyphs, data })
+        }
+    }
+
+    /// Get hinted integer advance width of glyph at ppem, if there is a record for the ppem
+    pub(super) fn advance(&self, ppem: u8, glyph_id: GlyphID) -> Option<u8> {
+        let idx = glyph_id.0 as usize;
+        if idx >= self.num_glyphs {
+            return None;
+        }
+        let num_records = get_i16_unchecked(self.data, 2).max(0) as usize;
+        let record_size = get_u32_unchecked(self.data, 4) as usize;
+        (0..num_records)
+            .map(|i| 8 + i * record_size)
+            .find(|&off| self.data[off] == ppem)
+            .map(|off| self.data[off + 2 + idx])
+    }
+}
@@ -2,7 +2,7 @@
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
 use super::error::*;
-use super::types::{get_i16_unchecked, get_u16_unchecked};
+use super::types::{get_i16_unchecked, get_u16_unchecked, get_u32_unchecked};
 
 #[derive(Debug)]
 pub(super) enum IdxToLocFmt {
@@ -16,46 +16,67 @@ impl<'a> std::fmt::Debug for Head<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("Head")
             .field("units_per_em", &self.units_per_em())
-            .field("xmin", &self._xmin())
-            .field("ymin", &self._ymin())
-            .field("xmax", &self._xmax())
-            .field("ymax", &self._ymax())
-            .field("lowest_rec_ppem", &self._lowest_rec_ppem())
+            .field("xmin", &self.xmin())
+            .field("ymin", &self.ymin())
+            .field("xmax", &self.xmax())
+            .field("ymax", &self.ymax())
+            .field("lowest_rec_ppem", &self.lowest_rec_ppem())
             .field("index_to_loc_format", &self.idx_to_loc_fmt().unwrap())
             .finish()
     }
 }
 
 impl<'a> Head<'a> {
-    pub(super) fn load(data: &[u8]) -> Result<Head> {
+    pub(super) fn load(data: &[u8]) -> Result<Head<'_>> {
         if data.len() < 54 {
-            Err(Error::Invalid)
+            Err(Error::truncated(0, 54))
         } else {
             Ok(Head(data))
         }
     }
 
+    /// Font revision, as set by the font manufacturer
+    pub(super) fn font_revision(&self) -> f32 {
+        get_u32_unchecked(self.0, 4) as i32 as f32 / 65536.0
+    }
+
+    pub(super) fn checksum_adjustment(&self) -> u32 {
+        get_u32_unchecked(self.0, 8)
+    }
+
+    pub(super) fn magic_number(&self) -> u32 {
+        get_u32_unchecked(self.0, 12)
+    }
+
+    pub(super) fn flags(&self) -> u16 {
+        get_u16_unchecked(self.0, 16)
+    }
+
     pub(super) fn units_per_em(&self) -> u16 {
         get_u16_unchecked(self.0, 18)
     }
 
-    pub(super) fn _xmin(&self) -> i16 {
+    pub(super) fn xmin(&self) -> i16 {
         get_i16_unchecked(self.0, 36)
     }
 
-    pub(super) fn _ymin(&self) -> i16 {
+    pub(super) fn ymin(&self) -> i16 {
         get_i16_unchecked(self.0, 38)
     }
 
-    pub(super) fn _xmax(&self) -> i16 {
+    pub(super) fn xmax(&self) -> i16 {
         get_i16_unchecked(self.0, 40)
     }
 
-    pub(super) fn _ymax(&self) -> i16 {
+    pub(super) fn ymax(&self) -> i16 {
         get_i16_unchecked(self.0, 42)
     }
 
-    pub(super) fn _lowest_rec_ppem(&self) -> u16 {
+    pub(super) fn mac_style(&self) -> u16 {
+        get_u16_unchecked(self.0, 44)
+    }
+
+    pub(super) fn lowest_rec_ppem(&self) -> u16 {
         get_u16_unchecked(self.0, 46)
     }
 
@@ -63,7 +84,7 @@ impl<'a> Head<'a> {
         match get_i16_unchecked(self.0, 50) {
             0 => Ok(IdxToLocFmt::Off16),
             1 => Ok(IdxToLocFmt::Off32),
-            _ => Err(Error::Invalid),
+            _ => Err(Error::malformed("invalid glyph location format")),
         }
     }
 }
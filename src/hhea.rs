@@ -9,30 +9,38 @@ pub(super) struct Hhea<'a>(&'a [u8]);
 impl<'a> std::fmt::Debug for Hhea<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("Hhea")
-            .field("ascender", &self._ascender())
-            .field("descender", &self._descender())
+            .field("ascender", &self.ascender())
+            .field("descender", &self.descender())
             .field("num_of_h_metrics", &self.num_of_h_metrics())
             .finish()
     }
 }
 
 impl<'a> Hhea<'a> {
-    pub(super) fn load(data: &[u8]) -> Result<Hhea> {
+    pub(super) fn load(data: &[u8]) -> Result<Hhea<'_>> {
         if data.len() < 36 {
-            Err(Error::Invalid)
+            Err(Error::truncated(0, 36))
         } else {
             Ok(Hhea(data))
         }
     }
 
-    pub(super) fn _ascender(&self) -> i16 {
+    pub(super) fn ascender(&self) -> i16 {
         get_i16_unchecked(self.0, 4)
     }
 
-    pub(super) fn _descender(&self) -> i16 {
+    pub(super) fn descender(&self) -> i16 {
         get_i16_unchecked(self.0, 6)
     }
 
+    pub(super) fn line_gap(&self) -> i16 {
+        get_i16_unchecked(self.0, 8)
+    }
+
+    pub(super) fn advance_width_max(&self) -> u16 {
+        get_u16_unchecked(self.0, 10)
+    }
+
     pub(super) fn num_of_h_metrics(&self) -> u16 {
         get_u16_unchecked(self.0, 34)
     }
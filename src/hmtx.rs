@@ -2,43 +2,114 @@
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
 use super::error::*;
-use super::types::{get_i16_unchecked, get_u16_unchecked, GlyphID};
+use super::types::{get_i16, get_i16_unchecked, get_u16_unchecked, GlyphID};
 
+/// Horizontal metrics for a glyph, in font units
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HorizontalMetrics {
+    /// Horizontal advance width
+    pub advance: u16,
+    /// Distance from the horizontal origin to the left of the glyph bounding box
+    pub left_side_bearing: i16,
+}
+
+/// Handle to hmtx table. The table consists of `numOfHMetrics` longHorMetric (advance, lsb)
+/// records, followed by left side bearings for the remaining glyphs, which share the last
+/// advance
 pub(super) struct Hmtx<'a> {
     num_glyphs: usize,
-    num_of_h_metrics: usize,
-    data: &'a [u8],
+    /// longHorMetric records
+    h_metrics: &'a [u8],
+    /// Left side bearings of glyphs following the longHorMetric records
+    left_side_bearings: &'a [u8],
+    /// Advance of all glyphs, for faces without an hmtx table
+    uniform_advance: Option<u16>,
 }
 
 impl<'a> Hmtx<'a> {
-    pub(super) fn load(data: &[u8], num_glyphs: usize, num_of_h_metrics: usize) -> Result<Hmtx> {
-        if data.len() < num_of_h_metrics * 2 + num_glyphs * 2 || num_of_h_metrics == 0 {
-            Err(Error::Invalid)
+    /// Load hmtx table. Unless `strict` is set, a truncated table is read with as many
+    /// longHorMetric records as fit in the data
+    pub(super) fn load(
+        data: &[u8],
+        num_glyphs: usize,
+        num_of_h_metrics: usize,
+        strict: bool,
+    ) -> Result<Hmtx<'_>> {
+        let mut num_of_h_metrics = num_of_h_metrics;
+        let len = num_of_h_metrics * 4 + num_glyphs.saturating_sub(num_of_h_metrics) * 2;
+        if data.len() < len {
+            if strict {
+                return Err(Error::truncated(0, len));
+            }
+            num_of_h_metrics = num_of_h_metrics.min(data.len() / 4);
+        }
+        if num_of_h_metrics == 0 {
+            Err(Error::malformed("no horizontal metrics"))
         } else {
+            let (h_metrics, left_side_bearings) = data.split_at(num_of_h_metrics * 4);
             Ok(Hmtx {
                 num_glyphs: num_glyphs,
-                num_of_h_metrics: num_of_h_metrics,
-                data: data,
+                h_metrics,
+                left_side_bearings,
+                uniform_advance: None,
             })
         }
     }
 
-    pub(super) fn get_metrics(&self, glyph_id: GlyphID) -> Result<(u16, i16)> {
-        if (glyph_id.0 as usize) >= self.num_glyphs {
+    /// Metrics for faces without an hmtx table. All glyphs have the same advance, and left
+    /// side bearings of 0
+    pub(super) fn uniform(num_glyphs: usize, advance: u16) -> Hmtx<'a> {
+        Hmtx {
+            num_glyphs,
+            h_metrics: &[],
+            left_side_bearings: &[],
+            uniform_advance: Some(advance),
+        }
+    }
+
+    fn num_of_h_metrics(&self) -> usize {
+        self.h_metrics.len() / 4
+    }
+
+    /// Iterate over advance widths in the longHorMetric array
+    pub(super) fn advances(&self) -> impl Iterator<Item = u16> + 'a {
+        let data = self.h_metrics;
+        (0..self.num_of_h_metrics()).map(move |i| get_u16_unchecked(data, i * 4))
+    }
+
+    /// Get advance width of a glyph. Glyphs beyond the longHorMetric array share the last
+    /// advance width
+    pub(super) fn advance(&self, glyph_id: GlyphID) -> Result<u16> {
+        let idx = glyph_id.0 as usize;
+        if idx >= self.num_glyphs {
             Err(Error::GlyphIDOutOfBounds)
+        } else if let Some(advance) = self.uniform_advance {
+            Ok(advance)
         } else {
-            let glyph_off = glyph_id.0 as usize * 2;
-            if (glyph_id.0 as usize) < self.num_of_h_metrics {
-                Ok((
-                    get_u16_unchecked(self.data, glyph_off),
-                    get_i16_unchecked(self.data, glyph_off + 2),
-                ))
-            } else {
-                Ok((
-                    get_u16_unchecked(self.data, (self.num_of_h_metrics - 1) * 2),
-                    get_i16_unchecked(self.data, glyph_off + 2),
-                ))
-            }
+            let idx = idx.min(self.num_of_h_metrics() - 1);
+            Ok(get_u16_unchecked(self.h_metrics, idx * 4))
         }
     }
+
+    /// Get left side bearing of a glyph
+    pub(super) fn lsb(&self, glyph_id: GlyphID) -> Result<i16> {
+        let idx = glyph_id.0 as usize;
+        let num_of_h_metrics = self.num_of_h_metrics();
+        if idx >= self.num_glyphs {
+            Err(Error::GlyphIDOutOfBounds)
+        } else if self.uniform_advance.is_some() {
+            Ok(0)
+        } else if idx < num_of_h_metrics {
+            Ok(get_i16_unchecked(self.h_metrics, idx * 4 + 2))
+        } else {
+            get_i16(self.left_side_bearings, (idx - num_of_h_metrics) * 2)
+        }
+    }
+
+    pub(super) fn get_metrics(&self, glyph_id: GlyphID) -> Result<HorizontalMetrics> {
+        Ok(HorizontalMetrics {
+            advance: self.advance(glyph_id)?,
+            left_side_bearing: self.lsb(glyph_id)?,
+        })
+    }
 }
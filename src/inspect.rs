@@ -0,0 +1,203 @@
+//! Structured summaries of faces, for debugging and reporting problems with fonts
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::dsig::Dsig;
+use super::types::Tag;
+use super::{Face, VariationAxis};
+
+/// Summary of a face. With the `serde` feature enabled, this can be serialized to JSON etc.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FaceSummary {
+    pub sfnt_version: Tag,
+    pub tables: Vec<TableSummary>,
+    pub head: HeadSummary,
+    pub hhea: HheaSummary,
+    pub os2: Option<Os2Summary>,
+    pub cmap_subtables: Vec<CmapSubtableSummary>,
+    pub axes: Vec<VariationAxis>,
+    /// Digital signature, if the face has a DSIG table that could be read
+    pub dsig: Option<DsigSummary>,
+}
+
+/// Entry in the table directory
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TableSummary {
+    pub tag: Tag,
+    pub offset: u32,
+    pub length: u32,
+    /// Checksum stored in the table directory
+    pub checksum: u32,
+    /// Checksum computed from the table data
+    pub computed_checksum: u32,
+}
+
+/// Fields of the head table
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HeadSummary {
+    pub font_revision: f32,
+    pub flags: u16,
+    pub units_per_em: u16,
+    pub xmin: i16,
+    pub ymin: i16,
+    pub xmax: i16,
+    pub ymax: i16,
+    pub mac_style: u16,
+    pub lowest_rec_ppem: u16,
+    pub index_to_loc_format: String,
+}
+
+/// Fields of the hhea table
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HheaSummary {
+    pub ascender: i16,
+    pub descender: i16,
+    pub line_gap: i16,
+    pub advance_width_max: u16,
+    pub num_of_h_metrics: u16,
+}
+
+/// Fields of the OS/2 table
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Os2Summary {
+    pub version: u16,
+    pub x_avg_char_width: i16,
+    pub weight_class: u16,
+    pub width_class: u16,
+    pub fs_type: u16,
+    pub panose: Vec<u8>,
+    pub vendor_id: Tag,
+    pub fs_selection: u16,
+    pub typo_ascender: i16,
+    pub typo_descender: i16,
+    pub typo_line_gap: i16,
+    pub win_ascent: u16,
+    pub win_descent: u16,
+}
+
+/// cmap encoding record
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CmapSubtableSummary {
+    pub platform_id: u16,
+    pub encoding_id: u16,
+    /// Subtable format, if it could be read
+    pub format: Option<u16>,
+    /// Whether this subtable is used for character to glyph mapping
+    pub active: bool,
+}
+
+/// Header of the DSIG table
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DsigSummary {
+    pub version: u32,
+    pub flags: u16,
+    pub signatures: Vec<SignatureSummary>,
+}
+
+/// DSIG signature record
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SignatureSummary {
+    /// Signature format. Format 1 is a PKCS#7 signature
+    pub format: u32,
+    /// Length of the signature block
+    pub length: u32,
+    /// Whether the signature block lies within the table, and its signature within the block.
+    /// The signature itself isn't verified
+    pub valid_block: bool,
+}
+
+/// Produce a summary of a face
+pub fn inspect(face: &Face) -> FaceSummary {
+    let tables = face
+        .table_records()
+        .map(|(tag, checksum, offset, data)| TableSummary {
+            tag,
+            offset,
+            length: data.len() as u32,
+            checksum,
+            computed_checksum: face.table_checksum(tag, data),
+        })
+        .collect();
+    let head = &face.head;
+    let hhea = &face.hhea;
+    let cmap = face.cmap().ok();
+    let active = cmap.and_then(|cmap| cmap.active_subtable());
+    FaceSummary {
+        sfnt_version: face.sfnt_version,
+        tables,
+        head: HeadSummary {
+            font_revision: head.font_revision(),
+            flags: head.flags(),
+            units_per_em: head.units_per_em(),
+            xmin: head.xmin(),
+            ymin: head.ymin(),
+            xmax: head.xmax(),
+            ymax: head.ymax(),
+            mac_style: head.mac_style(),
+            lowest_rec_ppem: head.lowest_rec_ppem(),
+            index_to_loc_format: match head.idx_to_loc_fmt() {
+                Ok(fmt) => format!("{:?}", fmt),
+                Err(_) => "Invalid".to_owned(),
+            },
+        },
+        hhea: HheaSummary {
+            ascender: hhea.ascender(),
+            descender: hhea.descender(),
+            line_gap: hhea.line_gap(),
+            advance_width_max: hhea.advance_width_max(),
+            num_of_h_metrics: hhea.num_of_h_metrics(),
+        },
+        os2: face.os2().map(|os2| Os2Summary {
+            version: os2.version(),
+            x_avg_char_width: os2.x_avg_char_width(),
+            weight_class: os2.weight_class(),
+            width_class: os2.width_class(),
+            fs_type: os2.fs_type(),
+            panose: os2.panose().to_vec(),
+            vendor_id: os2.vendor_id(),
+            fs_selection: os2.fs_selection(),
+            typo_ascender: os2.typo_ascender(),
+            typo_descender: os2.typo_descender(),
+            typo_line_gap: os2.typo_line_gap(),
+            win_ascent: os2.win_ascent(),
+            win_descent: os2.win_descent(),
+        }),
+        cmap_subtables: cmap
+            .into_iter()
+            .flat_map(|cmap| cmap.subtables())
+            .map(|subtable| CmapSubtableSummary {
+                platform_id: subtable.platform_id,
+                encoding_id: subtable.encoding_id,
+                format: subtable.format().ok(),
+                active: active.is_some_and(|active| {
+                    active.platform_id == subtable.platform_id
+                        && active.encoding_id == subtable.encoding_id
+                }),
+            })
+            .collect(),
+        axes: face.variation_axes(),
+        dsig: face
+            .table(Tag::new(b"DSIG"))
+            .and_then(|data| Dsig::load(data).ok())
+            .map(|dsig| DsigSummary {
+                version: dsig.version(),
+                flags: dsig.flags(),
+                signatures: dsig
+                    .signatures()
+                    .into_iter()
+                    .map(|signature| SignatureSummary {
+                        format: signature.format,
+                        length: signature.length,
+                        valid_block: signature.valid_block,
+                    })
+                    .collect(),
+            }),
+    }
+}
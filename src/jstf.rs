@@ -0,0 +1,131 @@
+//! Justification table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_tag, get_u16, subtable, GlyphID, Tag};
+
+/// Handle to JSTF table
+pub struct Jstf<'a>(&'a [u8]);
+
+impl<'a> std::fmt::Debug for Jstf<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Jstf")
+    }
+}
+
+/// Justification data for a script
+pub struct JstfScript<'a>(&'a [u8]);
+
+/// Justification data for a language system, consisting of priority levels. Lower priority
+/// levels are to be tried first
+pub struct JstfLangSys<'a>(&'a [u8]);
+
+/// Lookups to enable or disable at one justification priority level. Lookup indices refer to
+/// the GSUB and GPOS lookup lists, except for the `*_max` lookups, which are GPOS-format lookups
+/// stored in the JSTF table itself
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct JstfPriority {
+    pub gsub_shrinkage_enable: Vec<u16>,
+    pub gsub_shrinkage_disable: Vec<u16>,
+    pub gpos_shrinkage_enable: Vec<u16>,
+    pub gpos_shrinkage_disable: Vec<u16>,
+    /// Number of JstfMax lookups for shrinkage
+    pub shrinkage_max_lookups: u16,
+    pub gsub_extension_enable: Vec<u16>,
+    pub gsub_extension_disable: Vec<u16>,
+    pub gpos_extension_enable: Vec<u16>,
+    pub gpos_extension_disable: Vec<u16>,
+    /// Number of JstfMax lookups for extension
+    pub extension_max_lookups: u16,
+}
+
+impl<'a> Jstf<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<Jstf<'a>> {
+        let len = 6 + get_u16(data, 4)? as usize * 6;
+        if data.len() < len {
+            Err(Error::truncated(0, len))
+        } else {
+            Ok(Jstf(data))
+        }
+    }
+
+    /// Get justification data for a script
+    pub fn script(&self, script: Tag) -> Option<JstfScript<'a>> {
+        let count = get_u16(self.0, 4).ok()? as usize;
+        (0..count)
+            .map(|i| 6 + i * 6)
+            .find(|&off| get_tag(self.0, off).ok() == Some(script))
+            .and_then(|off| subtable(self.0, off + 4))
+            .map(JstfScript)
+    }
+}
+
+impl<'a> JstfScript<'a> {
+    /// Glyphs, such as kashida, that may be inserted to extend the line
+    pub fn extender_glyphs(&self) -> Vec<GlyphID> {
+        subtable(self.0, 0)
+            .map(|data| {
+                let count = get_u16(data, 0).unwrap_or(0) as usize;
+                (0..count)
+                    .filter_map(|i| get_u16(data, 2 + i * 2).ok())
+                    .map(|id| GlyphID(id as u32))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get justification data for a language system. `None` selects the default language
+    /// system. If there is no data for the given language, the default is returned
+    pub fn lang_sys(&self, lang: Option<Tag>) -> Option<JstfLangSys<'a>> {
+        if let Some(lang) = lang {
+            let count = get_u16(self.0, 4).ok()? as usize;
+            let found = (0..count)
+                .map(|i| 6 + i * 6)
+                .find(|&off| get_tag(self.0, off).ok() == Some(lang))
+                .and_then(|off| subtable(self.0, off + 4));
+            if let Some(data) = found {
+                return Some(JstfLangSys(data));
+            }
+        }
+        subtable(self.0, 2).map(JstfLangSys)
+    }
+}
+
+impl<'a> JstfLangSys<'a> {
+    /// Get priority levels, in the order in which they are to be applied
+    pub fn priorities(&self) -> Vec<JstfPriority> {
+        let count = get_u16(self.0, 0).unwrap_or(0) as usize;
+        (0..count)
+            .filter_map(|i| subtable(self.0, 2 + i * 2))
+            .map(|data| {
+                let mod_list = |i: usize| {
+                    subtable(data, i * 2)
+                        .map(|list| {
+                            let count = get_u16(list, 0).unwrap_or(0) as usize;
+                            (0..count)
+                                .filter_map(|j| get_u16(list, 2 + j * 2).ok())
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                let max_count = |i: usize| {
+                    subtable(data, i * 2)
+                        .and_then(|max| get_u16(max, 0).ok())
+                        .unwrap_or(0)
+                };
+                JstfPriority {
+                    gsub_shrinkage_enable: mod_list(0),
+                    gsub_shrinkage_disable: mod_list(1),
+                    gpos_shrinkage_enable: mod_list(2),
+                    gpos_shrinkage_disable: mod_list(3),
+                    shrinkage_max_lookups: max_count(4),
+                    gsub_extension_enable: mod_list(5),
+                    gsub_extension_disable: mod_list(6),
+                    gpos_extension_enable: mod_list(7),
+                    gpos_extension_disable: mod_list(8),
+                    extension_max_lookups: max_count(9),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,73 @@
+//! Kerning table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16, get_u16, get_u16_unchecked, get_u32, GlyphID};
+
+/// Handle to kern table. Only the OpenType (version 0) layout is supported
+pub(super) struct Kern<'a>(&'a [u8]);
+
+impl<'a> Kern<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Kern<'_>> {
+        if data.len() < 4 {
+            return Err(Error::truncated(0, 4));
+        }
+        match get_u16_unchecked(data, 0) {
+            0 => Ok(Kern(data)),
+            _ => Err(Error::malformed("unsupported kern table version")),
+        }
+    }
+
+    /// Iterate over (coverage, data) for format 0 subtables with horizontal kerning values. Cross-
+    /// stream and minimum value subtables are skipped
+    fn subtables(&self) -> impl Iterator<Item = (u16, &'a [u8])> + 'a {
+        let data = self.0;
+        let count = get_u16_unchecked(data, 2) as usize;
+        (0..count)
+            .scan(4, move |off, _| {
+                let subtable = data.get(*off..)?;
+                let length = get_u16(subtable, 2).ok()? as usize;
+                let coverage = get_u16(subtable, 4).ok()?;
+                *off += length.max(6);
+                Some((coverage, subtable))
+            })
+            .filter(|&(coverage, _)| coverage & 0xff07 == 0x0001)
+    }
+
+    /// Get horizontal kerning between `left` and `right`, in font units. Returns `None` if no
+    /// subtable has a value for the pair. Truncated subtables don't affect the others
+    pub(super) fn kerning(&self, left: GlyphID, right: GlyphID) -> Option<i16> {
+        let key = (left.0 << 16) | (right.0 & 0xffff);
+        let mut result = None;
+        for (coverage, subtable) in self.subtables() {
+            let count = match get_u16(subtable, 6) {
+                Ok(count) => count as usize,
+                Err(_) => continue,
+            };
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let rec_off = 14 + mid * 6;
+                let (pair, value) =
+                    match (get_u32(subtable, rec_off), get_i16(subtable, rec_off + 4)) {
+                        (Ok(pair), Ok(value)) => (pair, value),
+                        _ => break,
+                    };
+                if pair == key {
+                    // Overriding subtables replace the accumulated value
+                    result = if coverage & 0x0008 != 0 {
+                        Some(value)
+                    } else {
+                        Some(result.unwrap_or(0i16).wrapping_add(value))
+                    };
+                    break;
+                } else if pair < key {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+        }
+        result
+    }
+}
@@ -2,27 +2,245 @@
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+/// Enter a tracing span until the end of the enclosing scope, if the `tracing` feature is
+/// enabled
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+/// Emit a tracing event, if the `tracing` feature is enabled
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)*);
+    };
+}
 
 pub mod error;
 use error::*;
 
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+
+mod sniff;
+pub use sniff::{sniff, FontFormat};
 mod types;
-pub use types::GlyphID;
+pub use types::{
+    Affine, Antialiasing, DecorationMetrics, FaceStyle, GlyphID, LineMetrics, MetricsSource,
+    ParseOptions, Rounding, ScaledDecorationMetrics, ScriptMetrics, ScriptTransform, SpaceAdvance,
+    Tag,
+};
 use types::*;
 
+mod avar;
+mod case;
+pub use case::{transform_case, TextCase};
 mod cmap;
+mod colr;
+pub use colr::ColorLayer;
+mod coverage;
+pub use coverage::{font_runs, FontRun};
+mod cpal;
+pub use cpal::Color;
+mod dfont;
+mod dsig;
+mod fallback;
+mod fvar;
+pub use fvar::VariationAxis;
+mod gdef;
+pub use gdef::GlyphClass;
+mod geom;
+pub use geom::{Point, Rect};
 mod glyf;
+pub use glyf::{GlyphComponent, GlyphPoint, GlyphPoints};
+mod gpos;
+pub use gpos::{GlyphAdjustment, KernClassMatrix};
+mod grid;
+pub use grid::GridCluster;
+mod gsub;
+mod hdmx;
 mod head;
 mod hhea;
 mod hmtx;
+pub use hmtx::HorizontalMetrics;
+pub mod inspect;
+mod jstf;
+mod kern;
+pub use jstf::{Jstf, JstfLangSys, JstfPriority, JstfScript};
 mod loca;
+mod ltsh;
+mod math;
+pub use math::{
+    AssembledPart, GlyphAssembly, GlyphPart, Math, MathConstant, MathDirection, MathKernCorner,
+    MathVariant, StretchedGlyph,
+};
 mod maxp;
+mod merge;
+pub use merge::merge;
+mod meta;
+mod name;
+mod os2;
+mod otl;
+pub use os2::{CodePages, EmbeddingLevel, EmbeddingPermissions, UnicodeBlock, UnicodeRanges};
+pub use otl::{CharacterVariant, SizeParams};
+mod path;
+pub use path::{Path, PathOp};
+mod pdf;
+pub use pdf::{PdfFontDescriptor, PdfSubset};
+mod post;
+mod provider;
+pub use provider::FontDataProvider;
+mod run;
+pub use run::{Direction, PositionedGlyph};
+mod scan;
+pub use scan::{scan_dir, ScanDir, ScannedFace};
+mod stat;
+mod strip;
+pub use strip::StripOptions;
+mod subset;
+#[cfg(feature = "system")]
+pub mod system;
+mod vhea;
+mod vmtx;
+pub use vmtx::VerticalMetrics;
+mod validate;
+mod var;
+pub use validate::ValidationProblem;
+mod vorg;
+pub mod width;
+mod write;
+pub use write::FontBuilder;
 
-/// Result of rendering a glyph
+/// Result of rendering a glyph. Coverage is stored one byte per pixel, row by row from the top.
+/// Rows start `stride` bytes apart, and may be padded past `width`
 pub struct GlyphBitmap {
     pub width: usize,
     pub height: usize,
-    pub data: Box<[u8]>,
+    /// Horizontal offset in pixels from the pen position to the left edge of the bitmap
+    pub left: i32,
+    /// Vertical offset in pixels from the baseline up to the top edge of the bitmap
+    pub top: i32,
+    stride: usize,
+    data: Box<[u8]>,
+}
+
+impl GlyphBitmap {
+    /// Get number of bytes from the start of one row to the start of the next
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get coverage data, `height` rows of `stride` bytes each
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get pointer to the start of the coverage data, for uploading to the GPU
+    pub fn as_ptr(&self) -> *const u8 {
+        self.data.as_ptr()
+    }
+
+    /// Get coverage of row `y`, without padding
+    ///
+    /// # Panics
+    /// Panics if `y` is not less than `height`
+    pub fn row(&self, y: usize) -> &[u8] {
+        let start = y * self.stride;
+        &self.data[start..start + self.width]
+    }
+
+    /// Get iterator over rows from the top, without padding
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[u8]> + '_ {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    /// Get copy of bitmap cropped to the smallest box containing all non-zero coverage, with
+    /// `left` and `top` adjusted so it lands in the same place. Rendered bitmaps are sized from
+    /// the glyph's header bounds plus padding, so this saves atlas space. A bitmap without
+    /// coverage is cropped to zero size
+    pub fn trimmed(&self) -> GlyphBitmap {
+        let covered = |row: &[u8]| row.iter().any(|&c| c != 0);
+        let y0 = match self.rows().position(covered) {
+            Some(y0) => y0,
+            None => {
+                return GlyphBitmap {
+                    width: 0,
+                    height: 0,
+                    left: self.left,
+                    top: self.top,
+                    stride: 0,
+                    data: Box::new([]),
+                }
+            }
+        };
+        let y1 = self.height - self.rows().rev().position(covered).unwrap_or(0);
+        let rows = || self.rows().skip(y0).take(y1 - y0);
+        let x0 = rows()
+            .filter_map(|row| row.iter().position(|&c| c != 0))
+            .min()
+            .unwrap_or(0);
+        let x1 = rows()
+            .filter_map(|row| row.iter().rposition(|&c| c != 0))
+            .max()
+            .map_or(0, |x| x + 1);
+        let data: Vec<u8> = rows().flat_map(|row| row[x0..x1].iter().cloned()).collect();
+        GlyphBitmap {
+            width: x1 - x0,
+            height: y1 - y0,
+            left: self.left + x0 as i32,
+            top: self.top - y0 as i32,
+            stride: x1 - x0,
+            data: data.into_boxed_slice(),
+        }
+    }
+
+    /// Get copy of bitmap with rows padded so that the stride is a multiple of `alignment`
+    /// bytes, as needed by some texture upload APIs
+    ///
+    /// # Panics
+    /// Panics if `alignment` is zero
+    pub fn with_row_alignment(&self, alignment: usize) -> GlyphBitmap {
+        assert!(
+            alignment > 0,
+            "with_row_alignment: alignment must be non-zero"
+        );
+        let stride = self.width.div_ceil(alignment) * alignment;
+        let mut data = vec![0; stride * self.height];
+        for (dst, src) in data.chunks_mut(stride.max(1)).zip(self.rows()) {
+            dst[..src.len()].copy_from_slice(src);
+        }
+        GlyphBitmap {
+            width: self.width,
+            height: self.height,
+            left: self.left,
+            top: self.top,
+            stride,
+            data: data.into_boxed_slice(),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl GlyphBitmap {
+    /// Convert to a grayscale image, with coverage as the pixel value
+    pub fn to_gray_image(&self) -> image::GrayImage {
+        let data = self.rows().flatten().cloned().collect();
+        image::GrayImage::from_raw(self.width as u32, self.height as u32, data)
+            .expect("bitmap data doesn't match its size")
+    }
+
+    /// Save bitmap as a grayscale PNG image
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> image::ImageResult<()> {
+        self.to_gray_image()
+            .save_with_format(path, image::ImageFormat::Png)
+    }
 }
 
 /// Glyph outline information
@@ -44,312 +262,4295 @@ enum FaceTyp<'a> {
     CFF,
 }
 
+/// A table that is parsed on first use. Holds `None` once loaded if the table is absent or
+/// invalid
+type Lazy<T> = OnceLock<Option<T>>;
+
 /// A face within the OpenType font file. This face alone cannot be used to render glyphs -
-/// it must be scaled first
+/// it must be scaled first.
+///
+/// Only the tables needed to make sense of the face (`head`, `hhea`, `maxp`, and `loca` for
+/// TrueType outlines) are validated when the face is loaded. Other tables are parsed on first
+/// use, and a table that fails to parse is treated as absent
 pub struct Face<'a> {
+    sfnt_version: Tag,
+    options: ParseOptions,
+    /// Offset table followed by the table records
+    directory: &'a [u8],
     tables: HashMap<Tag, &'a [u8]>,
     head: head::Head<'a>,
     hhea: hhea::Hhea<'a>,
     maxp: maxp::Maxp<'a>,
-    hmtx: hmtx::Hmtx<'a>,
-    cmap: cmap::Cmap<'a>,
+    hmtx: Lazy<hmtx::Hmtx<'a>>,
+    cmap: Arc<Lazy<cmap::Cmap<'a>>>,
+    os2: Lazy<os2::Os2<'a>>,
+    post: Arc<Lazy<post::Post<'a>>>,
+    vmtx: Lazy<vmtx::Vmtx<'a>>,
+    vorg: Lazy<vorg::Vorg<'a>>,
+    math: Lazy<math::Math<'a>>,
+    meta: Lazy<meta::Meta<'a>>,
+    gdef: Lazy<gdef::Gdef<'a>>,
+    gpos: Lazy<gpos::Gpos<'a>>,
+    kern: Lazy<kern::Kern<'a>>,
+    name: Lazy<name::Name<'a>>,
+    hdmx: Lazy<hdmx::Hdmx<'a>>,
+    ltsh: Lazy<ltsh::Ltsh<'a>>,
+    jstf: Lazy<jstf::Jstf<'a>>,
+    fvar: Lazy<fvar::Fvar<'a>>,
+    colr: Lazy<colr::Colr<'a>>,
+    cpal: Lazy<cpal::Cpal<'a>>,
+    /// Normalized variation coordinates, in fvar axis order. Empty at the default instance
+    coords: Vec<f32>,
     typ: FaceTyp<'a>,
 }
 
 impl<'a> std::fmt::Debug for Face<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        f.debug_struct("Face")
-            .field("head", &self.head)
+        let mut s = f.debug_struct("Face");
+        s.field("head", &self.head)
             .field("hhea", &self.hhea)
-            .field("maxp", &self.maxp)
-            .field("cmap", &self.cmap)
-            .field("typ", &self.typ)
-            .finish()
+            .field("maxp", &self.maxp);
+        match self.cmap() {
+            Ok(cmap) => s.field("cmap", cmap),
+            Err(err) => s.field("cmap", &err),
+        };
+        s.field("typ", &self.typ).finish()
     }
 }
 
 impl<'a> Face<'a> {
     /// Scale face to get a `ScaledFace`
-    pub fn scale(&self, point_width: f32, point_height: f32, dpi_x: u16, dpi_y: u16) -> ScaledFace {
+    pub fn scale(
+        &self,
+        point_width: f32,
+        point_height: f32,
+        dpi_x: u16,
+        dpi_y: u16,
+    ) -> ScaledFace<'_, 'a> {
         let units_per_em = self.head.units_per_em() as f32;
         let pix_width = (point_width * dpi_x as f32) / (72.0 * units_per_em);
         let pix_height = (point_height * dpi_y as f32) / (72.0 * units_per_em);
         ScaledFace {
             scale_width: pix_width,
             scale_height: pix_height,
+            rounding: Rounding::None,
+            antialiasing: Antialiasing::Normal,
             face: self,
         }
     }
 
-    /// Get glyph ID for codepoint
+    /// Get glyph ID for codepoint. Codepoints the face doesn't cover give `GlyphID::NOTDEF`;
+    /// use `lookup_glyph_id` to tell them apart from codepoints mapped to `.notdef`
     pub fn get_glyph_id(&self, codepoint: u32) -> Result<GlyphID> {
-        self.cmap.get_glyph_id(codepoint)
+        self.cmap()?.get_glyph_id(codepoint)
     }
 
-    /// Get glyph information
-    fn get_glyph(&self, id: GlyphID) -> Result<Glyph> {
+    /// Get glyph ID for codepoint, or `None` if the character map doesn't cover it. This lets
+    /// fallback logic skip faces without the character, while respecting codepoints the face
+    /// explicitly maps to `.notdef`
+    pub fn lookup_glyph_id(&self, codepoint: u32) -> Result<Option<GlyphID>> {
+        self.cmap()?.lookup(codepoint)
+    }
+
+    /// Decode the location of every glyph in the loca table up front, so that loading glyphs
+    /// later doesn't have to. This is only worth it when many glyphs will be loaded. Does
+    /// nothing for faces with CFF outlines
+    pub fn precompute_glyph_index(&self) -> Result<()> {
         match self.typ {
-            FaceTyp::TrueType(ref loca, ref glyf) => loca
-                .get_offset(id)
-                .and_then(|off| glyf.glyph(off))
-                .map(|ttglyph| Glyph {
-                    outline: GlyphOutline::TrueType(ttglyph),
-                }),
+            FaceTyp::TrueType(ref loca, _) => loca.precompute(),
+            FaceTyp::CFF => Ok(()),
+        }
+    }
+
+    /// Check if glyph has an outline. Glyphs like the space have none
+    pub fn has_outline(&self, id: GlyphID) -> Result<bool> {
+        match self.typ {
+            FaceTyp::TrueType(ref loca, _) => loca.get_range(id).map(|range| !range.is_empty()),
             FaceTyp::CFF => Err(Error::Unimplemented("CFF support".to_owned())),
         }
     }
 
-    /// Load face information from data. The `offset` provided is the offset from the beginning
-    /// of the file to the Offset Table for the face
-    fn load(data: &[u8], offset: usize) -> Result<Face> {
-        let sfnt_version = get_tag(data, offset)?;
-        let num_tables = get_u16(data, offset + 4)? as usize;
-        let mut record_off = offset + 12;
-        let mut tables = HashMap::new();
-        for _ in 0..num_tables {
-            let tag = get_tag(data, record_off)?;
-            let table_off = get_u32(data, record_off + 8)? as usize;
-            let table_len = get_u32(data, record_off + 12)? as usize;
-            if table_off + table_len > data.len() {
-                return Err(Error::Invalid);
-            }
-            let table_data = &data[table_off..(table_off + table_len)];
-            tables.insert(tag, table_data);
-            record_off += 16;
+    /// Get bounding box of a glyph's outline in font units, with y increasing upwards, or
+    /// `None` if it has no outline
+    pub fn glyph_bounds(&self, id: GlyphID) -> Result<Option<Rect>> {
+        match self.get_glyph(id)?.outline {
+            GlyphOutline::TrueType(ref ttg) => Ok(ttg.bounds()),
         }
-        // Get the tables we need
-        let head = tables
-            .get(&Tag::from_str("head"))
-            .ok_or(Error::Invalid)
-            .and_then(|data| head::Head::load(data))?;
-        let hhea = tables
-            .get(&Tag::from_str("hhea"))
-            .ok_or(Error::Invalid)
-            .and_then(|data| hhea::Hhea::load(data))?;
-        let maxp = tables
-            .get(&Tag::from_str("maxp"))
-            .ok_or(Error::Invalid)
-            .and_then(|data| maxp::Maxp::load(data))?;
-        let hmtx = tables
-            .get(&Tag::from_str("hmtx"))
-            .ok_or(Error::Invalid)
-            .and_then(|data| {
-                hmtx::Hmtx::load(
-                    data,
-                    maxp.num_glyphs() as usize,
-                    hhea.num_of_h_metrics() as usize,
-                )
-            })?;
-        let cmap = tables
-            .get(&Tag::from_str("cmap"))
-            .ok_or(Error::Invalid)
-            .and_then(|data| cmap::Cmap::load(data))?;
-        let num_glyphs = maxp.num_glyphs() as usize;
-        let idx_to_loc_fmt = head.idx_to_loc_fmt()?;
-        let typ = match sfnt_version {
-            Tag(0x00010000) => {
-                let loca = tables
-                    .get(&Tag::from_str("loca"))
-                    .ok_or(Error::Invalid)
-                    .and_then(|data| loca::Loca::load(data, num_glyphs, idx_to_loc_fmt))?;
-                let glyf = tables
-                    .get(&Tag::from_str("glyf"))
-                    .ok_or(Error::Invalid)
-                    .map(|data| glyf::Glyf(data))?;
-                FaceTyp::TrueType(loca, glyf)
-            }
-            Tag(0x4F54544F) => FaceTyp::CFF,
-            _ => return Err(Error::Invalid),
-        };
-        Ok(Face {
-            tables: tables,
-            head: head,
-            hhea: hhea,
-            maxp: maxp,
-            hmtx: hmtx,
-            cmap: cmap,
-            typ: typ,
-        })
     }
-}
 
-/// Glyph data with scaling
-#[derive(Debug)]
-pub struct ScaledGlyph<'a> {
-    scale_width: f32,
-    scale_height: f32,
-    glyph: Glyph<'a>,
-}
+    /// Get points of a glyph's outline as stored in the font, in font units, for tools that
+    /// need the original point structure rather than path operations
+    pub fn glyph_points(&self, id: GlyphID) -> Result<GlyphPoints<'a>> {
+        match self.get_glyph(id)?.outline {
+            GlyphOutline::TrueType(ref ttg) => ttg.points(),
+        }
+    }
 
-impl<'a> ScaledGlyph<'a> {
-    pub fn render(&self) -> Result<GlyphBitmap> {
-        match self.glyph.outline {
-            GlyphOutline::TrueType(ref ttg) => ttg.render(self.scale_width, self.scale_height),
+    /// Get TrueType instructions of a glyph, for analyzing hinting coverage. Glyphs without
+    /// instructions give an empty slice
+    pub fn glyph_instructions(&self, id: GlyphID) -> Result<&'a [u8]> {
+        match self.get_glyph(id)?.outline {
+            GlyphOutline::TrueType(ref ttg) => ttg.instructions(),
         }
     }
-}
 
-/// We can't render glyphs for a face without appropriate scaling. So, only a `ScaledFace`
-/// allows rendering of glyphs. Multiple `ScaledFace` instances can be created for the same
-/// `Face`, at negligible extra cost
-#[derive(Debug)]
-pub struct ScaledFace<'a> {
-    scale_width: f32,
-    scale_height: f32,
-    face: &'a Face<'a>,
-}
+    /// Get components of a composite glyph with their flags. Simple glyphs have none
+    pub fn glyph_components(&self, id: GlyphID) -> Result<Vec<GlyphComponent>> {
+        match self.get_glyph(id)?.outline {
+            GlyphOutline::TrueType(ref ttg) => ttg.components(),
+        }
+    }
 
-impl<'a> ScaledFace<'a> {
-    /// Get glyph ID for codepoint
-    pub fn get_glyph_id(&self, codepoint: u32) -> Result<GlyphID> {
-        self.face.get_glyph_id(codepoint)
+    /// Check if the face has a usable character map. Faces without one, like some fonts
+    /// embedded in PDFs, can only be used with glyph IDs
+    pub fn has_charmap(&self) -> bool {
+        self.cmap()
+            .is_ok_and(|cmap| cmap.active_subtable().is_some())
     }
 
-    /// Get glyph information for glyph_id
-    pub fn get_glyph(&self, glyph_id: GlyphID) -> Result<ScaledGlyph> {
-        self.face.get_glyph(glyph_id).map(|glyph| ScaledGlyph {
-            scale_height: self.scale_height,
-            scale_width: self.scale_width,
-            glyph: glyph,
-        })
+    /// Check if the face has horizontal metrics. Faces without them give all glyphs the
+    /// maximum advance width from the hhea table (or one em, if that is 0)
+    pub fn has_horizontal_metrics(&self) -> bool {
+        self.tables.contains_key(&Tag::new(b"hmtx")) && self.hmtx().is_ok()
     }
-}
 
-/// An OpenType font file can either be a "font collection" (e.g. *.otc) file, or contain a
-/// single font. To provide a uniform interface, rype opens a font file as a `FontCollection`.
-/// The `FontCollection` can then be queried for individual `Face`s.
-pub struct FontCollection {
-    data: Box<[u8]>,
-    face_offsets: Vec<usize>,
-}
+    /// Get raw data for a table, if present in the face
+    pub fn table(&self, tag: Tag) -> Option<&'a [u8]> {
+        self.tables.get(&tag).cloned()
+    }
 
-impl std::fmt::Debug for FontCollection {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        f.debug_struct("FontCollection")
-            .field(
-                "faces",
-                &self
-                    .face_offsets
-                    .iter()
-                    .map(|off| Face::load(&self.data, *off))
-                    .collect::<Vec<Result<Face>>>(),
+    /// Iterate over all tables in the face, in no particular order
+    pub fn tables(&self) -> impl Iterator<Item = (Tag, &'a [u8])> + '_ {
+        self.tables.iter().map(|(&tag, &data)| (tag, data))
+    }
+
+    /// Iterate over (tag, checksum, offset, data) for entries in the table directory, in
+    /// directory order
+    fn table_records(&self) -> impl Iterator<Item = (Tag, u32, u32, &'a [u8])> + '_ {
+        let records = self.directory.get(12..).unwrap_or(&[]);
+        records.chunks(16).map(move |record| {
+            let tag = Tag(get_u32_unchecked(record, 0));
+            (
+                tag,
+                get_u32_unchecked(record, 4),
+                get_u32_unchecked(record, 8),
+                self.tables[&tag],
             )
-            .finish()
+        })
     }
-}
 
-impl FontCollection {
-    /// Load font collection from file
-    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Result<FontCollection> {
-        let data = std::fs::read(path)?.into_boxed_slice();
-        FontCollection::from_data(data)
+    /// Compute checksum for table data. The `checkSumAdjustment` field of `head` is skipped
+    fn table_checksum(&self, tag: Tag, data: &[u8]) -> u32 {
+        let checksum = table_checksum(data);
+        if tag == Tag::new(b"head") && data.len() >= 12 {
+            checksum.wrapping_sub(table_checksum(&data[8..12]))
+        } else {
+            checksum
+        }
     }
 
-    /// Load font collection from in-memory buffer. (Note: this creates a copy of the memory
-    /// buffer)
-    pub fn new_from(data: &[u8]) -> Result<FontCollection> {
-        FontCollection::from_data(data.into())
+    /// Get hash identifying the font data of the face, for deduplicating fonts loaded from
+    /// different files or collections. It covers the head and name tables, and the tag, length
+    /// and checksum of every table, so faces that differ only in their position within a file
+    /// get the same identity. The hash is stable across platforms and rype versions. It
+    /// doesn't include the variation instance
+    pub fn identity(&self) -> u64 {
+        let mut tags: Vec<_> = self.tables.keys().cloned().collect();
+        tags.sort();
+        let mut hash = FNV_OFFSET;
+        for tag in tags {
+            let data = self.tables[&tag];
+            let checksum = self.table_checksum(tag, data);
+            hash = fnv1a(hash, &tag.0.to_be_bytes());
+            hash = fnv1a(hash, &(data.len() as u32).to_be_bytes());
+            hash = fnv1a(hash, &checksum.to_be_bytes());
+        }
+        if let Some(head) = self.table(Tag::new(b"head")) {
+            // Skip checkSumAdjustment, which depends on the rest of the file
+            hash = fnv1a(hash, head.get(..8).unwrap_or(head));
+            hash = fnv1a(hash, head.get(12..).unwrap_or(&[]));
+        }
+        fnv1a(hash, self.table(Tag::new(b"name")).unwrap_or(&[]))
     }
 
-    /// Get face at given index
-    pub fn get_face(&self, idx: usize) -> Result<Face> {
-        self.face_offsets
-            .get(idx)
-            .ok_or(Error::FaceIndexOutOfBounds)
-            .and_then(|&off| Face::load(&self.data, off))
+    /// Check the head magic number, table checksums and `checkSumAdjustment`, and that tables
+    /// rype understands can be parsed. Returns an empty list if no problems were found. Faces
+    /// loaded from a `FontDataProvider` have no table directory, so checksums aren't checked
+    pub fn validate(&self) -> Vec<ValidationProblem> {
+        let mut problems = Vec::new();
+        if self.head.magic_number() != validate::HEAD_MAGIC {
+            problems.push(ValidationProblem::BadMagicNumber(self.head.magic_number()));
+        }
+        let mut font_checksum = table_checksum(self.directory);
+        for (tag, stored, _, data) in self.table_records() {
+            let computed = self.table_checksum(tag, data);
+            if computed != stored {
+                problems.push(ValidationProblem::TableChecksum {
+                    tag,
+                    stored,
+                    computed,
+                });
+            }
+            font_checksum = font_checksum.wrapping_add(computed);
+        }
+        let adjustment = validate::CHECKSUM_MAGIC.wrapping_sub(font_checksum);
+        if !self.directory.is_empty() && adjustment != self.head.checksum_adjustment() {
+            problems.push(ValidationProblem::ChecksumAdjustment {
+                stored: self.head.checksum_adjustment(),
+                computed: adjustment,
+            });
+        }
+        let parsed = [
+            (b"cmap", self.cmap().is_ok()),
+            (b"hmtx", self.hmtx().is_ok()),
+            (b"OS/2", self.os2().is_some()),
+            (b"post", self.post().is_some()),
+            (b"vmtx", self.vmtx().is_some()),
+            (b"VORG", self.vorg().is_some()),
+            (b"MATH", self.math().is_some()),
+            (b"meta", self.meta().is_some()),
+            (b"hdmx", self.hdmx().is_some()),
+            (b"LTSH", self.ltsh().is_some()),
+            (b"JSTF", self.jstf().is_some()),
+            (b"fvar", self.fvar().is_some()),
+            (b"COLR", self.colr().is_some()),
+            (b"CPAL", self.cpal().is_some()),
+        ];
+        for &(tag, ok) in parsed.iter() {
+            let tag = Tag::new(tag);
+            if !ok && self.tables.contains_key(&tag) {
+                problems.push(ValidationProblem::InvalidTable(tag));
+            }
+        }
+        problems
     }
 
-    /// Get number of faces in font collection
-    pub fn num_faces(&self) -> usize {
-        self.face_offsets.len()
+    /// Write the face out as a standalone font file. Faces from collections and Mac font
+    /// suitcases are extracted this way
+    pub fn to_bytes(&self) -> Vec<u8> {
+        FontBuilder::from_face(self).build()
     }
 
-    /// Load font collection from data
-    fn from_data(data: Box<[u8]>) -> Result<FontCollection> {
-        // Is this a font collection, or a single face?
-        let tag = get_tag(&data, 0)?;
-        let face_offsets = if tag == Tag::from_str("ttcf") {
-            let num_fonts = get_u32(&data, 8)? as usize;
-            let mut face_offsets = Vec::with_capacity(num_fonts);
-            let mut off = 12;
-            for _ in 0..num_fonts {
-                let face_off = get_u32(&data, off)? as usize;
-                face_offsets.push(face_off);
-                off += 12;
-            }
-            face_offsets
-        } else {
-            vec![0]
+    /// Write the face out as a font file with the tables, GSUB features, and glyph
+    /// instructions selected by `options` removed. The DSIG table is always removed, since the
+    /// signature no longer matches
+    pub fn strip(&self, options: &StripOptions) -> Result<Vec<u8>> {
+        strip::strip(self, options)
+    }
+
+    /// Build a font file containing only the glyphs needed to render `codepoints`, plus the
+    /// glyphs in `glyphs`. Glyphs reachable through GSUB substitutions and composite glyph
+    /// components are kept too. Glyphs are renumbered, so layout tables (GSUB, GPOS, etc.) are
+    /// not included in the subset. Only faces with TrueType outlines are supported for now
+    pub fn subset(&self, codepoints: &[u32], glyphs: &[GlyphID]) -> Result<Vec<u8>> {
+        subset::subset(self, codepoints, glyphs).map(|(data, _)| data)
+    }
+
+    /// Get entries for a PDF font descriptor for the face
+    pub fn pdf_descriptor(&self) -> PdfFontDescriptor {
+        pdf::descriptor(self)
+    }
+
+    /// Get PDF `Widths` array for a simple font, where `encoding[i]` is the codepoint for
+    /// character code `FirstChar + i`
+    pub fn pdf_widths(&self, encoding: &[u32]) -> Result<Vec<i32>> {
+        pdf::widths(self, encoding)
+    }
+
+    /// Build a subset of the face for embedding in a PDF document as a CIDFontType2 font,
+    /// along with its `CIDToGIDMap` and `W` array entries
+    pub fn pdf_subset(&self, glyphs: &[GlyphID]) -> Result<PdfSubset> {
+        pdf::pdf_subset(self, glyphs)
+    }
+
+    /// Get design-variation axes, if this is a variable font
+    pub fn variation_axes(&self) -> Vec<VariationAxis> {
+        self.fvar().map(|fvar| fvar.axes()).unwrap_or_default()
+    }
+
+    /// Select a variable font instance by user-space axis values, e.g. `(Tag::new(b"wght"),
+    /// 700.0)`. Values are clamped to the axis ranges, and axes not given are at their
+    /// defaults. Faces without variations are returned unchanged
+    pub fn with_variations(mut self, settings: &[(Tag, f32)]) -> Face<'a> {
+        let mut coords = self
+            .fvar()
+            .map(|fvar| fvar.normalize(settings))
+            .unwrap_or_default();
+        if let Some(Ok(avar)) = self.table(Tag::new(b"avar")).map(avar::Avar::load) {
+            avar.map(&mut coords);
+        }
+        if coords.iter().all(|&coord| coord == 0.0) {
+            coords.clear();
+        }
+        self.coords = coords;
+        self
+    }
+
+    /// Get normalized variation coordinates of the selected instance, from -1 to 1 in fvar
+    /// axis order. Empty at the default instance
+    pub fn variation_coords(&self) -> &[f32] {
+        &self.coords
+    }
+
+    /// Get font embedding permissions from the OS/2 table. Returns `None` if the face has no
+    /// OS/2 table
+    pub fn embedding_permissions(&self) -> Option<EmbeddingPermissions> {
+        self.os2().map(|os2| os2.embedding_permissions())
+    }
+
+    /// Get the Unicode blocks the OS/2 table declares as supported. This is a cheap heuristic;
+    /// use `lookup_glyph_id` for precise coverage. Returns `None` if the face has no OS/2 table
+    pub fn unicode_ranges(&self) -> Option<UnicodeRanges> {
+        self.os2().map(|os2| os2.unicode_ranges())
+    }
+
+    /// Check if the OS/2 table declares the given Unicode block as supported
+    pub fn supports_block(&self, block: UnicodeBlock) -> bool {
+        self.unicode_ranges()
+            .is_some_and(|ranges| ranges.supports_block(block))
+    }
+
+    /// Get the code pages the OS/2 table declares as supported. Returns `None` if the face has
+    /// no OS/2 table, or if it predates version 1
+    pub fn code_pages(&self) -> Option<CodePages> {
+        self.os2().and_then(|os2| os2.code_pages())
+    }
+
+    /// Get parameters of the GPOS `size` feature, giving the design size and intended size
+    /// range of optical size variants
+    pub fn size_params(&self) -> Option<SizeParams> {
+        let (params, legacy) =
+            otl::feature_params(self.table(Tag::new(b"GPOS"))?, Tag::new(b"size"))?;
+        let (design_size, subfamily_id, name_id, start, end) =
+            otl::size_params(params).or_else(|| otl::size_params(legacy))?;
+        Some(SizeParams {
+            design_size: design_size as f32 / 10.0,
+            subfamily_id,
+            subfamily_name: self
+                .name()
+                .and_then(|name| name.get(name_id))
+                .filter(|_| name_id != 0),
+            range_start: start as f32 / 10.0,
+            range_end: end as f32 / 10.0,
+        })
+    }
+
+    /// Get optical size the face was designed for, and the range of sizes it is intended for,
+    /// in points, as (design size, exclusive range start, inclusive range end). Taken from the
+    /// GPOS `size` feature, or else from the STAT `opsz` axis value matching the face
+    fn optical_size(&self) -> Option<(f32, f32, f32)> {
+        if let Some(params) = self.size_params() {
+            let (start, end) = match params.subfamily_id {
+                0 => (0.0, f32::INFINITY),
+                _ => (params.range_start, params.range_end),
+            };
+            return Some((params.design_size, start, end));
+        }
+        let stat = self.table(Tag::new(b"STAT")).map(stat::Stat::load)?.ok()?;
+        let values = stat.axis_values(Tag::new(b"opsz"));
+        // Static faces of a family often list the values of all faces, so pick the one named
+        // in the face's style
+        let value = match values.len() {
+            1 => values[0],
+            _ => {
+                let style = self.full_name()?;
+                *values.iter().find(|value| {
+                    self.name()
+                        .and_then(|name| name.get(value.name_id))
+                        .is_some_and(|name| style.contains(&name))
+                })?
+            }
         };
-        Ok(FontCollection {
-            data: data,
-            face_offsets: face_offsets,
+        Some((value.nominal, value.min, value.max))
+    }
+
+    /// Get name of a stylistic set feature (`ss01`-`ss20`) for user interfaces, e.g. "Sans serif
+    /// lowercase r"
+    pub fn stylistic_set_name(&self, tag: Tag) -> Option<String> {
+        let (params, _) = otl::feature_params(self.table(Tag::new(b"GSUB"))?, tag)?;
+        self.name()?.get(get_u16(params, 2).ok()?)
+    }
+
+    /// Get user interface strings and covered characters of a character variant feature
+    /// (`cv01`-`cv99`)
+    pub fn character_variant(&self, tag: Tag) -> Option<CharacterVariant> {
+        let (params, _) = otl::feature_params(self.table(Tag::new(b"GSUB"))?, tag)?;
+        let name = |name_id: u16| match name_id {
+            0 => None,
+            name_id => self.name().and_then(|name| name.get(name_id)),
+        };
+        let first_label = get_u16(params, 10).ok()?;
+        let num_labels = get_u16(params, 8).ok()?;
+        let num_chars = get_u16(params, 12).ok()? as usize;
+        Some(CharacterVariant {
+            label: name(get_u16(params, 2).ok()?),
+            tooltip: name(get_u16(params, 4).ok()?),
+            sample_text: name(get_u16(params, 6).ok()?),
+            parameter_labels: (0..num_labels)
+                .filter_map(|i| name(first_label.checked_add(i)?))
+                .collect(),
+            characters: (0..num_chars)
+                .filter_map(|i| {
+                    let off = 14 + i * 3;
+                    let bytes = params.get(off..off + 3)?;
+                    let c = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+                    std::char::from_u32(c)
+                })
+                .collect(),
         })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
+    /// Get family name from the name table. The typographic family name is preferred, as it
+    /// groups faces with more than the four basic styles (e.g. "Hack" rather than "Hack Bold")
+    pub fn family_name(&self) -> Option<String> {
+        self.name()?.family()
+    }
 
-    fn get_path(res: &str) -> PathBuf {
-        let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        buf.push("fonts");
-        buf.push(res);
-        buf
+    /// Get style name (e.g. "Bold Italic") from the name table, preferring the typographic
+    /// subfamily name
+    pub fn style_name(&self) -> Option<String> {
+        self.name()?.style()
     }
 
-    #[test]
-    fn test_firacode() {
-        let path = get_path("FiraCode-Regular.otf");
-        let fc = FontCollection::new(&path).unwrap();
-        assert_eq!(
-            &format!("{:?}", fc),
-            "FontCollection { faces: [Ok(Face { head: Head \
-             { units_per_em: 1950, xmin: -3556, ymin: -1001, xmax: 2385, ymax: 2401, \
-             lowest_rec_ppem: 3, index_to_loc_format: Off16 }, hhea: Hhea { ascender: 1800, \
-             descender: -600, num_of_h_metrics: 1746 }, maxp: Maxp { num_glyphs: 1746 }, \
-             cmap: Cmap { subtables: [Subtable { platform_id: 0, encoding_id: 3, format: Ok(4) \
-             }, Subtable { platform_id: 3, encoding_id: 1, format: Ok(4) }, Subtable { \
-             platform_id: 0, encoding_id: 4, format: Ok(12) }, Subtable { platform_id: 3, \
-             encoding_id: 10, format: Ok(12) }], active: Some(Subtable { platform_id: 3, \
-             encoding_id: 10, format: Ok(12) }) }, typ: CFF })] }"
-        );
+    /// Get full name of the face from the name table
+    pub fn full_name(&self) -> Option<String> {
+        self.name().and_then(|name| name.get(name::FULL_NAME))
     }
 
-    #[test]
-    fn test_firacode_tables() {
-        let path = get_path("FiraCode-Regular.otf");
-        let fc = FontCollection::new(&path).unwrap();
-        let face = fc.get_face(0).unwrap();
-        let tables = face.tables;
-        let mut names = tables
-            .keys()
-            .map(|k| format!("{}", k))
-            .collect::<Vec<String>>();
-        names.sort();
-        assert_eq!(
-            &names.join(", "),
-            "CFF , GDEF, GPOS, GSUB, OS/2, cmap, head, hhea, \
-             hmtx, maxp, name, post"
-        );
+    /// Get PostScript name of the face (e.g. "Hack-Regular") from the name table
+    pub fn postscript_name(&self) -> Option<String> {
+        self.name().and_then(|name| name.get(name::POSTSCRIPT_NAME))
     }
 
-    #[test]
-    fn test_firacode_cmap() {
-        let path = get_path("FiraCode-Regular.otf");
-        let fc = FontCollection::new(&path).unwrap();
-        let face = fc.get_face(0).unwrap();
-        assert_eq!(face.get_glyph_id('A' as u32).unwrap(), GlyphID(1));
-        assert_eq!(face.get_glyph_id('B' as u32).unwrap(), GlyphID(13));
-        assert_eq!(face.get_glyph_id('C' as u32).unwrap(), GlyphID(14));
-        assert_eq!(face.get_glyph_id('D' as u32).unwrap(), GlyphID(20));
-        assert_eq!(face.get_glyph_id('E' as u32).unwrap(), GlyphID(24));
-        assert_eq!(face.get_glyph_id('F' as u32).unwrap(), GlyphID(34));
-        assert_eq!(face.get_glyph_id('a' as u32).unwrap(), GlyphID(134));
-        assert_eq!(face.get_glyph_id('>' as u32).unwrap(), GlyphID(1171));
-        assert_eq!(face.get_glyph_id('=' as u32).unwrap(), GlyphID(1169));
+    /// Get height of lowercase letters above the baseline, in font units. Uses OS/2
+    /// `sxHeight` when set, and otherwise measures the outline of 'x'
+    pub fn x_height(&self) -> Option<i16> {
+        self.os2()
+            .and_then(|os2| os2.x_height())
+            .filter(|&height| height > 0)
+            .or_else(|| self.glyph_top('x'))
+    }
+
+    /// Get height of capital letters above the baseline, in font units. Uses OS/2
+    /// `sCapHeight` when set, and otherwise measures the outline of 'H'
+    pub fn cap_height(&self) -> Option<i16> {
+        self.os2()
+            .and_then(|os2| os2.cap_height())
+            .filter(|&height| height > 0)
+            .or_else(|| self.glyph_top('H'))
+    }
+
+    /// Get top of the outline of the glyph for `c`, if the face has one
+    fn glyph_top(&self, c: char) -> Option<i16> {
+        let id = self.lookup_glyph_id(c as u32).ok()??;
+        match self.get_glyph(id).ok()?.outline {
+            GlyphOutline::TrueType(ref ttg) => ttg.bounds().map(|rect| rect.max.y as i16),
+        }
+    }
+
+    /// Get weight, stretch, and slant of the face. OS/2 `usWeightClass`, `usWidthClass`, and
+    /// `fsSelection` are preferred. `macStyle` in the head table fills in when OS/2 values are
+    /// missing or out of range, or adds italic when `fsSelection` doesn't claim the face is
+    /// regular
+    pub fn style(&self) -> FaceStyle {
+        let mac_style = self.head.mac_style();
+        let (weight_class, width_class, fs_selection) = match self.os2() {
+            Some(os2) => (os2.weight_class(), os2.width_class(), os2.fs_selection()),
+            None => (0, 0, 0),
+        };
+        let weight = match weight_class {
+            // Some old fonts use a 1-9 scale
+            1..=9 => weight_class * 100,
+            10..=1000 => weight_class,
+            _ if (fs_selection & 0x0020 != 0) || (mac_style & 0x0001 != 0) => 700,
+            _ => 400,
+        };
+        let stretch = match width_class {
+            1..=9 => width_class,
+            _ if mac_style & 0x0020 != 0 => 3,
+            _ if mac_style & 0x0040 != 0 => 7,
+            _ => 5,
+        };
+        let italic =
+            fs_selection & 0x0201 != 0 || (fs_selection & 0x0040 == 0 && mac_style & 0x0002 != 0);
+        FaceStyle {
+            weight,
+            stretch,
+            italic,
+        }
+    }
+
+    /// Get weight class, from 1 to 1000, with 400 being regular and 700 bold
+    pub fn weight(&self) -> u16 {
+        self.style().weight
+    }
+
+    /// Check whether the face is italic or oblique
+    pub fn is_italic(&self) -> bool {
+        self.style().italic
+    }
+
+    /// Get PostScript name of glyph, if the face provides glyph names
+    pub fn glyph_name(&self, id: GlyphID) -> Option<&'a str> {
+        self.post().and_then(|post| post.glyph_name(id))
+    }
+
+    /// Get glyph ID for PostScript glyph name, if the face provides glyph names
+    pub fn glyph_id_by_name(&self, name: &str) -> Option<GlyphID> {
+        self.post().and_then(|post| post.glyph_id_by_name(name))
+    }
+
+    /// Get italic angle in counter-clockwise degrees from the vertical. Returns `None` if the
+    /// face has no post table
+    pub fn italic_angle(&self) -> Option<f32> {
+        self.post().map(|post| post.italic_angle())
+    }
+
+    /// Check whether the post table marks the face as fixed-pitch. Returns `None` if the face
+    /// has no post table
+    pub fn is_fixed_pitch(&self) -> Option<bool> {
+        self.post().map(|post| post.is_fixed_pitch())
+    }
+
+    /// Check whether all glyphs in the face have the same advance width. Faces that declare
+    /// themselves monospaced (via post or the OS/2 PANOSE classification) are still verified
+    /// against hmtx, but may contain double-width glyphs and a small number (< 1%) of stray
+    /// advances
+    pub fn is_monospace(&self) -> bool {
+        let declared = self.post().is_some_and(|post| post.is_fixed_pitch())
+            || self.os2().is_some_and(|os2| os2.is_panose_monospaced());
+        let hmtx = match self.hmtx() {
+            Ok(hmtx) => hmtx,
+            Err(_) => return declared,
+        };
+        let mut counts = HashMap::new();
+        for advance in hmtx.advances().filter(|&adv| adv != 0) {
+            *counts.entry(advance).or_insert(0usize) += 1;
+        }
+        let (width, total) = match counts.iter().max_by_key(|&(_, &count)| count) {
+            Some((&width, _)) => (width, counts.values().sum::<usize>()),
+            None => return declared,
+        };
+        if !declared {
+            return counts.len() == 1;
+        }
+        let double = width.checked_mul(2).and_then(|double| counts.get(&double));
+        let matching = counts[&width] + double.unwrap_or(&0);
+        matching * 100 >= total * 99
+    }
+
+    /// Get line metrics in font units, from the table indicated by `source`. If the face has
+    /// no OS/2 table, hhea metrics are always used
+    pub fn line_metrics(&self, source: MetricsSource) -> LineMetrics {
+        let hhea = LineMetrics {
+            ascender: self.hhea.ascender() as f32,
+            descender: self.hhea.descender() as f32,
+            line_gap: self.hhea.line_gap() as f32,
+        };
+        let os2 = match self.os2() {
+            Some(os2) => os2,
+            None => return hhea,
+        };
+        let source = match source {
+            MetricsSource::Auto if os2.use_typo_metrics() => MetricsSource::Typo,
+            MetricsSource::Auto if os2.win_ascent() != 0 || os2.win_descent() != 0 => {
+                MetricsSource::Win
+            }
+            MetricsSource::Auto => MetricsSource::Hhea,
+            source => source,
+        };
+        match source {
+            MetricsSource::Typo => LineMetrics {
+                ascender: os2.typo_ascender() as f32,
+                descender: os2.typo_descender() as f32,
+                line_gap: os2.typo_line_gap() as f32,
+            },
+            MetricsSource::Win => {
+                let ascender = os2.win_ascent() as f32;
+                let descender = -(os2.win_descent() as f32);
+                LineMetrics {
+                    ascender,
+                    descender,
+                    line_gap: (hhea.line_height() - (ascender - descender)).max(0.0),
+                }
+            }
+            _ => hhea,
+        }
+    }
+
+    /// Get horizontal advance and left side bearing for a glyph, in font units. See
+    /// `has_horizontal_metrics` for faces without an hmtx table
+    pub fn horizontal_metrics(&self, id: GlyphID) -> Result<HorizontalMetrics> {
+        self.hmtx()?.get_metrics(id)
+    }
+
+    /// Get vertical advance and top side bearing for a glyph, in font units
+    pub fn vertical_metrics(&self, id: GlyphID) -> Result<VerticalMetrics> {
+        self.vmtx()
+            .ok_or(Error::NoVerticalMetrics)
+            .and_then(|vmtx| vmtx.get_metrics(id))
+    }
+
+    /// Get y coordinate of the vertical origin of a glyph from the VORG table, in font units.
+    /// Returns `None` if the face has no VORG table
+    pub fn vertical_origin(&self, id: GlyphID) -> Option<i16> {
+        self.vorg().map(|vorg| vorg.vert_origin_y(id))
+    }
+
+    /// Get handle to the MATH table, for laying out mathematical formulas. Returns `None` if
+    /// the face has no MATH table
+    pub fn math(&self) -> Option<&Math<'a>> {
+        self.lazy(&self.math, b"MATH", math::Math::load)
+    }
+
+    /// Get number of color palettes in the CPAL table, or 0 if the face has none
+    pub fn num_palettes(&self) -> u16 {
+        self.cpal().map_or(0, |cpal| cpal.num_palettes())
+    }
+
+    /// Get palette to use by default for drawing text over `background`, from the CPAL
+    /// palette type flags. This lets icon fonts adapt to dark mode. Returns 0 if the face
+    /// doesn't mark palettes as usable with light or dark backgrounds
+    pub fn default_palette(&self, background: Color) -> u16 {
+        self.cpal()
+            .map_or(0, |cpal| cpal.default_palette(background))
+    }
+
+    /// Get COLR layers of a color glyph from bottom to top, with colors from palette
+    /// `palette`. Entries missing from the palette are given the foreground color. Returns an
+    /// empty list if the glyph has no color layers
+    pub fn color_layers(&self, id: GlyphID, palette: u16) -> Vec<ColorLayer> {
+        let layers = match self.colr() {
+            Some(colr) => colr.layers(id),
+            None => return Vec::new(),
+        };
+        let cpal = self.cpal();
+        layers
+            .into_iter()
+            .map(|(glyph_id, entry)| ColorLayer {
+                glyph_id,
+                color: cpal.and_then(|cpal| cpal.color(palette, entry)),
+            })
+            .collect()
+    }
+
+    /// Get handle to the JSTF table, with lookups to apply for justification. Returns `None`
+    /// if the face has no JSTF table
+    pub fn jstf(&self) -> Option<&Jstf<'a>> {
+        self.lazy(&self.jstf, b"JSTF", jstf::Jstf::load)
+    }
+
+    /// Get ScriptLangTags (e.g. "Hans", "ja-Jpan") for the languages the face was designed
+    /// for, from the meta table
+    pub fn design_languages(&self) -> Vec<&'a str> {
+        self.meta()
+            .map(|meta| meta.script_lang_tags(Tag::new(b"dlng")))
+            .unwrap_or_default()
+    }
+
+    /// Get ScriptLangTags for the languages the face can render, from the meta table
+    pub fn supported_languages(&self) -> Vec<&'a str> {
+        self.meta()
+            .map(|meta| meta.script_lang_tags(Tag::new(b"slng")))
+            .unwrap_or_default()
+    }
+
+    /// Check if the face positions combining marks with GPOS mark attachment (the `mark`
+    /// feature)
+    pub fn has_mark_attachment(&self) -> bool {
+        self.table(Tag::new(b"GPOS"))
+            .is_some_and(|gpos| otl::has_feature(gpos, Tag::new(b"mark")))
+    }
+
+    /// Get layout class of glyph (base, mark, etc.) from the GDEF table. Glyphs are of unknown
+    /// class if the face has no glyph class definitions
+    pub fn glyph_class(&self, glyph_id: GlyphID) -> GlyphClass {
+        self.gdef()
+            .map_or(GlyphClass::Unknown, |gdef| gdef.glyph_class(glyph_id))
+    }
+
+    /// Get glyphs of a layout class from the GDEF table, e.g. all marks. Only glyphs explicitly
+    /// listed in the glyph class definitions are included
+    pub fn glyphs_with_class(&self, class: GlyphClass) -> Vec<GlyphID> {
+        self.gdef()
+            .map(|gdef| gdef.glyphs_with_class(class))
+            .unwrap_or_default()
+    }
+
+    /// Get x coordinates of carets between components of a ligature glyph, in font units, from
+    /// the GDEF ligature caret list. This lets editors place the cursor inside ligatures like
+    /// "ffi". Glyphs that aren't ligatures, or have no carets, give an empty list
+    pub fn ligature_carets(&self, id: GlyphID) -> Vec<f32> {
+        self.carets(id, 0)
+            .into_iter()
+            .map(|(units, _)| units)
+            .collect()
+    }
+
+    /// Get ligature carets as (x coordinate in font units, Device table adjustment in pixels)
+    /// for `ppem`, resolving carets placed on outline points
+    fn carets(&self, id: GlyphID, ppem: u16) -> Vec<(f32, i16)> {
+        let carets = match self.gdef() {
+            Some(gdef) => gdef.ligature_carets(id, ppem, &self.coords),
+            None => return Vec::new(),
+        };
+        carets
+            .into_iter()
+            .filter_map(|caret| match caret {
+                gdef::CaretValue::Coordinate(units, pixels) => Some((units, pixels)),
+                gdef::CaretValue::Point(idx) => self
+                    .glyph_points(id)
+                    .ok()?
+                    .nth(idx as usize)
+                    .map(|point| (point.x as f32, 0)),
+            })
+            .collect()
+    }
+
+    /// Get horizontal kerning between a pair of glyphs, in font units. Pair adjustments of the
+    /// GPOS `kern` feature are used if the face has a GPOS table, and the legacy kern table
+    /// otherwise. Pairs without kerning give 0
+    pub fn kerning(&self, left: GlyphID, right: GlyphID) -> i16 {
+        match self.gpos() {
+            Some(gpos) => gpos
+                .kerning(left, right, &self.instance(0))
+                .map(|(units, _)| units.round() as i16),
+            None => self.kern().and_then(|kern| kern.kerning(left, right)),
+        }
+        .unwrap_or(0)
+    }
+
+    /// Apply GSUB lookups of `features` for `script` and `lang` to a glyph sequence, for callers
+    /// that itemize and map text themselves. Lookups are applied in lookup list order, along
+    /// with the language system's required feature. Only single, multiple, alternate and
+    /// ligature substitutions are applied; contextual lookups are skipped. Lookup flags that
+    /// skip glyphs by their GDEF class (e.g. ignoring marks) are honoured. Glyphs are returned
+    /// unchanged if the face has no GSUB table
+    pub fn apply_gsub(
+        &self,
+        glyphs: &[GlyphID],
+        features: &[Tag],
+        script: Tag,
+        lang: Option<Tag>,
+    ) -> Vec<GlyphID> {
+        let mut glyphs = glyphs.to_vec();
+        if let Some(table) = self.table(Tag::new(b"GSUB")) {
+            if let Ok(gsub) = gsub::Gsub::load(table) {
+                let indices = otl::lookup_indices(table, script, lang, features);
+                gsub.apply(&indices, &mut glyphs, self.gdef());
+            }
+        }
+        glyphs
+    }
+
+    /// Get GPOS adjustments of a glyph sequence from lookups of `features` for `script` and
+    /// `lang`, in font units. Only single and pair adjustments are applied; attachment and
+    /// contextual lookups are skipped. Lookup flags are honoured as with `apply_gsub`.
+    /// Adjustments are zero if the face has no GPOS table
+    pub fn apply_gpos(
+        &self,
+        glyphs: &[GlyphID],
+        features: &[Tag],
+        script: Tag,
+        lang: Option<Tag>,
+    ) -> Vec<GlyphAdjustment> {
+        match (self.table(Tag::new(b"GPOS")), self.gpos()) {
+            (Some(table), Some(gpos)) => {
+                let indices = otl::lookup_indices(table, script, lang, features);
+                gpos.apply(&indices, glyphs, &self.instance(0), self.gdef())
+            }
+            _ => vec![GlyphAdjustment::default(); glyphs.len()],
+        }
+    }
+
+    /// Get kerning matrices of class-based pair adjustment subtables of the GPOS `kern`
+    /// feature, in lookup order. This allows exporting class kerning wholesale rather than
+    /// querying pair by pair. Pairs kerned by several subtables add up, as with `kerning`
+    pub fn kerning_class_matrices(&self) -> Vec<KernClassMatrix> {
+        self.gpos()
+            .map(|gpos| {
+                gpos.kerning_class_matrices(&self.instance(0), self.maxp.num_glyphs() as u32)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get recommended subscript size and offset from the OS/2 table, in font units
+    pub fn subscript_metrics(&self) -> Option<ScriptMetrics> {
+        self.os2().map(|os2| {
+            let (x_size, y_size, x_offset, y_offset) = os2.subscript();
+            ScriptMetrics {
+                x_size: x_size as f32,
+                y_size: y_size as f32,
+                x_offset: x_offset as f32,
+                y_offset: -(y_offset as f32),
+            }
+        })
+    }
+
+    /// Get recommended superscript size and offset from the OS/2 table, in font units
+    pub fn superscript_metrics(&self) -> Option<ScriptMetrics> {
+        self.os2().map(|os2| {
+            let (x_size, y_size, x_offset, y_offset) = os2.superscript();
+            ScriptMetrics {
+                x_size: x_size as f32,
+                y_size: y_size as f32,
+                x_offset: x_offset as f32,
+                y_offset: y_offset as f32,
+            }
+        })
+    }
+
+    /// Get underline position and thickness from the post table, in font units
+    pub fn underline_metrics(&self) -> Option<DecorationMetrics> {
+        self.post().map(|post| DecorationMetrics {
+            position: post.underline_position(),
+            thickness: post.underline_thickness(),
+        })
+    }
+
+    /// Get strikeout position and thickness from the OS/2 table, in font units
+    pub fn strikeout_metrics(&self) -> Option<DecorationMetrics> {
+        self.os2().map(|os2| DecorationMetrics {
+            position: os2.strikeout_position(),
+            thickness: os2.strikeout_size(),
+        })
+    }
+
+    /// Get advance of space character `c` in font units, or `None` if `c` isn't a space. If the
+    /// face has no glyph for it, the advance is synthesized so that the space isn't drawn as
+    /// `.notdef`: monospaced faces use their cell width, and others the advance of a related
+    /// character or a fraction of the em
+    pub fn space_advance(&self, c: char) -> Result<Option<SpaceAdvance>> {
+        let width = match fallback::space_width(c) {
+            Some(width) => width,
+            None => return Ok(None),
+        };
+        if let Some(advance) = self.char_advance(c)? {
+            return Ok(Some(SpaceAdvance {
+                advance: advance as f32,
+                synthesized: false,
+            }));
+        }
+        let advance = if self.is_monospace() {
+            let cell = match self.char_advance(' ')?.or(self.char_advance('0')?) {
+                Some(advance) => advance as f32,
+                None => self.os2().map_or(0, |os2| os2.x_avg_char_width()) as f32,
+            };
+            if width::is_wide(c) {
+                cell * 2.0
+            } else {
+                cell
+            }
+        } else {
+            let like = match width.like {
+                Some(like) => self.char_advance(like)?,
+                None => None,
+            };
+            like.map_or(self.head.units_per_em() as f32 * width.em, f32::from)
+        };
+        Ok(Some(SpaceAdvance {
+            advance,
+            synthesized: true,
+        }))
+    }
+
+    /// Get advance of the glyph for `c` in font units, if the face has one
+    fn char_advance(&self, c: char) -> Result<Option<u16>> {
+        match self.lookup_glyph_id(c as u32)? {
+            Some(glyph_id) if glyph_id != GlyphID::NOTDEF => {
+                self.hmtx()?.advance(glyph_id).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Get table, parsing it with `load` on first use
+    fn lazy<'s, T, F>(&'s self, cell: &'s Lazy<T>, tag: &[u8; 4], load: F) -> Option<&'s T>
+    where
+        F: FnOnce(&'a [u8]) -> Result<T>,
+    {
+        cell.get_or_init(|| {
+            let tag = Tag::new(tag);
+            trace_span!("parse_table", tag = %tag);
+            let data = self.tables.get(&tag)?;
+            let table = load(data);
+            trace_event!(bytes = data.len(), ok = table.is_ok(), "parsed table");
+            table.ok()
+        })
+        .as_ref()
+    }
+
+    /// Get table, parsing it with `load` on first use, with an error saying why the table
+    /// can't be used if it is missing or invalid
+    fn required<'s, T, F>(&'s self, cell: &'s Lazy<T>, tag: &[u8; 4], load: F) -> Result<&'s T>
+    where
+        F: Fn(&'a [u8]) -> Result<T>,
+    {
+        if let Some(table) = self.lazy(cell, tag, &load) {
+            return Ok(table);
+        }
+        // Failed parses aren't kept, so parse again to get the error
+        let tag = Tag::new(tag);
+        let data = self.tables.get(&tag).ok_or(Error::MissingTable(tag))?;
+        Err(load(data)
+            .err()
+            .map_or(Error::malformed("table failed to parse"), |err| {
+                err.in_table(tag)
+            }))
+    }
+
+    fn cmap(&self) -> Result<&cmap::Cmap<'a>> {
+        self.required(&self.cmap, b"cmap", cmap::Cmap::load)
+    }
+
+    fn hmtx(&self) -> Result<&hmtx::Hmtx<'a>> {
+        let num_glyphs = self.maxp.num_glyphs() as usize;
+        let num_h_metrics = self.hhea.num_of_h_metrics() as usize;
+        let strict = self.options.strict;
+        if !self.tables.contains_key(&Tag::new(b"hmtx")) {
+            // Fonts addressed only by glyph ID may leave out metrics
+            let advance = match self.hhea.advance_width_max() {
+                0 => self.head.units_per_em(),
+                advance => advance,
+            };
+            let hmtx = self
+                .hmtx
+                .get_or_init(|| Some(hmtx::Hmtx::uniform(num_glyphs, advance)));
+            return hmtx.as_ref().ok_or(Error::MissingTable(Tag::new(b"hmtx")));
+        }
+        self.required(&self.hmtx, b"hmtx", |data| {
+            hmtx::Hmtx::load(data, num_glyphs, num_h_metrics, strict)
+        })
+    }
+
+    fn os2(&self) -> Option<&os2::Os2<'a>> {
+        self.lazy(&self.os2, b"OS/2", os2::Os2::load)
+    }
+
+    fn post(&self) -> Option<&post::Post<'a>> {
+        self.lazy(&self.post, b"post", post::Post::load)
+    }
+
+    fn vmtx(&self) -> Option<&vmtx::Vmtx<'a>> {
+        let num_glyphs = self.maxp.num_glyphs() as usize;
+        let vhea = self.tables.get(&Tag::new(b"vhea"));
+        self.lazy(&self.vmtx, b"vmtx", |data| {
+            let vhea = vhea
+                .ok_or(Error::MissingTable(Tag::new(b"vhea")))
+                .and_then(|data| vhea::Vhea::load(data))?;
+            vmtx::Vmtx::load(data, num_glyphs, vhea.num_of_long_ver_metrics() as usize)
+        })
+    }
+
+    fn vorg(&self) -> Option<&vorg::Vorg<'a>> {
+        self.lazy(&self.vorg, b"VORG", vorg::Vorg::load)
+    }
+
+    fn meta(&self) -> Option<&meta::Meta<'a>> {
+        self.lazy(&self.meta, b"meta", meta::Meta::load)
+    }
+
+    fn gdef(&self) -> Option<&gdef::Gdef<'a>> {
+        self.lazy(&self.gdef, b"GDEF", gdef::Gdef::load)
+    }
+
+    fn colr(&self) -> Option<&colr::Colr<'a>> {
+        self.lazy(&self.colr, b"COLR", colr::Colr::load)
+    }
+
+    fn cpal(&self) -> Option<&cpal::Cpal<'a>> {
+        self.lazy(&self.cpal, b"CPAL", cpal::Cpal::load)
+    }
+
+    /// Get size and instance for adjusting GPOS values
+    fn instance(&self, ppem: u16) -> gpos::Instance<'a, '_> {
+        gpos::Instance {
+            ppem,
+            store: self
+                .gdef()
+                .filter(|_| !self.coords.is_empty())
+                .and_then(|gdef| gdef.variation_store()),
+            coords: &self.coords,
+        }
+    }
+
+    fn gpos(&self) -> Option<&gpos::Gpos<'a>> {
+        self.lazy(&self.gpos, b"GPOS", gpos::Gpos::load)
+    }
+
+    fn kern(&self) -> Option<&kern::Kern<'a>> {
+        self.lazy(&self.kern, b"kern", kern::Kern::load)
+    }
+
+    fn name(&self) -> Option<&name::Name<'a>> {
+        self.lazy(&self.name, b"name", name::Name::load)
+    }
+
+    fn hdmx(&self) -> Option<&hdmx::Hdmx<'a>> {
+        let num_glyphs = self.maxp.num_glyphs() as usize;
+        self.lazy(&self.hdmx, b"hdmx", |data| {
+            hdmx::Hdmx::load(data, num_glyphs)
+        })
+    }
+
+    fn ltsh(&self) -> Option<&ltsh::Ltsh<'a>> {
+        self.lazy(&self.ltsh, b"LTSH", ltsh::Ltsh::load)
+    }
+
+    fn fvar(&self) -> Option<&fvar::Fvar<'a>> {
+        self.lazy(&self.fvar, b"fvar", fvar::Fvar::load)
+    }
+
+    /// Get glyph information
+    fn get_glyph(&self, id: GlyphID) -> Result<Glyph<'a>> {
+        match self.typ {
+            FaceTyp::TrueType(ref loca, ref glyf) => loca
+                .get_range(id)
+                .and_then(|range| glyf.glyph(range))
+                .map(|ttglyph| Glyph {
+                    outline: GlyphOutline::TrueType(ttglyph),
+                }),
+            FaceTyp::CFF => Err(Error::Unimplemented("CFF support".to_owned())),
+        }
+    }
+
+    /// Load face information from data. The `offset` provided is the offset from the beginning
+    /// of the file to the Offset Table for the face
+    fn load(data: &[u8], offset: usize, options: ParseOptions) -> Result<Face<'_>> {
+        Face::load_shared(data, offset, options, &mut SharedTables::default())
+    }
+
+    /// Load face information from data, re-using parsed tables from `shared` if other faces in
+    /// the collection point to the same table data
+    fn load_shared(
+        data: &'a [u8],
+        offset: usize,
+        options: ParseOptions,
+        shared: &mut SharedTables<'a>,
+    ) -> Result<Face<'a>> {
+        trace_span!("load_face", offset = offset);
+        let (sfnt_version, directory, tables) = read_directory(data, offset, options)?;
+        Face::from_tables(sfnt_version, directory, tables, options, shared)
+    }
+
+    /// Load face from a provider of table data. Such faces have no table directory, so
+    /// validation skips checksums
+    pub fn from_provider<P: FontDataProvider + ?Sized>(
+        provider: &'a P,
+        options: ParseOptions,
+    ) -> Result<Face<'a>> {
+        let tables = provider
+            .tags()
+            .into_iter()
+            .filter_map(|tag| provider.table(tag).map(|data| (tag, data)))
+            .collect();
+        Face::from_tables(
+            provider.sfnt_version(),
+            &[],
+            tables,
+            options,
+            &mut SharedTables::default(),
+        )
+    }
+
+    /// Load face information from table data, given the data for the table directory
+    fn from_tables(
+        sfnt_version: Tag,
+        directory: &'a [u8],
+        tables: HashMap<Tag, &'a [u8]>,
+        options: ParseOptions,
+        shared: &mut SharedTables<'a>,
+    ) -> Result<Face<'a>> {
+        // Get the tables we need
+        let head = load_required(&tables, b"head", head::Head::load)?;
+        let hhea = load_required(&tables, b"hhea", hhea::Hhea::load)?;
+        let maxp = load_required(&tables, b"maxp", maxp::Maxp::load)?;
+        let cmap = SharedTables::get(&mut shared.cmap, tables.get(&Tag::new(b"cmap")));
+        let post = SharedTables::get(&mut shared.post, tables.get(&Tag::new(b"post")));
+        let num_glyphs = maxp.num_glyphs() as usize;
+        let idx_to_loc_fmt = head.idx_to_loc_fmt()?;
+        let typ = match sfnt_version {
+            Tag(0x00010000) | Tag(0x74727565) => {
+                let loca = load_required(&tables, b"loca", |data| {
+                    loca::Loca::load(data, num_glyphs, idx_to_loc_fmt)
+                })?;
+                let glyf = load_required(&tables, b"glyf", |data| Ok(glyf::Glyf(data)))?;
+                FaceTyp::TrueType(loca, glyf)
+            }
+            Tag(0x4F54544F) => FaceTyp::CFF,
+            _ => return Err(Error::UnknownFormat),
+        };
+        Ok(Face {
+            sfnt_version,
+            options,
+            directory,
+            tables: tables,
+            head: head,
+            hhea: hhea,
+            maxp: maxp,
+            hmtx: Lazy::new(),
+            cmap: cmap,
+            os2: Lazy::new(),
+            post,
+            vmtx: Lazy::new(),
+            vorg: Lazy::new(),
+            math: Lazy::new(),
+            meta: Lazy::new(),
+            gdef: Lazy::new(),
+            gpos: Lazy::new(),
+            kern: Lazy::new(),
+            name: Lazy::new(),
+            hdmx: Lazy::new(),
+            ltsh: Lazy::new(),
+            jstf: Lazy::new(),
+            fvar: Lazy::new(),
+            colr: Lazy::new(),
+            cpal: Lazy::new(),
+            coords: Vec::new(),
+            typ: typ,
+        })
+    }
+}
+
+/// Parse a table the face can't be loaded without
+fn load_required<'a, T, F>(tables: &HashMap<Tag, &'a [u8]>, tag: &[u8; 4], load: F) -> Result<T>
+where
+    F: FnOnce(&'a [u8]) -> Result<T>,
+{
+    let tag = Tag::new(tag);
+    let data = tables.get(&tag).ok_or(Error::MissingTable(tag))?;
+    load(data).map_err(|err| err.in_table(tag))
+}
+
+/// Lazily-parsed tables that can be shared between faces of a collection, keyed by the
+/// address and length of the table data. The cache borrows the collection's data for `'a`, so
+/// the data can't change or be freed while it is alive, and equal keys always refer to the
+/// same bytes. Only tables that do non-trivial work when loading are shared
+#[derive(Default)]
+struct SharedTables<'a> {
+    cmap: HashMap<(usize, usize), Arc<Lazy<cmap::Cmap<'a>>>>,
+    post: HashMap<(usize, usize), Arc<Lazy<post::Post<'a>>>>,
+}
+
+impl<'a> SharedTables<'a> {
+    /// Get table slot for `data` from cache, or insert an empty one. Faces without the table
+    /// get a slot of their own
+    fn get<T>(
+        cache: &mut HashMap<(usize, usize), Arc<Lazy<T>>>,
+        data: Option<&&'a [u8]>,
+    ) -> Arc<Lazy<T>> {
+        match data {
+            Some(data) => {
+                let key = (data.as_ptr() as usize, data.len());
+                trace_event!(hit = cache.contains_key(&key), "shared table lookup");
+                cache.entry(key).or_default().clone()
+            }
+            None => Arc::default(),
+        }
+    }
+}
+
+/// Glyph data with scaling
+#[derive(Debug)]
+pub struct ScaledGlyph<'a> {
+    scale_width: f32,
+    scale_height: f32,
+    antialiasing: Antialiasing,
+    /// Left side bearing, used to place the outline relative to the pen position
+    lsb: Option<i16>,
+    glyph: Glyph<'a>,
+}
+
+impl<'a> ScaledGlyph<'a> {
+    pub fn render(&self) -> Result<GlyphBitmap> {
+        trace_span!("render_glyph");
+        trace_event!(
+            width = self.scale_width,
+            height = self.scale_height,
+            "rasterizing"
+        );
+        match self.glyph.outline {
+            GlyphOutline::TrueType(ref ttg) => ttg.render(
+                self.scale_width,
+                self.scale_height,
+                self.lsb,
+                self.antialiasing,
+            ),
+        }
+    }
+}
+
+/// We can't render glyphs for a face without appropriate scaling. So, only a `ScaledFace`
+/// allows rendering of glyphs. Multiple `ScaledFace` instances can be created for the same
+/// `Face`, at negligible extra cost
+#[derive(Debug)]
+pub struct ScaledFace<'f, 'a> {
+    scale_width: f32,
+    scale_height: f32,
+    rounding: Rounding,
+    antialiasing: Antialiasing,
+    face: &'f Face<'a>,
+}
+
+impl<'f, 'a> ScaledFace<'f, 'a> {
+    /// Get glyph ID for codepoint. Codepoints the face doesn't cover give `GlyphID::NOTDEF`
+    pub fn get_glyph_id(&self, codepoint: u32) -> Result<GlyphID> {
+        self.face.get_glyph_id(codepoint)
+    }
+
+    /// Get glyph ID for codepoint, or `None` if the character map doesn't cover it
+    pub fn lookup_glyph_id(&self, codepoint: u32) -> Result<Option<GlyphID>> {
+        self.face.lookup_glyph_id(codepoint)
+    }
+
+    /// Render the `.notdef` glyph, which is drawn for characters no face covers. This is on
+    /// `ScaledFace` rather than `Face` because rendering needs a size, so use
+    /// `face.scale(..).notdef_bitmap()`
+    pub fn notdef_bitmap(&self) -> Result<GlyphBitmap> {
+        self.get_glyph(GlyphID::NOTDEF)?.render()
+    }
+
+    /// Set how advances and positions are rounded to the pixel grid. The default is
+    /// `Rounding::None`
+    pub fn with_rounding(mut self, rounding: Rounding) -> ScaledFace<'f, 'a> {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Get rounding mode for advances and positions
+    pub fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    /// Set anti-aliasing quality of rendered glyphs. The default is `Antialiasing::Normal`
+    pub fn with_antialiasing(mut self, antialiasing: Antialiasing) -> ScaledFace<'f, 'a> {
+        self.antialiasing = antialiasing;
+        self
+    }
+
+    /// Get anti-aliasing quality of rendered glyphs
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    /// Round a pen position in pixels according to the rounding mode
+    pub fn round_position(&self, position: f32) -> f32 {
+        self.rounding.apply(position)
+    }
+
+    /// Get horizontal advance width of a glyph in pixels, rounded according to the rounding
+    /// mode
+    pub fn advance(&self, glyph_id: GlyphID) -> Result<f32> {
+        let advance = self.face.hmtx()?.advance(glyph_id)? as f32 * self.scale_width;
+        Ok(self.rounding.apply(advance))
+    }
+
+    /// Get advance of space character `c` in pixels, rounded according to the rounding mode, or
+    /// `None` if `c` isn't a space. See `Face::space_advance`
+    pub fn space_advance(&self, c: char) -> Result<Option<SpaceAdvance>> {
+        Ok(self.face.space_advance(c)?.map(|space| SpaceAdvance {
+            advance: self.rounding.apply(space.advance * self.scale_width),
+            synthesized: space.synthesized,
+        }))
+    }
+
+    /// Get horizontal kerning between a pair of glyphs in pixels, to be added to the advance of
+    /// `left`. GPOS Device table adjustments for the ppem size are included. This is not
+    /// rounded, as it is applied before rounding the pen position
+    pub fn kerning(&self, left: GlyphID, right: GlyphID) -> f32 {
+        let ppem = (self.scale_width * self.face.head.units_per_em() as f32).round() as u16;
+        match self.face.gpos() {
+            Some(gpos) => gpos
+                .kerning(left, right, &self.face.instance(ppem))
+                .map_or(0.0, |(units, pixels)| {
+                    units * self.scale_width + pixels as f32
+                }),
+            None => self.face.kerning(left, right) as f32 * self.scale_width,
+        }
+    }
+
+    /// Get x coordinates of carets between components of a ligature glyph in pixels, relative
+    /// to the glyph origin. GDEF Device table adjustments for the ppem size are included
+    pub fn ligature_carets(&self, id: GlyphID) -> Vec<f32> {
+        let ppem = (self.scale_width * self.face.head.units_per_em() as f32).round() as u16;
+        self.face
+            .carets(id, ppem)
+            .into_iter()
+            .map(|(units, pixels)| units * self.scale_width + pixels as f32)
+            .collect()
+    }
+
+    /// Get horizontal advances of `glyph_ids` in pixels, rounded according to the rounding
+    /// mode, into `out`. This is faster than calling `advance` for each glyph, for grid and
+    /// terminal layouts that need widths of many glyphs
+    ///
+    /// # Panics
+    /// Panics if `glyph_ids` and `out` have different lengths
+    pub fn advances(&self, glyph_ids: &[GlyphID], out: &mut [f32]) -> Result<()> {
+        assert_eq!(glyph_ids.len(), out.len(), "advances: length mismatch");
+        let hmtx = self.face.hmtx()?;
+        for (&glyph_id, advance) in glyph_ids.iter().zip(out.iter_mut()) {
+            *advance = self
+                .rounding
+                .apply(hmtx.advance(glyph_id)? as f32 * self.scale_width);
+        }
+        Ok(())
+    }
+
+    /// Get height of lowercase letters above the baseline, in pixels
+    pub fn x_height(&self) -> Option<f32> {
+        self.face
+            .x_height()
+            .map(|height| height as f32 * self.scale_height)
+    }
+
+    /// Get height of capital letters above the baseline, in pixels
+    pub fn cap_height(&self) -> Option<f32> {
+        self.face
+            .cap_height()
+            .map(|height| height as f32 * self.scale_height)
+    }
+
+    /// Get underline position and thickness, in pixels
+    pub fn underline_metrics(&self) -> Option<ScaledDecorationMetrics> {
+        self.face
+            .underline_metrics()
+            .map(|metrics| self.scale_decoration(metrics))
+    }
+
+    /// Get strikeout position and thickness, in pixels
+    pub fn strikeout_metrics(&self) -> Option<ScaledDecorationMetrics> {
+        self.face
+            .strikeout_metrics()
+            .map(|metrics| self.scale_decoration(metrics))
+    }
+
+    fn scale_decoration(&self, metrics: DecorationMetrics) -> ScaledDecorationMetrics {
+        ScaledDecorationMetrics {
+            position: metrics.position as f32 * self.scale_height,
+            thickness: metrics.thickness as f32 * self.scale_height,
+        }
+    }
+
+    /// Get recommended subscript size and offset, in pixels
+    pub fn subscript_metrics(&self) -> Option<ScriptMetrics> {
+        self.face
+            .subscript_metrics()
+            .map(|metrics| self.scale_script(metrics))
+    }
+
+    /// Get recommended superscript size and offset, in pixels
+    pub fn superscript_metrics(&self) -> Option<ScriptMetrics> {
+        self.face
+            .superscript_metrics()
+            .map(|metrics| self.scale_script(metrics))
+    }
+
+    /// Get transformation for synthesizing subscripts from regular glyphs rendered at this
+    /// size, e.g. when the face lacks the `subs` feature
+    pub fn subscript_transform(&self) -> Option<ScriptTransform> {
+        self.subscript_metrics()
+            .map(|metrics| self.script_transform(metrics))
+    }
+
+    /// Get transformation for synthesizing superscripts from regular glyphs rendered at this
+    /// size, e.g. when the face lacks the `sups` feature
+    pub fn superscript_transform(&self) -> Option<ScriptTransform> {
+        self.superscript_metrics()
+            .map(|metrics| self.script_transform(metrics))
+    }
+
+    fn scale_script(&self, metrics: ScriptMetrics) -> ScriptMetrics {
+        ScriptMetrics {
+            x_size: metrics.x_size * self.scale_width,
+            y_size: metrics.y_size * self.scale_height,
+            x_offset: metrics.x_offset * self.scale_width,
+            y_offset: metrics.y_offset * self.scale_height,
+        }
+    }
+
+    fn script_transform(&self, metrics: ScriptMetrics) -> ScriptTransform {
+        let units_per_em = self.face.head.units_per_em() as f32;
+        ScriptTransform {
+            scale_x: metrics.x_size / (self.scale_width * units_per_em),
+            scale_y: metrics.y_size / (self.scale_height * units_per_em),
+            offset_x: metrics.x_offset,
+            offset_y: metrics.y_offset,
+        }
+    }
+
+    /// Get line metrics in pixels, from the table indicated by `source`
+    pub fn line_metrics(&self, source: MetricsSource) -> LineMetrics {
+        let metrics = self.face.line_metrics(source);
+        LineMetrics {
+            ascender: metrics.ascender * self.scale_height,
+            descender: metrics.descender * self.scale_height,
+            line_gap: metrics.line_gap * self.scale_height,
+        }
+    }
+
+    /// Get hinted integer advance width of a glyph in pixels, for grid-fitted layout. This is
+    /// only available at integral ppem sizes, when the hdmx table has a record for the size,
+    /// or when the LTSH table says the glyph's advance scales linearly at this size (in which
+    /// case the rounded linear advance is returned)
+    pub fn device_advance(&self, glyph_id: GlyphID) -> Option<u16> {
+        let ppem = self.scale_width * self.face.head.units_per_em() as f32;
+        if ppem.fract() != 0.0 || !(1.0..=255.0).contains(&ppem) {
+            return None;
+        }
+        let ppem = ppem as u8;
+        if let Some(advance) = self
+            .face
+            .hdmx()
+            .and_then(|hdmx| hdmx.advance(ppem, glyph_id))
+        {
+            return Some(advance as u16);
+        }
+        match self.face.ltsh() {
+            Some(ltsh) if ltsh.is_linear(ppem, glyph_id) => {
+                let advance = self.face.hmtx().ok()?.advance(glyph_id).ok()?;
+                Some((advance as f32 * self.scale_width).round() as u16)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get width of a terminal cell in pixels: the advance of '0' rounded according to the
+    /// rounding mode, or of the widest glyph if the face doesn't map '0'
+    pub fn cell_width(&self) -> Result<f32> {
+        match self.lookup_glyph_id('0' as u32)? {
+            Some(zero) => self.advance(zero),
+            None => Ok(self
+                .rounding
+                .apply(self.face.hhea.advance_width_max() as f32 * self.scale_width)),
+        }
+    }
+
+    /// Lay out a line of text on a grid of cells, as in a terminal. Each character gets one
+    /// cell, or two for East Asian Wide and Fullwidth characters (UAX #11), and combining marks
+    /// share the cell of the character before them. Tabs advance to the next multiple of
+    /// `tab_width` columns. Multiply columns by `cell_width` for pixel positions
+    pub fn layout_grid(&self, text: &str, tab_width: usize) -> Result<Vec<GridCluster>> {
+        grid::layout(self, text, tab_width)
+    }
+
+    /// Place glyphs of a shaped run, given in logical order, from left to right in visual
+    /// order, so renderers with a left-to-right pen can draw runs of either direction. Glyphs
+    /// of right-to-left runs are placed by cumulative advance from the right edge of the run.
+    /// `adjustments` (e.g. from `Face::apply_gpos`) are applied to glyphs by index, and glyphs
+    /// without one are unadjusted. Returns the placed glyphs and the width of the run in pixels
+    pub fn position_run(
+        &self,
+        glyph_ids: &[GlyphID],
+        adjustments: &[GlyphAdjustment],
+        direction: Direction,
+    ) -> Result<(Vec<PositionedGlyph>, f32)> {
+        run::position(self, glyph_ids, adjustments, direction)
+    }
+
+    /// Get outline of a line of text, in pixels with y increasing downwards. The pen starts at
+    /// the origin on the baseline, and each glyph is placed by the advance of the one before
+    /// it, rounded according to the rounding mode. Characters are mapped to glyphs one by one
+    /// through the character map, without shaping or kerning. If the face has no GPOS mark
+    /// attachment, combining marks are centered above or below the character before them.
+    /// Spaces the face has no glyph for are left blank, with synthesized advances
+    pub fn text_to_path(&self, text: &str) -> Result<Path> {
+        let mut ops = Vec::new();
+        let mut pen = 0.0;
+        let fallback_marks = !self.face.has_mark_attachment();
+        let units_per_em = self.face.head.units_per_em() as f32;
+        // Pen position and bounds of the last base glyph and the marks on it
+        let mut base: Option<(f32, Rect)> = None;
+        for c in text.chars() {
+            if let Some(space) = self.space_advance(c)? {
+                if space.synthesized {
+                    pen = self.round_position(pen + space.advance);
+                    base = None;
+                    continue;
+                }
+            }
+            let glyph_id = self.get_glyph_id(c as u32)?;
+            let glyph = self.face.get_glyph(glyph_id)?;
+            let GlyphOutline::TrueType(ref ttg) = glyph.outline;
+            let bounds = ttg.bounds();
+            let is_mark = fallback_marks && width::is_zero_width(c) && !c.is_control();
+            let (x, y) = match (is_mark, base, bounds) {
+                (true, Some((base_pen, base_bounds)), Some(mark_bounds)) => {
+                    let (offset, bounds) =
+                        fallback::mark_offset(base_bounds, mark_bounds, units_per_em);
+                    base = Some((base_pen, bounds));
+                    (base_pen / self.scale_width + offset.x, offset.y)
+                }
+                (true, _, _) => (pen / self.scale_width, 0.0),
+                (false, _, _) => {
+                    base = bounds.map(|bounds| (pen, bounds));
+                    (pen / self.scale_width, 0.0)
+                }
+            };
+            let affine = Affine::translation(x, y).scaled(self.scale_width, -self.scale_height);
+            ttg.outline(affine, &mut ops)?;
+            pen = self.round_position(pen + self.advance(glyph_id)?);
+        }
+        Ok(Path::new(ops))
+    }
+
+    /// Get glyph information for glyph_id
+    pub fn get_glyph(&self, glyph_id: GlyphID) -> Result<ScaledGlyph<'_>> {
+        let lsb = self.face.hmtx().and_then(|hmtx| hmtx.lsb(glyph_id)).ok();
+        self.face.get_glyph(glyph_id).map(|glyph| ScaledGlyph {
+            scale_height: self.scale_height,
+            scale_width: self.scale_width,
+            antialiasing: self.antialiasing,
+            lsb,
+            glyph: glyph,
+        })
+    }
+
+    /// Render glyph with its COLR layers flattened into one coverage bitmap, for monochrome
+    /// displays. Layers are composited over white in linear light with colors from palette
+    /// `palette`, and coverage is how dark the result is by luminance, so shapes stay
+    /// distinguishable in grayscale. The foreground color counts as black. Glyphs without
+    /// color layers are rendered as usual
+    pub fn render_flattened(&self, glyph_id: GlyphID, palette: u16) -> Result<GlyphBitmap> {
+        let layers = self.face.color_layers(glyph_id, palette);
+        if layers.is_empty() {
+            return self.get_glyph(glyph_id)?.render();
+        }
+        let mut bitmaps = Vec::with_capacity(layers.len());
+        for layer in layers {
+            let bitmap = self.get_glyph(layer.glyph_id)?.render()?;
+            if bitmap.width > 0 && bitmap.height > 0 {
+                bitmaps.push((bitmap, layer.color));
+            }
+        }
+        let left = bitmaps.iter().map(|(b, _)| b.left).min().unwrap_or(0);
+        let right = bitmaps.iter().map(|(b, _)| b.left + b.width as i32).max();
+        let top = bitmaps.iter().map(|(b, _)| b.top).max().unwrap_or(0);
+        let bottom = bitmaps.iter().map(|(b, _)| b.top - b.height as i32).min();
+        let width = right.map_or(0, |right| (right - left) as usize);
+        let height = bottom.map_or(0, |bottom| (top - bottom) as usize);
+        // Luminance of the composited layers, starting from a white background
+        let mut luminance = vec![1.0f32; width * height];
+        for (bitmap, color) in bitmaps.iter() {
+            let (value, opacity) = color.map_or((0.0, 1.0), |color| {
+                (color.luminance(), color.alpha as f32 / 255.0)
+            });
+            let x0 = (bitmap.left - left) as usize;
+            let y0 = (top - bitmap.top) as usize;
+            for (y, row) in bitmap.rows().enumerate() {
+                let start = (y0 + y) * width + x0;
+                let out = &mut luminance[start..start + bitmap.width];
+                for (l, &coverage) in out.iter_mut().zip(row) {
+                    *l += (value - *l) * coverage as f32 / 255.0 * opacity;
+                }
+            }
+        }
+        let data = luminance
+            .iter()
+            .map(|l| ((1.0 - l) * 255.0).round() as u8)
+            .collect::<Vec<_>>();
+        Ok(GlyphBitmap {
+            width,
+            height,
+            left,
+            top,
+            stride: width,
+            data: data.into_boxed_slice(),
+        })
+    }
+}
+
+/// sfnt version, table directory data, and the data of each table of a face
+type Directory<'a> = (Tag, &'a [u8], HashMap<Tag, &'a [u8]>);
+
+/// Read the table directory of the face whose Offset Table is at `offset` in `data`
+fn read_directory(data: &[u8], offset: usize, options: ParseOptions) -> Result<Directory<'_>> {
+    let sfnt_version = get_tag(data, offset)?;
+    let num_tables = get_u16(data, offset + 4)? as usize;
+    trace_event!(bytes = data.len(), num_tables = num_tables, "loading face");
+    let mut record_off = offset + 12;
+    let directory = data
+        .get(offset..record_off + num_tables * 16)
+        .ok_or(Error::truncated(offset, 12 + num_tables * 16))?;
+    let mut tables = HashMap::new();
+    let mut ranges = Vec::with_capacity(num_tables);
+    for _ in 0..num_tables {
+        let tag = get_tag(data, record_off)?;
+        let table_off = get_u32(data, record_off + 8)? as usize;
+        let table_len = get_u32(data, record_off + 12)? as usize;
+        let mut table_end = table_off + table_len;
+        if table_end > data.len() {
+            // Truncated font. Keep whatever part of the table we have
+            if options.strict {
+                return Err(Error::truncated(table_off, table_len).in_table(tag));
+            }
+            table_end = data.len();
+        }
+        let table_data = data.get(table_off..table_end).unwrap_or(&[]);
+        tables.insert(tag, table_data);
+        if table_off < table_end {
+            ranges.push((table_off, table_end));
+        }
+        record_off += 16;
+    }
+    if options.strict {
+        ranges.sort();
+        if ranges.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+            return Err(Error::malformed("tables overlap"));
+        }
+    }
+    Ok((sfnt_version, directory, tables))
+}
+
+/// An OpenType font file can either be a "font collection" (e.g. *.otc) file, or contain a
+/// single font. To provide a uniform interface, rype opens a font file as a `FontCollection`.
+/// The `FontCollection` can then be queried for individual `Face`s. Mac OS font suitcases
+/// (*.dfont) are also opened as collections of the `sfnt` resources they contain.
+pub struct FontCollection {
+    data: SharedData,
+    faces: Vec<FaceLocation>,
+    options: ParseOptions,
+}
+
+/// Font data that can be shared with other owners, such as a font database
+pub type SharedData = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// Location of a face within the font file
+#[derive(Clone, Debug)]
+struct FaceLocation {
+    /// Range of data that table offsets are relative to
+    data: std::ops::Range<usize>,
+    /// Offset of the Offset Table for the face, within `data`
+    offset: usize,
+}
+
+impl std::fmt::Debug for FontCollection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FontCollection")
+            .field(
+                "faces",
+                &(0..self.num_faces())
+                    .map(|idx| self.get_face(idx))
+                    .collect::<Vec<Result<Face>>>(),
+            )
+            .finish()
+    }
+}
+
+impl FontCollection {
+    /// Load font collection from file
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Result<FontCollection> {
+        FontCollection::new_with_options(path, ParseOptions::default())
+    }
+
+    /// Load font collection from file, parsing faces with the given options
+    pub fn new_with_options<P: AsRef<std::path::Path>>(
+        path: P,
+        options: ParseOptions,
+    ) -> Result<FontCollection> {
+        let data = std::fs::read(path)?;
+        FontCollection::from_data(Arc::new(data), options)
+    }
+
+    /// Load font collection from in-memory buffer. (Note: this creates a copy of the memory
+    /// buffer)
+    pub fn new_from(data: &[u8]) -> Result<FontCollection> {
+        FontCollection::new_from_with_options(data, ParseOptions::default())
+    }
+
+    /// Load font collection from in-memory buffer, parsing faces with the given options.
+    /// (Note: this creates a copy of the memory buffer)
+    pub fn new_from_with_options(data: &[u8], options: ParseOptions) -> Result<FontCollection> {
+        FontCollection::from_data(Arc::new(data.to_vec()), options)
+    }
+
+    /// Load font collection from data shared with other owners, without copying it. This fits
+    /// font databases like `fontdb` (`Source::Binary`) and `font-kit` (`Handle::Memory`),
+    /// which hand out font data as an `Arc` along with the index of the face to pass to
+    /// `get_face`
+    pub fn from_shared(data: SharedData) -> Result<FontCollection> {
+        FontCollection::from_shared_with_options(data, ParseOptions::default())
+    }
+
+    /// Load font collection from shared data without copying it, parsing faces with the given
+    /// options
+    pub fn from_shared_with_options(
+        data: SharedData,
+        options: ParseOptions,
+    ) -> Result<FontCollection> {
+        FontCollection::from_data(data, options)
+    }
+
+    /// Get face at given index
+    pub fn get_face(&self, idx: usize) -> Result<Face<'_>> {
+        self.faces
+            .get(idx)
+            .ok_or(Error::FaceIndexOutOfBounds)
+            .and_then(|loc| Face::load(&self.bytes()[loc.data.clone()], loc.offset, self.options))
+    }
+
+    /// Get metadata of face at index `idx`, reading only the tables it comes from. This is much
+    /// cheaper than `get_face`, for listing many fonts
+    pub fn face_info(&self, idx: usize) -> Result<FaceInfo> {
+        let loc = self.faces.get(idx).ok_or(Error::FaceIndexOutOfBounds)?;
+        let data = &self.bytes()[loc.data.clone()];
+        let (sfnt_version, _, tables) = read_directory(data, loc.offset, self.options)?;
+        let format = match sfnt_version {
+            Tag(0x00010000) | Tag(0x74727565) => FontFormat::TrueType,
+            Tag(0x4F54544F) => FontFormat::OpenTypeCff,
+            _ => return Err(Error::UnknownFormat),
+        };
+        let maxp = load_required(&tables, b"maxp", maxp::Maxp::load)?;
+        let name = tables
+            .get(&Tag::new(b"name"))
+            .and_then(|&data| name::Name::load(data).ok());
+        let axes = tables
+            .get(&Tag::new(b"fvar"))
+            .and_then(|&data| fvar::Fvar::load(data).ok())
+            .map(|fvar| fvar.axes())
+            .unwrap_or_default();
+        Ok(FaceInfo {
+            family_name: name.as_ref().and_then(|name| name.family()),
+            style_name: name.as_ref().and_then(|name| name.style()),
+            num_glyphs: maxp.num_glyphs(),
+            format,
+            axes,
+        })
+    }
+
+    /// Iterate over all faces in the collection. Faces are loaded lazily, and tables shared
+    /// between faces (common in CJK collections) are only parsed once
+    pub fn faces(&self) -> Faces<'_> {
+        Faces {
+            collection: self,
+            idx: 0,
+            shared: SharedTables::default(),
+        }
+    }
+
+    /// Find the face with the given PostScript name, as used to reference fonts in PDF and
+    /// other document formats
+    pub fn face_by_postscript_name(&self, name: &str) -> Option<Face<'_>> {
+        self.faces()
+            .filter_map(|face| face.ok())
+            .find(|face| face.postscript_name().as_deref() == Some(name))
+    }
+
+    /// Group faces in the collection by typographic family name, in order of first appearance.
+    /// Members of a family are sorted by stretch, weight, and slant. Faces that fail to load or
+    /// have no family name are left out
+    pub fn families(&self) -> Vec<Family> {
+        let mut families: Vec<Family> = Vec::new();
+        for (index, face) in self.faces().enumerate() {
+            let face = match face {
+                Ok(face) => face,
+                Err(_) => continue,
+            };
+            let name = match face.family_name() {
+                Some(name) => name,
+                None => continue,
+            };
+            let member = FamilyMember {
+                index,
+                style_name: face.style_name(),
+                style: face.style(),
+            };
+            match families.iter_mut().find(|family| family.name == name) {
+                Some(family) => family.members.push(member),
+                None => families.push(Family {
+                    name,
+                    members: vec![member],
+                }),
+            }
+        }
+        for family in &mut families {
+            family
+                .members
+                .sort_by_key(|m| (m.style.stretch, m.style.weight, m.style.italic));
+        }
+        families
+    }
+
+    /// Get the face of `family` with `style` best suited to text at `point_size`, for families
+    /// with optical size variants (e.g. "Caption", "Text" and "Display" cuts). Size ranges come
+    /// from the GPOS `size` feature or the STAT `opsz` axis. Faces with no size information
+    /// are used when no face's range covers the size. Variable faces with an `opsz` axis have
+    /// it set to the size
+    pub fn face_for_optical_size(
+        &self,
+        family: &Family,
+        style: FaceStyle,
+        point_size: f32,
+    ) -> Option<Face<'_>> {
+        let opsz = Tag::new(b"opsz");
+        let mut best: Option<((f32, bool, f32), Face)> = None;
+        for member in family.members.iter().filter(|m| m.style == style) {
+            let face = match self.get_face(member.index) {
+                Ok(face) => face,
+                Err(_) => continue,
+            };
+            if face.variation_axes().iter().any(|axis| axis.tag == opsz) {
+                return Some(face.with_variations(&[(opsz, point_size)]));
+            }
+            // Distance from the size range, then whether there is a range at all, then
+            // distance from the design size
+            let key = match face.optical_size() {
+                Some((design, start, end)) => {
+                    let distance = if point_size <= start {
+                        start - point_size
+                    } else if point_size > end {
+                        point_size - end
+                    } else {
+                        0.0
+                    };
+                    (distance, false, (point_size - design).abs())
+                }
+                None => (0.0, true, 0.0),
+            };
+            if best.as_ref().is_none_or(|(best_key, _)| key < *best_key) {
+                best = Some((key, face));
+            }
+        }
+        best.map(|(_, face)| face)
+    }
+
+    /// Get number of faces in font collection
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Get font file data
+    fn bytes(&self) -> &[u8] {
+        (*self.data).as_ref()
+    }
+
+    /// Load font collection from data
+    fn from_data(shared: SharedData, options: ParseOptions) -> Result<FontCollection> {
+        let data = (*shared).as_ref();
+        trace_span!("load_collection", bytes = data.len());
+        // Is this a font collection, or a single face?
+        let whole = |offset| FaceLocation {
+            data: 0..data.len(),
+            offset,
+        };
+        let faces = match sniff(data) {
+            FontFormat::TrueType | FontFormat::OpenTypeCff => vec![whole(0)],
+            FontFormat::Collection => {
+                // The face count is checked against the data before allocating for it
+                let num_fonts = get_u32(data, 8)? as usize;
+                if data.len().saturating_sub(12) / 4 < num_fonts {
+                    return Err(Error::truncated(12, num_fonts.saturating_mul(4)));
+                }
+                let mut faces = Vec::with_capacity(num_fonts);
+                let mut off = 12;
+                for _ in 0..num_fonts {
+                    let face_off = get_u32(data, off)? as usize;
+                    faces.push(whole(face_off));
+                    off += 4;
+                }
+                faces
+            }
+            FontFormat::Dfont => dfont::sfnt_resources(data)?
+                .into_iter()
+                .map(|(start, len)| FaceLocation {
+                    data: start..start + len,
+                    offset: 0,
+                })
+                .collect(),
+            FontFormat::Woff => return Err(Error::Unimplemented("WOFF fonts".to_owned())),
+            FontFormat::Woff2 => return Err(Error::Unimplemented("WOFF2 fonts".to_owned())),
+            FontFormat::Unknown => return Err(Error::UnknownFormat),
+        };
+        Ok(FontCollection {
+            data: shared,
+            faces,
+            options,
+        })
+    }
+}
+
+/// Faces of a collection that share a typographic family name
+#[derive(Clone, Debug, PartialEq)]
+pub struct Family {
+    pub name: String,
+    pub members: Vec<FamilyMember>,
+}
+
+/// Metadata of a face, as returned by `FontCollection::face_info`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaceInfo {
+    /// Family name, preferring the typographic family name
+    pub family_name: Option<String>,
+    /// Style name, preferring the typographic subfamily name
+    pub style_name: Option<String>,
+    pub num_glyphs: u16,
+    /// `FontFormat::TrueType` or `FontFormat::OpenTypeCff`, depending on the outline format
+    pub format: FontFormat,
+    /// Variation axes, empty for faces without variations
+    pub axes: Vec<VariationAxis>,
+}
+
+/// A face within a `Family`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FamilyMember {
+    /// Index of the face in the collection
+    pub index: usize,
+    /// Style name, e.g. "Bold Italic"
+    pub style_name: Option<String>,
+    pub style: FaceStyle,
+}
+
+/// Lazy iterator over faces in a `FontCollection`
+pub struct Faces<'a> {
+    collection: &'a FontCollection,
+    idx: usize,
+    shared: SharedTables<'a>,
+}
+
+impl<'a> Iterator for Faces<'a> {
+    type Item = Result<Face<'a>>;
+
+    fn next(&mut self) -> Option<Result<Face<'a>>> {
+        let collection = self.collection;
+        let loc = collection.faces.get(self.idx)?;
+        self.idx += 1;
+        Some(Face::load_shared(
+            &collection.bytes()[loc.data.clone()],
+            loc.offset,
+            collection.options,
+            &mut self.shared,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.collection.faces.len() - self.idx;
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn get_path(res: &str) -> PathBuf {
+        let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        buf.push("fonts");
+        buf.push(res);
+        buf
+    }
+
+    /// Pack words as big-endian u16s, for building synthetic tables
+    fn be(words: &[i32]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|&w| (w as u16).to_be_bytes())
+            .collect()
+    }
+
+    /// Load Hack with `extra` tables added, replacing its own tables with the same tags. The
+    /// table data is leaked, so the face can be returned
+    fn face_with_tables(extra: &[(&[u8; 4], Vec<u8>)]) -> Face<'static> {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let mut tables = face
+            .tables()
+            .map(|(tag, data)| (tag, data.to_vec()))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        for (tag, data) in extra {
+            tables.insert(Tag::new(tag), data.clone());
+        }
+        Face::from_provider(Box::leak(Box::new(tables)), ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_firacode() {
+        let path = get_path("FiraCode-Regular.otf");
+        let fc = FontCollection::new(&path).unwrap();
+        assert_eq!(
+            &format!("{:?}", fc),
+            "FontCollection { faces: [Ok(Face { head: Head \
+             { units_per_em: 1950, xmin: -3556, ymin: -1001, xmax: 2385, ymax: 2401, \
+             lowest_rec_ppem: 3, index_to_loc_format: Off16 }, hhea: Hhea { ascender: 1800, \
+             descender: -600, num_of_h_metrics: 1746 }, maxp: Maxp { num_glyphs: 1746 }, \
+             cmap: Cmap { subtables: [Subtable { platform_id: 0, encoding_id: 3, format: Ok(4) \
+             }, Subtable { platform_id: 3, encoding_id: 1, format: Ok(4) }, Subtable { \
+             platform_id: 0, encoding_id: 4, format: Ok(12) }, Subtable { platform_id: 3, \
+             encoding_id: 10, format: Ok(12) }], active: Some(Subtable { platform_id: 3, \
+             encoding_id: 10, format: Ok(12) }) }, typ: CFF })] }"
+        );
+    }
+
+    #[test]
+    fn test_firacode_tables() {
+        let path = get_path("FiraCode-Regular.otf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let tables = face.tables;
+        let mut names = tables
+            .keys()
+            .map(|k| format!("{}", k))
+            .collect::<Vec<String>>();
+        names.sort();
+        assert_eq!(
+            &names.join(", "),
+            "CFF , GDEF, GPOS, GSUB, OS/2, cmap, head, hhea, \
+             hmtx, maxp, name, post"
+        );
+    }
+
+    #[test]
+    fn test_firacode_cmap() {
+        let path = get_path("FiraCode-Regular.otf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.get_glyph_id('A' as u32).unwrap(), GlyphID(1));
+        assert_eq!(face.get_glyph_id('B' as u32).unwrap(), GlyphID(13));
+        assert_eq!(face.get_glyph_id('C' as u32).unwrap(), GlyphID(14));
+        assert_eq!(face.get_glyph_id('D' as u32).unwrap(), GlyphID(20));
+        assert_eq!(face.get_glyph_id('E' as u32).unwrap(), GlyphID(24));
+        assert_eq!(face.get_glyph_id('F' as u32).unwrap(), GlyphID(34));
+        assert_eq!(face.get_glyph_id('a' as u32).unwrap(), GlyphID(134));
+        assert_eq!(face.get_glyph_id('>' as u32).unwrap(), GlyphID(1171));
+        assert_eq!(face.get_glyph_id('=' as u32).unwrap(), GlyphID(1169));
+    }
+
+    #[test]
+    fn test_embedding_permissions() {
+        for font in &["FiraCode-Regular.otf", "Hack-Regular.ttf"] {
+            let fc = FontCollection::new(get_path(font)).unwrap();
+            let face = fc.get_face(0).unwrap();
+            assert_eq!(
+                face.embedding_permissions(),
+                Some(EmbeddingPermissions {
+                    level: EmbeddingLevel::Installable,
+                    no_subsetting: false,
+                    bitmap_only: false,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_unicode_ranges() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let ranges = face.unicode_ranges().unwrap();
+        assert_eq!(ranges.bits() as u32, 0xa500_06ef);
+        assert!(ranges.supports_block(UnicodeBlock::BasicLatin));
+        assert!(face.supports_block(UnicodeBlock::Cyrillic));
+        assert!(!face.supports_block(UnicodeBlock::Arabic));
+        assert!(!face.supports_block(UnicodeBlock::DominoTiles));
+        let code_pages = face.code_pages().unwrap();
+        assert_eq!(code_pages.bits(), 0xdfd7_0000_2000_019f);
+        assert!(code_pages.supports_code_page(1251));
+        assert!(code_pages.supports_code_page(437));
+        assert!(!code_pages.supports_code_page(1255));
+        assert!(!code_pages.supports_code_page(864));
+        assert!(!code_pages.supports_code_page(1));
+        assert!(code_pages.supports_macintosh());
+        assert!(!code_pages.is_symbol());
+    }
+
+    #[test]
+    fn test_post() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.glyph_name(GlyphID(0)), Some(".notdef"));
+        assert_eq!(face.glyph_name(GlyphID(1425)), Some("uni0041"));
+        assert_eq!(face.glyph_name(GlyphID(1573)), None);
+        assert_eq!(face.glyph_id_by_name("uni0061"), Some(GlyphID(118)));
+        assert_eq!(face.glyph_id_by_name("nonexistent"), None);
+        assert_eq!(face.italic_angle(), Some(0.0));
+        assert_eq!(face.is_fixed_pitch(), Some(true));
+        assert_eq!(
+            face.underline_metrics(),
+            Some(DecorationMetrics {
+                position: -220,
+                thickness: 90,
+            })
+        );
+        assert_eq!(
+            face.strikeout_metrics(),
+            Some(DecorationMetrics {
+                position: 530,
+                thickness: 102,
+            })
+        );
+        assert_eq!(
+            face.scale(12.0, 12.0, 96, 96).underline_metrics(),
+            Some(ScaledDecorationMetrics {
+                position: -220.0 / 128.0,
+                thickness: 90.0 / 128.0,
+            })
+        );
+
+        // FiraCode has a version 3.0 post table, without glyph names
+        let fc = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.glyph_name(GlyphID(1)), None);
+        assert_eq!(face.is_fixed_pitch(), Some(true));
+    }
+
+    #[test]
+    fn test_is_monospace() {
+        for font in &["FiraCode-Regular.otf", "Hack-Regular.ttf"] {
+            let fc = FontCollection::new(get_path(font)).unwrap();
+            let face = fc.get_face(0).unwrap();
+            assert!(face.is_monospace());
+        }
+    }
+
+    #[test]
+    fn test_line_metrics() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let win = LineMetrics {
+            ascender: 1901.0,
+            descender: -483.0,
+            line_gap: 0.0,
+        };
+        assert_eq!(face.line_metrics(MetricsSource::Auto), win);
+        assert_eq!(face.line_metrics(MetricsSource::Win), win);
+        assert_eq!(
+            face.line_metrics(MetricsSource::Typo),
+            LineMetrics {
+                ascender: 1556.0,
+                descender: -492.0,
+                line_gap: 410.0,
+            }
+        );
+
+        // FiraCode sets USE_TYPO_METRICS
+        let fc = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let metrics = face.line_metrics(MetricsSource::Auto);
+        assert_eq!(metrics, face.line_metrics(MetricsSource::Typo));
+        assert_eq!(metrics.line_height(), 2400.0);
+    }
+
+    #[test]
+    fn test_tags() {
+        assert_eq!("cvt ".parse::<Tag>().unwrap(), Tag::new(b"cvt "));
+        assert_eq!("cvt".parse::<Tag>().unwrap(), Tag::new(b"cvt "));
+        assert!("".parse::<Tag>().is_err());
+        assert!("glyph".parse::<Tag>().is_err());
+        assert_eq!(Tag::new(b"OS/2").to_string(), "OS/2");
+
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(
+            face.table(Tag::new(b"TTFA")).map(|data| data.len()),
+            Some(1376)
+        );
+        assert!(face.table(Tag::new(b"CFF ")).is_none());
+        assert_eq!(face.tables().count(), 17);
+    }
+
+    #[test]
+    fn test_inspect() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let summary = inspect::inspect(&face);
+        assert_eq!(summary.sfnt_version, Tag(0x00010000));
+        assert_eq!(summary.tables.len(), 17);
+        assert!(summary
+            .tables
+            .iter()
+            .all(|table| table.checksum == table.computed_checksum));
+        assert_eq!(summary.head.units_per_em, 2048);
+        assert_eq!(summary.hhea.num_of_h_metrics, 1543);
+        assert_eq!(summary.os2.unwrap().weight_class, 400);
+        assert_eq!(
+            summary.cmap_subtables.iter().filter(|s| s.active).count(),
+            1
+        );
+        assert!(summary.axes.is_empty());
+        // Hack has a placeholder DSIG table without signatures
+        let dsig = summary.dsig.unwrap();
+        assert_eq!((dsig.version, dsig.flags, dsig.signatures.len()), (1, 0, 0));
+
+        let mut data = vec![0, 0, 0, 1, 0, 1, 0, 1, 0, 0, 0, 1, 0, 0, 0, 12, 0, 0, 0, 20];
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 4, 0xde, 0xad, 0xbe, 0xef]);
+        let signatures = dsig::Dsig::load(&data).unwrap().signatures();
+        assert_eq!((signatures[0].format, signatures[0].length), (1, 12));
+        assert!(signatures[0].valid_block);
+        data[27] = 5;
+        assert!(!dsig::Dsig::load(&data).unwrap().signatures()[0].valid_block);
+    }
+
+    #[test]
+    fn test_sniff() {
+        let data = std::fs::read(get_path("Hack-Regular.ttf")).unwrap();
+        assert_eq!(sniff(&data), FontFormat::TrueType);
+        let data = std::fs::read(get_path("FiraCode-Regular.otf")).unwrap();
+        assert_eq!(sniff(&data), FontFormat::OpenTypeCff);
+        assert_eq!(sniff(b"wOF2\0\x01\0\0"), FontFormat::Woff2);
+        assert_eq!(sniff(b"ttc"), FontFormat::Unknown);
+        assert!(FontCollection::new_from(b"wOFF\0\x01\0\0").is_err());
+    }
+
+    #[test]
+    fn test_dfont() {
+        // Wrap Hack in a resource fork with a single sfnt resource
+        let sfnt = std::fs::read(get_path("Hack-Regular.ttf")).unwrap();
+        let map_off = 256 + 4 + sfnt.len();
+        let mut data = Vec::new();
+        for &v in &[256, map_off, 4 + sfnt.len(), 50] {
+            data.extend_from_slice(&(v as u32).to_be_bytes());
+        }
+        data.resize(256, 0);
+        data.extend_from_slice(&(sfnt.len() as u32).to_be_bytes());
+        data.extend_from_slice(&sfnt);
+        data.extend_from_slice(&[0; 24]);
+        data.extend_from_slice(&[0, 28, 0, 50, 0, 0]);
+        data.extend_from_slice(b"sfnt\0\0\0\x0a");
+        data.extend_from_slice(&[0, 128, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(sniff(&data), FontFormat::Dfont);
+        let fc = FontCollection::new_from(&data).unwrap();
+        assert_eq!(fc.num_faces(), 1);
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.get_glyph_id('A' as u32).unwrap(), GlyphID(1425));
+
+        // Offsets that overflow are rejected
+        data[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(FontCollection::new_from(&data).is_err());
+        data[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        data[4..8].copy_from_slice(&(map_off as u32).to_be_bytes());
+        assert!(FontCollection::new_from(&data).is_err());
+    }
+
+    #[test]
+    fn test_collection() {
+        // Build a collection with two faces that share Hack's tables
+        let sfnt = std::fs::read(get_path("Hack-Regular.ttf")).unwrap();
+        let num_tables = get_u16(&sfnt, 4).unwrap() as usize;
+        let mut dir = sfnt[..12 + num_tables * 16].to_vec();
+        for i in 0..num_tables {
+            let off = 12 + i * 16 + 8;
+            let table_off = get_u32(&dir, off).unwrap() + 20;
+            dir[off..off + 4].copy_from_slice(&table_off.to_be_bytes());
+        }
+        let mut data = b"ttcf\0\x01\0\0\0\0\0\x02".to_vec();
+        data.extend_from_slice(&20u32.to_be_bytes());
+        data.extend_from_slice(&((20 + sfnt.len()) as u32).to_be_bytes());
+        data.extend_from_slice(&dir);
+        data.extend_from_slice(&sfnt[dir.len()..]);
+        data.extend_from_slice(&dir);
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        assert_eq!(fc.num_faces(), 2);
+        // A face count larger than the offset array is an error, not a huge allocation
+        let header = b"ttcf\0\x01\0\0\xff\xff\xff\xff\0\0\0\x0c\0\0\0\x0c\0\0\0\x0c\0\0\0\x0c";
+        assert!(FontCollection::new_from(&header[..]).is_err());
+        let faces = fc.faces().collect::<Result<Vec<_>>>().unwrap();
+        assert!(Arc::ptr_eq(&faces[0].cmap, &faces[1].cmap));
+        // Tables moved behind the collection header keep the face's identity
+        let standalone = FontCollection::new_from(&sfnt).unwrap();
+        let standalone = standalone.get_face(0).unwrap().identity();
+        assert_eq!(faces[0].identity(), standalone);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+        let fira = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        assert_ne!(fira.get_face(0).unwrap().identity(), standalone);
+        for face in &faces {
+            assert_eq!(face.get_glyph_id('A' as u32).unwrap(), GlyphID(1425));
+        }
+        assert!(fc.face_by_postscript_name("Hack-Regular").is_some());
+        assert!(fc.face_by_postscript_name("Hack-Bold").is_none());
+        let families = fc.families();
+        assert_eq!(families.len(), 1);
+        assert_eq!(families[0].name, "Hack");
+        let indices = families[0]
+            .members
+            .iter()
+            .map(|m| m.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_scan_dir() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fonts");
+        let faces: Vec<_> = scan_dir(&dir).collect();
+        let names: Vec<_> = faces
+            .iter()
+            .map(|face| (face.path.file_name().unwrap(), face.index))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (std::ffi::OsStr::new("FiraCode-Regular.otf"), 0),
+                (std::ffi::OsStr::new("Hack-Regular.ttf"), 0)
+            ]
+        );
+        assert_eq!(faces[1].info.family_name.as_deref(), Some("Hack"));
+        assert_eq!(scan_dir(dir.join("Hack-LICENSE.txt")).count(), 0);
+    }
+
+    #[test]
+    fn test_font_runs() {
+        let hack = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let fira = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        let (hack, fira) = (hack.get_face(0).unwrap(), fira.get_face(0).unwrap());
+        // Hack has no U+01FC, and neither face has U+4E2D
+        let text = "ab\u{1fc}c\u{301}\n\u{4e2d}\td";
+        let runs: Vec<_> = font_runs(&[&hack, &fira], text)
+            .into_iter()
+            .map(|run| (&text[run.text], run.face))
+            .collect();
+        assert_eq!(
+            runs,
+            vec![
+                ("ab", Some(0)),
+                ("\u{1fc}", Some(1)),
+                ("c\u{301}\n", Some(0)),
+                ("\u{4e2d}\t", None),
+                ("d", Some(0)),
+            ]
+        );
+        assert_eq!(
+            font_runs(&[], "a"),
+            vec![FontRun {
+                text: 0..1,
+                face: None
+            }]
+        );
+    }
+
+    #[test]
+    fn test_face_info() {
+        for (font, format) in &[
+            ("Hack-Regular.ttf", FontFormat::TrueType),
+            ("FiraCode-Regular.otf", FontFormat::OpenTypeCff),
+        ] {
+            let fc = FontCollection::new(get_path(font)).unwrap();
+            let face = fc.get_face(0).unwrap();
+            let info = fc.face_info(0).unwrap();
+            assert_eq!(info.family_name, face.family_name());
+            assert_eq!(info.style_name, face.style_name());
+            assert_eq!(info.num_glyphs, face.maxp.num_glyphs());
+            assert_eq!(info.format, *format);
+            assert_eq!(info.axes, face.variation_axes());
+            assert!(fc.face_info(1).is_err());
+        }
+    }
+
+    #[test]
+    fn test_layout_grid() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face
+            .scale(12.0, 12.0, 96, 96)
+            .with_rounding(Rounding::Round);
+        assert_eq!(scaled.cell_width().unwrap(), 10.0);
+        let text = "a\tb\u{4e2d}e\u{301}x";
+        let clusters = scaled.layout_grid(text, 4).unwrap();
+        let cells = clusters
+            .iter()
+            .map(|c| (&text[c.text.clone()], c.column, c.width, c.glyph_ids.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            vec![
+                ("a", 0, 1, 1),
+                ("b", 4, 1, 1),
+                ("\u{4e2d}", 5, 2, 1),
+                ("e\u{301}", 7, 1, 2),
+                ("x", 8, 1, 1),
+            ]
+        );
+        assert_eq!(
+            clusters[3].glyph_ids[0],
+            face.get_glyph_id('e' as u32).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_position_run() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face
+            .scale(12.0, 12.0, 96, 96)
+            .with_rounding(Rounding::Round);
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        let glyphs = [h, a, h];
+        // Second glyph is 1 pixel narrower, shifted right by 1 pixel and up by 2 pixels
+        let adjustments = [
+            GlyphAdjustment::default(),
+            GlyphAdjustment {
+                x_placement: 128.0,
+                y_placement: 256.0,
+                x_advance: -128.0,
+                y_advance: 0.0,
+            },
+        ];
+        let placed = |direction| {
+            let (glyphs, width) = scaled
+                .position_run(&glyphs, &adjustments, direction)
+                .unwrap();
+            let glyphs = glyphs
+                .iter()
+                .map(|g| (g.index, g.glyph_id, g.x, g.y))
+                .collect::<Vec<_>>();
+            (glyphs, width)
+        };
+        assert_eq!(
+            placed(Direction::LeftToRight),
+            (
+                vec![(0, h, 0.0, 0.0), (1, a, 11.0, -2.0), (2, h, 19.0, 0.0)],
+                29.0
+            )
+        );
+        assert_eq!(
+            placed(Direction::RightToLeft),
+            (
+                vec![(2, h, 0.0, 0.0), (1, a, 11.0, -2.0), (0, h, 19.0, 0.0)],
+                29.0
+            )
+        );
+        let (glyphs, width) = scaled
+            .position_run(&[], &[], Direction::RightToLeft)
+            .unwrap();
+        assert!(glyphs.is_empty());
+        assert_eq!(width, 0.0);
+    }
+
+    #[test]
+    fn test_transform_case() {
+        let upper = |text, lang| transform_case(text, TextCase::Uppercase, lang);
+        let lower = |text, lang| transform_case(text, TextCase::Lowercase, lang);
+        let title = |text, lang| transform_case(text, TextCase::Titlecase, lang);
+        assert_eq!(upper("straße", None), "STRASSE");
+        assert_eq!(lower("ΣΑΣ", None), "σας");
+        assert_eq!(upper("istanbul", None), "ISTANBUL");
+        assert_eq!(upper("istanbul", Some("tr")), "\u{130}STANBUL");
+        assert_eq!(
+            lower("D\u{130}YARBAKIR", Some("az-Latn")),
+            "diyarbak\u{131}r"
+        );
+        assert_eq!(lower("I\u{307}I", Some("TR")), "i\u{131}");
+        assert_eq!(lower("IZMIR", Some("en")), "izmir");
+        assert_eq!(
+            title("hello wORLD, it's \u{1c6}ungla", None),
+            "Hello World, It's \u{1c5}ungla"
+        );
+        assert_eq!(title("e\u{301}te 2nd", None), "E\u{301}te 2nd");
+        assert_eq!(title("izmir ISPARTA", Some("tr")), "\u{130}zmir Isparta");
+        assert_eq!(title("  ", None), "  ");
+    }
+
+    #[test]
+    fn test_width() {
+        assert_eq!(width::char_width('a'), 1);
+        assert_eq!(width::char_width('\u{4e2d}'), 2);
+        assert_eq!(width::char_width('\u{ff21}'), 2);
+        assert_eq!(width::char_width('\u{301}'), 0);
+        assert_eq!(width::char_width('\u{1f600}'), 2);
+        assert!(width::is_emoji_presentation('\u{1f600}'));
+        assert!(!width::is_emoji_presentation('\u{263a}'));
+        assert_eq!(width::str_width("a\u{263a}b"), 3);
+        assert_eq!(width::str_width("a\u{263a}\u{fe0f}b"), 4);
+        assert_eq!(width::str_width("\u{1f600}\u{fe0f}"), 2);
+        assert_eq!(
+            width::str_width("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}"),
+            2
+        );
+        assert_eq!(width::str_width("\u{2764}\u{fe0f}\u{200d}\u{1f525}"), 2);
+        assert_eq!(width::str_width("\u{1f1e9}\u{1f1ea}\u{1f1eb}"), 4);
+        assert_eq!(width::str_width("a\u{200d}b"), 2);
+
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let text = "\u{263a}\u{fe0f}x";
+        let clusters = scaled.layout_grid(text, 8).unwrap();
+        assert_eq!((clusters[0].width, clusters[1].column), (2, 2));
+        assert_eq!(width::str_width(text), 3);
+
+        // Flag, lone regional indicator, family ZWJ sequence, and emoji with a skin tone
+        // modifier. Hack has none of these, or ZWJ, so only three-glyph sequences are combined,
+        // by a `ccmp` ligature of .notdef glyphs
+        #[rustfmt::skip]
+        let gsub = be(&[
+            1, 0, 10, 30, 44,
+            // Script list
+            1, 0x4446, 0x4c54, 8,
+            4, 0,
+            0, 0xffff, 1, 0,
+            // Feature list
+            1, 0x6363, 0x6d70, 8,
+            0, 1, 0,
+            // Lookup list
+            1, 4,
+            4, 0, 1, 8,
+            1, 20, 1, 8, 1, 4, 501, 3, 0, 0, 1, 1, 0,
+        ]);
+        let face = face_with_tables(&[(b"GSUB", gsub)]);
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let text = "\u{1f1e9}\u{1f1ea}\u{1f1eb}\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\
+                    \u{1f44d}\u{1f3fd}x";
+        let clusters = scaled.layout_grid(text, 8).unwrap();
+        let cells = clusters
+            .iter()
+            .map(|c| (c.text.clone(), c.column, c.glyph_ids.clone(), c.decomposed))
+            .collect::<Vec<_>>();
+        let notdef = GlyphID::NOTDEF;
+        assert_eq!(
+            cells,
+            vec![
+                (0..8, 0, vec![notdef, notdef], true),
+                (8..12, 2, vec![notdef], false),
+                (12..30, 4, vec![GlyphID(501)], false),
+                (30..38, 6, vec![notdef, notdef], true),
+                (
+                    38..39,
+                    8,
+                    vec![face.get_glyph_id('x' as u32).unwrap()],
+                    false
+                ),
+            ]
+        );
+        assert_eq!(width::str_width(text), 9);
+    }
+
+    #[test]
+    fn test_affine() {
+        let close = |(x0, y0): (f32, f32), (x1, y1): (f32, f32)| {
+            (x0 - x1).abs() < 1e-5 && (y0 - y1).abs() < 1e-5
+        };
+        let quarter = std::f32::consts::FRAC_PI_2;
+        assert!(close(Affine::rotation(quarter).apply(1.0, 0.0), (0.0, 1.0)));
+        let affine = Affine::translation(1.0, 2.0)
+            .scaled(2.0, 3.0)
+            .rotated(quarter);
+        assert!(close(affine.apply(0.0, 0.0), (-6.0, 2.0)));
+        assert!(close(affine.apply_vector(1.0, 0.0), (0.0, 2.0)));
+        let inverse = affine.invert().unwrap();
+        assert!(close(inverse.apply(-6.0, 2.0), (0.0, 0.0)));
+        assert!(close(affine.then(&inverse).apply(3.0, 4.0), (3.0, 4.0)));
+        assert_eq!(Affine::scaling(0.0, 1.0).invert(), None);
+
+        let script = ScriptTransform {
+            scale_x: 0.5,
+            scale_y: 0.5,
+            offset_x: 1.0,
+            offset_y: -2.0,
+        };
+        assert!(close(
+            script.to_affine().apply(4.0, 4.0),
+            script.apply(4.0, 4.0)
+        ));
+
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let path = face.scale(12.0, 12.0, 96, 96).text_to_path("H").unwrap();
+        let moved = path.transformed(&Affine::translation(10.0, 0.0));
+        match (path.ops()[0], moved.ops()[0]) {
+            (PathOp::MoveTo(x0, y0), PathOp::MoveTo(x1, y1)) => {
+                assert!(close((x0 + 10.0, y0), (x1, y1)))
+            }
+            _ => panic!("path doesn't start with MoveTo"),
+        }
+    }
+
+    #[test]
+    fn test_geometry() {
+        let rect = Rect::new(0.0, 0.0, 4.0, 2.0);
+        assert_eq!((rect.width(), rect.height()), (4.0, 2.0));
+        assert_eq!(rect.center(), Point::new(2.0, 1.0));
+        assert!(rect.contains(Point::new(4.0, 1.0)) && !rect.contains(Point::new(5.0, 1.0)));
+        let union = rect.union(&Rect::new(-1.0, 1.0, 2.0, 3.0));
+        assert_eq!(union, Rect::new(-1.0, 0.0, 4.0, 3.0));
+        assert!(Rect::new(1.0, 1.0, 1.0, 2.0).is_empty());
+
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let h = face.get_glyph_id('H' as u32).unwrap();
+        let bounds = face.glyph_bounds(h).unwrap().unwrap();
+        assert_eq!(bounds.max.y, face.cap_height().unwrap() as f32);
+        assert_eq!(
+            face.glyph_bounds(face.get_glyph_id(' ' as u32).unwrap())
+                .unwrap(),
+            None
+        );
+        // At 12pt and 96 DPI, a font unit is 1/128 px. Paths have y increasing downwards
+        let path = face.scale(12.0, 12.0, 96, 96).text_to_path("H").unwrap();
+        let path_bounds = path.bounds().unwrap();
+        assert!((path_bounds.min.y + bounds.max.y / 128.0).abs() < 1e-3);
+        assert!((path_bounds.width() - bounds.width() / 128.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_glyph_points() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        // 'H' is a single contour of straight lines, and 'o' has two contours with curves
+        let h = face.get_glyph_id('H' as u32).unwrap();
+        let points: Vec<_> = face.glyph_points(h).unwrap().collect();
+        assert_eq!(points.iter().filter(|p| p.contour_end).count(), 1);
+        assert!(points.last().unwrap().contour_end);
+        assert!(points.iter().all(|p| p.on_curve));
+        let bounds = face.glyph_bounds(h).unwrap().unwrap();
+        assert_eq!(points.iter().map(|p| p.y).max(), Some(bounds.max.y as i16));
+        assert_eq!(points.iter().map(|p| p.x).min(), Some(bounds.min.x as i16));
+
+        let o = face.get_glyph_id('o' as u32).unwrap();
+        let points: Vec<_> = face.glyph_points(o).unwrap().collect();
+        assert_eq!(points.iter().filter(|p| p.contour_end).count(), 2);
+        assert!(points.iter().any(|p| !p.on_curve));
+
+        let space = face.get_glyph_id(' ' as u32).unwrap();
+        assert_eq!(face.glyph_points(space).unwrap().count(), 0);
+        assert!(face.glyph_instructions(space).unwrap().is_empty());
+        assert!(!face.glyph_instructions(o).unwrap().is_empty());
+        assert!(face.glyph_components(o).unwrap().is_empty());
+        // Repeat flags are expanded
+        assert!(points.iter().all(|p| p.flags & 0x08 == 0));
+        assert!(points.iter().all(|p| p.on_curve == (p.flags & 0x01 != 0)));
+
+        // Hack has no composite glyphs. This one has two components, with instructions
+        let data = be(&[
+            -1, 0, 0, 0, 0, 0x0023, 5, 10, 20, 0x0102, 7, 0x0102, 2, 0xb000,
+        ]);
+        let composite = glyf::Glyf(&data).glyph(0..data.len()).unwrap();
+        let components = composite.components().unwrap();
+        let ids: Vec<_> = components.iter().map(|c| (c.glyph_id, c.flags)).collect();
+        assert_eq!(ids, vec![(GlyphID(5), 0x0023), (GlyphID(7), 0x0102)]);
+        assert_eq!(composite.instructions().unwrap(), &[0xb0, 0x00]);
+        assert!(composite.points().is_err());
+    }
+
+    #[test]
+    fn test_space_advance() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let space = |c| face.space_advance(c).unwrap();
+        assert_eq!(space('a'), None);
+        let thin = space('\u{2009}').unwrap();
+        assert_eq!((thin.advance, thin.synthesized), (1233.0, false));
+        let ideographic = space('\u{3000}').unwrap();
+        assert_eq!(
+            (ideographic.advance, ideographic.synthesized),
+            (2466.0, true)
+        );
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let advance = scaled.space_advance('\u{3000}').unwrap().unwrap().advance;
+        assert!((advance - 2466.0 / 128.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_line_metrics_combine() {
+        let metrics = |ascender, descender, line_gap| LineMetrics {
+            ascender,
+            descender,
+            line_gap,
+        };
+        let runs = vec![metrics(10.0, -3.0, 2.0), metrics(8.0, -6.0, 0.0)];
+        let line = LineMetrics::combine(runs.clone(), None);
+        assert_eq!(line, metrics(11.0, -6.0, 0.0));
+        assert_eq!(line.line_height(), 17.0);
+        let line = LineMetrics::combine(runs, Some(metrics(12.0, -4.0, 4.0)));
+        assert_eq!(line, metrics(14.0, -6.0, 0.0));
+        let line = LineMetrics::combine(None, Some(metrics(12.0, -4.0, 4.0)));
+        assert_eq!(line.line_height(), 20.0);
+        assert_eq!(LineMetrics::combine(None, None).line_height(), 0.0);
+    }
+
+    #[test]
+    fn test_optical_size() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let style = face.style();
+        // Text cut for up to 14pt, display cut above, and one without size information
+        let mut fonts = Vec::new();
+        for &(design, start, end) in &[(100, 0, 140), (240, 140, 720)] {
+            #[rustfmt::skip]
+            let gpos = be(&[
+                1, 0, 0, 10, 32,
+                1, 0x7369, 0x7a65, 8, 4, 0, design, 1, 256, start, end,
+                0,
+            ]);
+            let mut builder = FontBuilder::from_face(&face);
+            builder.add_table(Tag::new(b"GPOS"), gpos);
+            fonts.push(builder.build());
+        }
+        fonts.push(FontBuilder::from_face(&face).build());
+
+        // Collection of the faces, with table offsets moved past the collection header
+        let mut data = b"ttcf\0\x01\0\0".to_vec();
+        data.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+        let mut offset = 12 + fonts.len() * 4;
+        for font in &fonts {
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            offset += font.len();
+        }
+        for font in &fonts {
+            let font_off = data.len() as u32;
+            let mut font = font.clone();
+            for i in 0..get_u16(&font, 4).unwrap() as usize {
+                let off = 12 + i * 16 + 8;
+                let table_off = get_u32(&font, off).unwrap() + font_off;
+                font[off..off + 4].copy_from_slice(&table_off.to_be_bytes());
+            }
+            data.extend_from_slice(&font);
+        }
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let mut family = fc.families().remove(0);
+        assert_eq!(family.members.len(), 3);
+        let design_size_for = |family: &Family, size| {
+            let face = fc.face_for_optical_size(family, style, size).unwrap();
+            face.size_params().map(|params| params.design_size)
+        };
+        assert_eq!(design_size_for(&family, 9.0), Some(10.0));
+        assert_eq!(design_size_for(&family, 14.0), Some(10.0));
+        assert_eq!(design_size_for(&family, 36.0), Some(24.0));
+        assert_eq!(design_size_for(&family, 100.0), None);
+        family.members.retain(|m| m.index != 2);
+        assert_eq!(design_size_for(&family, 100.0), Some(24.0));
+        let bold = FaceStyle {
+            weight: 700,
+            ..style
+        };
+        assert!(fc.face_for_optical_size(&family, bold, 12.0).is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        assert_eq!(fc.get_face(0).unwrap().validate(), vec![]);
+
+        let mut data = std::fs::read(&path).unwrap();
+        let head = fc.get_face(0).unwrap().table(Tag::new(b"head")).unwrap();
+        let head_off = head.as_ptr() as usize - fc.bytes().as_ptr() as usize;
+        data[head_off + 12] = 0;
+        let fc = FontCollection::new_from(&data).unwrap();
+        let problems = fc.get_face(0).unwrap().validate();
+        assert_eq!(problems.len(), 3);
+        assert_eq!(problems[0], ValidationProblem::BadMagicNumber(0x000F3CF5));
+        match problems[1] {
+            ValidationProblem::TableChecksum { tag, .. } => assert_eq!(tag, Tag::new(b"head")),
+            ref problem => panic!("unexpected problem: {}", problem),
+        }
+        match problems[2] {
+            ValidationProblem::ChecksumAdjustment { stored, .. } => assert_eq!(stored, 0xCD77E18A),
+            ref problem => panic!("unexpected problem: {}", problem),
+        }
+    }
+
+    #[test]
+    fn test_parse_options() {
+        // Shorten the length of Hack's hmtx table in the table directory
+        let mut data = std::fs::read(get_path("Hack-Regular.ttf")).unwrap();
+        let len_off = 12 + 11 * 16 + 12;
+        assert_eq!(
+            Tag(get_u32(&data, len_off - 12).unwrap()),
+            Tag::new(b"hmtx")
+        );
+        data[len_off..len_off + 4].copy_from_slice(&(6232u32 - 40).to_be_bytes());
+        let hmtx_invalid = ValidationProblem::InvalidTable(Tag::new(b"hmtx"));
+        let strict = ParseOptions { strict: true };
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert!(!face.validate().contains(&hmtx_invalid));
+        assert!(face.is_monospace());
+        let fc = FontCollection::new_from_with_options(&data, strict).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert!(face.validate().contains(&hmtx_invalid));
+
+        // Truncate the DSIG table at the end of the file
+        data.truncate(data.len() - 4);
+        let fc = FontCollection::new_from(&data).unwrap();
+        assert_eq!(
+            fc.get_face(0)
+                .unwrap()
+                .table(Tag::new(b"DSIG"))
+                .unwrap()
+                .len(),
+            4
+        );
+        let fc = FontCollection::new_from_with_options(&data, strict).unwrap();
+        assert!(fc.get_face(0).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut data = std::fs::read(get_path("Hack-Regular.ttf")).unwrap();
+        let strict = ParseOptions { strict: true };
+        let err = FontCollection::new_from_with_options(&data[..data.len() - 4], strict)
+            .and_then(|fc| fc.get_face(0).map(|_| ()))
+            .unwrap_err();
+        match err {
+            Error::Truncated {
+                table: Some(tag), ..
+            } => assert_eq!(tag, Tag::new(b"DSIG")),
+            err => panic!("unexpected error: {}", err),
+        }
+        match FontCollection::new_from(b"\0\0\0\0\0\0\0\0") {
+            Err(Error::UnknownFormat) => (),
+            _ => panic!("expected unknown format"),
+        }
+
+        // Rename the cmap table in the table directory
+        let rec_off = (0..data.len())
+            .step_by(16)
+            .map(|off| off + 12)
+            .find(|&off| &data[off..off + 4] == b"cmap")
+            .unwrap();
+        data[rec_off..rec_off + 4].copy_from_slice(b"xmap");
+        let fc = FontCollection::new_from(&data).unwrap();
+        match fc.get_face(0).unwrap().get_glyph_id('A' as u32) {
+            Err(Error::MissingTable(tag)) => assert_eq!(tag, Tag::new(b"cmap")),
+            _ => panic!("expected missing cmap"),
+        }
+    }
+
+    #[test]
+    fn test_subset() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let codepoints = "Hé!".chars().map(|c| c as u32).collect::<Vec<_>>();
+        let data = face.subset(&codepoints, &[]).unwrap();
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let subset = fc.get_face(0).unwrap();
+        assert_eq!(subset.validate(), vec![]);
+        // .notdef, "H", "é", and "!", in their original order
+        assert_eq!(subset.maxp.num_glyphs(), 4);
+        assert_eq!(subset.get_glyph_id('H' as u32).unwrap(), GlyphID(1));
+        assert_eq!(subset.get_glyph_id('é' as u32).unwrap(), GlyphID(2));
+        assert_eq!(subset.get_glyph_id('!' as u32).unwrap(), GlyphID(3));
+        assert_eq!(subset.get_glyph_id('A' as u32).unwrap(), GlyphID(0));
+        assert!(subset.is_monospace());
+        assert!(subset.table(Tag::new(b"GSUB")).is_none());
+        assert_eq!(subset.lookup_glyph_id(0xffff).unwrap(), None);
+
+        // The last format 4 segment maps U+FFFF to .notdef, with an idDelta of 1
+        let cmap = subset::build_cmap(vec![(0x41, 1), (0xffff, 2)].into_iter());
+        let seg_count_x2 = get_u16(&cmap, 12 + 6).unwrap() as usize;
+        assert_eq!(seg_count_x2, 4);
+        assert_eq!(get_u16(&cmap, 12 + 14 + seg_count_x2 * 3).unwrap(), 1);
+        let table = cmap::Cmap::load(&cmap).unwrap();
+        assert_eq!(table.lookup(0x41).unwrap(), Some(GlyphID(1)));
+        assert_eq!(table.lookup(0xffff).unwrap(), None);
+
+        // Segments that don't fit in format 4 are left to format 12
+        let cmap = subset::build_cmap((1..10000).map(|i| (i * 2, i as u16)));
+        assert_eq!(get_u16(&cmap, 2).unwrap(), 2);
+        let format4_len = get_u16(&cmap, 20 + 2).unwrap() as usize;
+        assert_eq!(format4_len, 16 + 8189 * 8);
+        assert_eq!(get_u32(&cmap, 12 + 4).unwrap() as usize, 20 + format4_len);
+        let table = cmap::Cmap::load(&cmap).unwrap();
+        assert_eq!(table.lookup(19998).unwrap(), Some(GlyphID(9999)));
+
+        let path = get_path("FiraCode-Regular.otf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert!(face.subset(&codepoints, &[]).is_err());
+    }
+
+    #[test]
+    fn test_pdf() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(
+            face.pdf_descriptor(),
+            PdfFontDescriptor {
+                flags: 33,
+                font_bbox: [-466, -295, 662, 990],
+                italic_angle: 0.0,
+                ascent: 928,
+                descent: -236,
+                cap_height: 729,
+                stem_v: 43,
+            }
+        );
+        assert_eq!(face.pdf_widths(&[0x41, 0x42]).unwrap(), vec![602, 602]);
+
+        let subset = face.pdf_subset(&[GlyphID(37)]).unwrap();
+        assert_eq!(subset.widths, vec![(0, 602), (37, 602)]);
+        assert_eq!(subset.cid_to_gid_map.len(), 38 * 2);
+        assert_eq!(&subset.cid_to_gid_map[74..], &[0, 1]);
+        let fc = FontCollection::new_from(&subset.font_file).unwrap();
+        assert_eq!(fc.get_face(0).unwrap().maxp.num_glyphs(), 2);
+    }
+
+    #[test]
+    fn test_strip() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let mut options = StripOptions::hinting();
+        options.gsub_features.push(Tag::new(b"frac"));
+        let data = face.strip(&options).unwrap();
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let stripped = fc.get_face(0).unwrap();
+        assert_eq!(stripped.validate(), vec![]);
+        for tag in &[b"fpgm", b"prep", b"cvt ", b"DSIG"] {
+            assert!(stripped.table(Tag::new(tag)).is_none());
+        }
+        let glyf = Tag::new(b"glyf");
+        assert!(stripped.table(glyf).unwrap().len() < face.table(glyf).unwrap().len());
+        let render = |face: &Face| {
+            let face = face.scale(12.0, 12.0, 96, 96);
+            let id = face.get_glyph_id('R' as u32).unwrap();
+            face.get_glyph(id)
+                .unwrap()
+                .render()
+                .unwrap()
+                .as_slice()
+                .to_vec()
+        };
+        assert_eq!(render(&stripped), render(&face));
+
+        // Both frac features should have no lookups
+        let gsub = stripped.table(Tag::new(b"GSUB")).unwrap();
+        let list = get_u16(gsub, 6).unwrap() as usize;
+        let lookups = (0..get_u16(gsub, list).unwrap() as usize)
+            .map(|i| list + 2 + i * 6)
+            .filter(|&rec| Tag(get_u32(gsub, rec).unwrap()) == Tag::new(b"frac"))
+            .map(|rec| get_u16(gsub, list + get_u16(gsub, rec + 4).unwrap() as usize + 2))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(lookups, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let codepoints = |s: &str| s.chars().map(|c| c as u32).collect::<Vec<_>>();
+        let first = face.subset(&codepoints("AB"), &[]).unwrap();
+        let second = face.subset(&codepoints("BC"), &[]).unwrap();
+        let first = FontCollection::new_from(&first).unwrap();
+        let second = FontCollection::new_from(&second).unwrap();
+        let (first, second) = (first.get_face(0).unwrap(), second.get_face(0).unwrap());
+        let data = merge(&[&first, &second]).unwrap();
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let merged = fc.get_face(0).unwrap();
+        assert_eq!(merged.validate(), vec![]);
+        let num_first = first.maxp.num_glyphs() as u32;
+        assert_eq!(
+            merged.maxp.num_glyphs() as u32,
+            num_first + second.maxp.num_glyphs() as u32
+        );
+        for c in "AB".chars() {
+            let id = first.get_glyph_id(c as u32).unwrap();
+            assert_eq!(merged.get_glyph_id(c as u32).unwrap(), id);
+        }
+        let id = second.get_glyph_id('C' as u32).unwrap();
+        assert_eq!(
+            merged.get_glyph_id('C' as u32).unwrap(),
+            GlyphID(id.0 + num_first)
+        );
+
+        assert_eq!(merged.lookup_glyph_id(0xffff).unwrap(), None);
+
+        // Format 12 groups are clipped where they overlap, and mappings to .notdef don't hide
+        // later faces' glyphs. 'A' maps to .notdef, then 1000 groups cover all codepoints
+        let mut groups = vec![(0x41, 0x41, 0)];
+        groups.extend((0..1000).map(|_| (0x20, 0x10ffff, 1)));
+        let mut words = vec![0, 1, 3, 10, 0, 12, 12, 0];
+        words.extend_from_slice(&[
+            0,
+            16 + groups.len() as i32 * 12,
+            0,
+            0,
+            0,
+            groups.len() as i32,
+        ]);
+        for &(start, end, id) in &groups {
+            words.extend_from_slice(&[start >> 16, start, end >> 16, end, 0, id]);
+        }
+        let base = face_with_tables(&[(b"cmap", be(&words))]);
+        let data = merge(&[&base, &face_with_tables(&[])]).unwrap();
+        let fc = FontCollection::new_from(&data).unwrap();
+        let merged = fc.get_face(0).unwrap();
+        assert_eq!(
+            merged.get_glyph_id('A' as u32).unwrap(),
+            GlyphID(1573 + 1425)
+        );
+        assert_eq!(
+            merged.get_glyph_id('B' as u32).unwrap(),
+            GlyphID(0x42 - 0x20 + 1)
+        );
+
+        // Variation tables of the base face are dropped as a set
+        let variable = face_with_tables(&[
+            (b"fvar", vec![0; 16]),
+            (b"avar", vec![0; 8]),
+            (b"STAT", vec![0; 20]),
+            (b"gvar", vec![0; 20]),
+            (b"HVAR", vec![0; 20]),
+        ]);
+        let data = merge(&[&variable, &face_with_tables(&[])]).unwrap();
+        let fc = FontCollection::new_from(&data).unwrap();
+        let merged = fc.get_face(0).unwrap();
+        for tag in [b"fvar", b"avar", b"STAT", b"gvar", b"HVAR"].iter() {
+            assert!(merged.table(Tag::new(tag)).is_none());
+        }
+        assert_eq!(merged.validate(), vec![]);
+
+        let path = get_path("FiraCode-Regular.otf");
+        let fc = FontCollection::new(&path).unwrap();
+        assert!(merge(&[&first, &fc.get_face(0).unwrap()]).is_err());
+    }
+
+    #[test]
+    fn test_precompute_glyph_index() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let space = face.get_glyph_id(' ' as u32).unwrap();
+        let h = face.get_glyph_id('H' as u32).unwrap();
+        assert!(!face.has_outline(space).unwrap());
+        assert!(face.has_outline(h).unwrap());
+        let subset = face.subset(&['H' as u32], &[]).unwrap();
+        face.precompute_glyph_index().unwrap();
+        assert!(!face.has_outline(space).unwrap());
+        assert!(face.has_outline(h).unwrap());
+        assert!(face.has_outline(GlyphID(1573)).is_err());
+        assert_eq!(face.subset(&['H' as u32], &[]).unwrap(), subset);
+    }
+
+    #[test]
+    fn test_render_empty_glyph() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let space = scaled.get_glyph_id(' ' as u32).unwrap();
+        let bitmap = scaled.get_glyph(space).unwrap().render().unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (0, 0));
+    }
+
+    #[test]
+    fn test_rounding() {
+        // 1233 units at 16 ppem is 9.63 pixels
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let h = face.get_glyph_id('H' as u32).unwrap();
+        let advance = |rounding| {
+            let scaled = face.scale(12.0, 12.0, 96, 96).with_rounding(rounding);
+            scaled.advance(h).unwrap()
+        };
+        assert_eq!(advance(Rounding::None), 1233.0 * 16.0 / 2048.0);
+        assert_eq!(advance(Rounding::Floor), 9.0);
+        assert_eq!(advance(Rounding::Round), 10.0);
+        let scaled = face
+            .scale(12.0, 12.0, 96, 96)
+            .with_rounding(Rounding::Round);
+        let mut advances = [0.0; 3];
+        let ids = [GlyphID(0), h, GlyphID(1572)];
+        scaled.advances(&ids, &mut advances).unwrap();
+        assert_eq!(advances, [10.0; 3]);
+        assert!(scaled.advances(&[GlyphID(1573)], &mut [0.0]).is_err());
+    }
+
+    #[test]
+    fn test_text_to_path() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        assert!(scaled.text_to_path(" ").unwrap().is_empty());
+        // The second 'H' is offset by one advance
+        let one = scaled.text_to_path("H").unwrap();
+        let two = scaled.text_to_path("HH").unwrap();
+        assert_eq!(one.ops().len() * 2, two.ops().len());
+        let advance = scaled.advance(GlyphID(37)).unwrap();
+        match (one.ops()[0], two.ops()[one.ops().len()]) {
+            (PathOp::MoveTo(x0, y0), PathOp::MoveTo(x1, y1)) => {
+                assert!((x1 - x0 - advance).abs() < 1e-3);
+                assert_eq!(y0, y1);
+            }
+            _ => panic!("contours must start with a move"),
+        }
+        assert!(one.to_svg().starts_with('M'));
+
+        // Hack has no mark attachment, so the acute is moved up from its lowercase position
+        // to 1/16 em above 'E', and centered on it
+        assert!(!face.has_mark_attachment());
+        let bounds = |ops: &[PathOp]| {
+            let init = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+            ops.iter().fold(init, |(x0, y0, x1, y1), op| {
+                let (x, y) = match *op {
+                    PathOp::MoveTo(x, y) | PathOp::LineTo(x, y) | PathOp::QuadTo(_, (x, y)) => {
+                        (x, y)
+                    }
+                };
+                (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+            })
+        };
+        let e = scaled.text_to_path("E").unwrap();
+        let accented = scaled.text_to_path("E\u{301}").unwrap();
+        let (e_x0, e_top, e_x1, _) = bounds(e.ops());
+        let (mark_x0, _, mark_x1, mark_bottom) = bounds(&accented.ops()[e.ops().len()..]);
+        assert!((e_top - 1.0 - mark_bottom).abs() < 0.01);
+        assert!(((e_x0 + e_x1) - (mark_x0 + mark_x1)).abs() < 0.01);
+    }
+
+    #[cfg(feature = "capi")]
+    #[test]
+    fn test_capi() {
+        use capi::*;
+        use std::ptr;
+        let data = std::fs::read(get_path("Hack-Regular.ttf")).unwrap();
+        unsafe {
+            let mut collection = ptr::null_mut();
+            let status = rype_collection_new(data.as_ptr(), data.len(), &mut collection);
+            assert_eq!(status, RypeStatus::Ok);
+            let mut face = ptr::null_mut();
+            assert_eq!(rype_face_new(collection, 0, &mut face), RypeStatus::Ok);
+            let mut glyph_id = 0;
+            assert_eq!(
+                rype_face_glyph_id(face, 'H' as u32, &mut glyph_id),
+                RypeStatus::Ok
+            );
+            assert_eq!(glyph_id, 37);
+            let mut scaled = ptr::null_mut();
+            let status = rype_scaled_face_new(face, 12.0, 12.0, 96, 96, &mut scaled);
+            assert_eq!(status, RypeStatus::Ok);
+            // Query the bitmap size, then render
+            let mut info = RypeBitmapInfo::default();
+            let status = rype_scaled_face_render(scaled, glyph_id, &mut info, ptr::null_mut(), 0);
+            assert_eq!(status, RypeStatus::BufferTooSmall);
+            let mut buffer = vec![0; info.width * info.height];
+            let status = rype_scaled_face_render(
+                scaled,
+                glyph_id,
+                &mut info,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            );
+            assert_eq!(status, RypeStatus::Ok);
+            let fc = FontCollection::new_from(&data).unwrap();
+            let rust_face = fc.get_face(0).unwrap();
+            let rust_scaled = rust_face.scale(12.0, 12.0, 96, 96);
+            let glyph = rust_scaled.get_glyph(GlyphID(glyph_id)).unwrap();
+            let bitmap = glyph.render().unwrap();
+            assert!(buffer.chunks(info.width).eq(bitmap.rows()));
+            assert_eq!(
+                rype_face_glyph_id(face, 'H' as u32, ptr::null_mut()),
+                RypeStatus::NullPointer
+            );
+            rype_scaled_face_free(scaled);
+            rype_face_free(face);
+            rype_collection_free(collection);
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.family_name().unwrap(), "Hack");
+        assert_eq!(face.style_name().unwrap(), "Regular");
+        assert_eq!(
+            face.style(),
+            FaceStyle {
+                weight: 400,
+                stretch: 5,
+                italic: false
+            }
+        );
+    }
+
+    #[cfg(feature = "system")]
+    #[test]
+    fn test_system_font_matching() {
+        use system::{best_match, SystemFont};
+        let font = |family: &str, weight, italic, monospace| SystemFont {
+            path: PathBuf::new(),
+            index: 0,
+            family: family.to_owned(),
+            weight,
+            italic,
+            monospace,
+        };
+        let fonts = vec![
+            font("Hack", 400, false, true),
+            font("Hack", 700, false, true),
+            font("Hack", 400, true, true),
+            font("Liberation Mono", 400, false, true),
+            font("Noto Sans", 400, false, false),
+        ];
+        let found = best_match(&fonts, "hack", 600, false).unwrap();
+        assert_eq!((found.weight, found.italic), (700, false));
+        let found = best_match(&fonts, "Hack", 700, true).unwrap();
+        assert_eq!((found.weight, found.italic), (400, true));
+        let found = best_match(&fonts, "monospace", 700, false).unwrap();
+        assert_eq!(found.family, "Liberation Mono");
+        assert!(best_match(&fonts, "serif", 400, false).is_none());
+        assert!(best_match(&fonts[..3], "monospace", 400, false).is_some());
+    }
+
+    #[test]
+    fn test_from_shared() {
+        // Tables point into the shared buffer, which isn't copied
+        let data = Arc::new(std::fs::read(get_path("Hack-Regular.ttf")).unwrap());
+        let fc = FontCollection::from_shared(data.clone()).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let head = face.table(Tag::new(b"head")).unwrap();
+        assert!(data.as_ptr_range().contains(&head.as_ptr()));
+        assert_eq!(face.get_glyph_id('H' as u32).unwrap(), GlyphID(37));
+    }
+
+    #[test]
+    fn test_from_provider() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let tables = face
+            .tables()
+            .map(|(tag, data)| (tag, data.to_vec()))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let face = Face::from_provider(&tables, ParseOptions::default()).unwrap();
+        assert!(face.validate().is_empty());
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let h = scaled.get_glyph_id('H' as u32).unwrap();
+        assert_eq!(h, GlyphID(37));
+        assert!(scaled.get_glyph(h).unwrap().render().is_ok());
+    }
+
+    #[test]
+    fn test_glyph_id_conversions() {
+        assert_eq!(GlyphID::new(37), GlyphID::from(37u16));
+        assert_eq!(GlyphID::new(37).to_u32(), 37);
+        assert_eq!(u32::from(GlyphID::NOTDEF), 0);
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.get_glyph_id(0x10ffff).unwrap(), GlyphID::NOTDEF);
+        assert!(face.horizontal_metrics(GlyphID::new(1573)).is_err());
+    }
+
+    #[test]
+    fn test_lookup_glyph_id() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.lookup_glyph_id('H' as u32).unwrap(), Some(GlyphID(37)));
+        assert_eq!(face.lookup_glyph_id(0x10ffff).unwrap(), None);
+        assert_eq!(face.lookup_glyph_id(0xe000).unwrap(), None);
+        assert_eq!(face.lookup_glyph_id(0xffff).unwrap(), None);
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        assert!(scaled.notdef_bitmap().unwrap().width > 0);
+    }
+
+    #[test]
+    fn test_x_height_cap_height() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.x_height(), Some(1120));
+        assert_eq!(face.cap_height(), Some(1493));
+        assert_eq!(face.glyph_top('H'), Some(1493));
+        assert_eq!(
+            face.scale(12.0, 12.0, 96, 96).cap_height(),
+            Some(1493.0 / 128.0)
+        );
+
+        // Without OS/2, the outlines are measured
+        let mut tables = face
+            .tables()
+            .map(|(tag, data)| (tag, data.to_vec()))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        tables.remove(&Tag::new(b"OS/2"));
+        let face = Face::from_provider(&tables, ParseOptions::default()).unwrap();
+        assert_eq!(face.x_height(), face.glyph_top('x'));
+        assert_eq!(face.cap_height(), Some(1493));
+    }
+
+    #[test]
+    fn test_kerning() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        assert_eq!(face_with_tables(&[]).kerning(h, a), 0);
+
+        // Legacy kern table with a single format 0 subtable
+        #[rustfmt::skip]
+        let kern = be(&[
+            0, 1,
+            0, 26, 0x0001, 2, 12, 1, 0,
+            37, 1425, -50,
+            1425, 37, -20,
+        ]);
+        let face = face_with_tables(&[(b"kern", kern.clone())]);
+        assert_eq!(face.kerning(h, a), -50);
+        assert_eq!(face.kerning(a, h), -20);
+        assert_eq!(face.kerning(h, h), 0);
+        // A truncated subtable doesn't discard values from the others
+        #[rustfmt::skip]
+        let truncated = be(&[
+            0, 2,
+            0, 20, 0x0001, 1, 6, 0, 0,
+            37, 1425, -50,
+            0, 20, 0x0001, 2, 12, 1, 0,
+            1425, 37, -20,
+        ]);
+        assert_eq!(face_with_tables(&[(b"kern", truncated)]).kerning(h, a), -50);
+
+        // GPOS takes priority. One kern lookup with glyph pairs, one with class pairs
+        #[rustfmt::skip]
+        let gpos = be(&[
+            1, 0, 0, 10, 26,
+            // Feature list
+            1, 0x6b65, 0x726e, 8, 0, 2, 0, 1,
+            // Lookup list
+            2, 6, 48,
+            2, 0, 1, 8,
+            1, 12, 0x44, 0, 1, 18, 1, 1, 37, 1, 1425, -100, 26,
+            // Device table with -1 and 2 pixel adjustments at 16 and 17 ppem
+            16, 17, 2, 0xf200,
+            2, 0, 1, 8,
+            2, 24, 4, 0, 30, 38, 2, 2, 0, 0, 0, -30,
+            1, 1, 37, 1, 37, 1, 1, 2, 1, 1425, 1425, 1,
+        ]);
+        let face = face_with_tables(&[(b"kern", kern), (b"GPOS", gpos)]);
+        assert_eq!(face.kerning(h, a), -130);
+        assert_eq!(face.kerning(a, h), 0);
+        let matrices = face.kerning_class_matrices();
+        assert_eq!(matrices.len(), 1);
+        assert_eq!(matrices[0].left_classes, vec![(h, 1)]);
+        assert_eq!(matrices[0].right_classes, vec![(a, 1)]);
+        assert_eq!((matrices[0].left_count, matrices[0].right_count), (2, 2));
+        assert_eq!(matrices[0].values, vec![0, 0, 0, -30]);
+        assert_eq!(matrices[0].value(1, 1), -30);
+        assert_eq!(matrices[0].value(2, 1), 0);
+
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        assert_eq!(scaled.kerning(h, a), -130.0 / 128.0 - 1.0);
+        let scaled = face.scale(12.0, 12.0, 72, 72);
+        assert_eq!(scaled.kerning(h, a), -130.0 * 12.0 / 2048.0);
+
+        // A class pair subtable with more records than it has room for is skipped. Overlapping
+        // Coverage and ClassDef ranges list each glyph once
+        let ranges = [2, 3, 0, 0xffff, 1, 0, 0xffff, 1, 0, 0xffff, 1];
+        let mut words = vec![1, 0, 0, 10, 26, 1, 0x6b65, 0x726e, 8, 0, 2, 0, 1, 2, 6, 52];
+        words.extend_from_slice(&[2, 0, 1, 8, 2, 16, 4, 0, 16, 16, 0xffff, 0xffff]);
+        words.extend_from_slice(&ranges);
+        words.extend_from_slice(&[2, 0, 1, 8, 2, 24, 4, 0, 24, 24, 2, 2, 0, 0, 0, -30]);
+        words.extend_from_slice(&ranges);
+        let face = face_with_tables(&[(b"GPOS", be(&words))]);
+        let matrices = face.kerning_class_matrices();
+        assert_eq!(matrices.len(), 1);
+        assert_eq!(matrices[0].left_classes.len(), 1573);
+        assert_eq!(matrices[0].right_classes.len(), 1573);
+        assert_eq!(matrices[0].value(1, 1), -30);
+    }
+
+    #[test]
+    fn test_gsub_max_len() {
+        // DFLT script with ccmp, whose 20 lookups all share one multiple substitution of 37 by
+        // 37, 37. Applying all of them would make 2^20 glyphs
+        let count = 20;
+        let mut words = vec![1, 0, 10, 30, 42 + count * 2];
+        words.extend_from_slice(&[1, 0x4446, 0x4c54, 8, 4, 0, 0, 0xffff, 1, 0]);
+        words.extend_from_slice(&[1, 0x6363, 0x6d70, 8, 0, count]);
+        words.extend(0..count);
+        words.push(count);
+        words.extend((0..count).map(|_| 2 + count * 2));
+        words.extend_from_slice(&[2, 0, 1, 8, 1, 8, 1, 14, 1, 1, 37, 2, 37, 37]);
+        let face = face_with_tables(&[(b"GSUB", be(&words))]);
+        let glyphs = face.apply_gsub(
+            &[GlyphID(37)],
+            &[Tag::new(b"ccmp")],
+            Tag::new(b"latn"),
+            None,
+        );
+        assert_eq!(glyphs.len(), 16384);
+        assert!(glyphs.iter().all(|&id| id == GlyphID(37)));
+    }
+
+    #[test]
+    fn test_apply_features() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        let (latn, cyrl) = (Tag::new(b"latn"), Tag::new(b"cyrl"));
+        let (liga, smcp) = (Tag::new(b"liga"), Tag::new(b"smcp"));
+        let (kern, cpsp) = (Tag::new(b"kern"), Tag::new(b"cpsp"));
+
+        // latn script with liga (ligature of 37 and 1425) and smcp (single substitution of 37).
+        // The ligature lookup has flag `flag` and mark filtering set 0
+        #[rustfmt::skip]
+        let gsub = |flag| be(&[
+            1, 0, 10, 32, 58,
+            // Script list
+            1, 0x6c61, 0x746e, 8,
+            4, 0,
+            0, 0xffff, 2, 0, 1,
+            // Feature list
+            2, 0x6c69, 0x6761, 14, 0x736d, 0x6370, 20,
+            0, 1, 1,
+            0, 1, 0,
+            // Lookup list
+            2, 6, 26,
+            1, 0, 1, 8,
+            1, 6, 1, 1, 1, 37,
+            4, flag, 1, 10, 0,
+            1, 18, 1, 8, 1, 4, 500, 2, 1425, 1, 1, 37,
+        ]);
+
+        // DFLT script with kern (pair adjustment of both glyphs) and cpsp (single adjustment).
+        // The pair adjustment lookup has flag `flag`
+        #[rustfmt::skip]
+        let gpos = |flag| be(&[
+            1, 0, 10, 32, 58,
+            // Script list
+            1, 0x4446, 0x4c54, 8,
+            4, 0,
+            0, 0xffff, 2, 0, 1,
+            // Feature list
+            2, 0x6370, 0x7370, 14, 0x6b65, 0x726e, 20,
+            0, 1, 1,
+            0, 1, 0,
+            // Lookup list
+            2, 6, 42,
+            2, flag, 1, 10, 0,
+            1, 20, 4, 1, 1, 12, 1, 1425, -50, 10, 1, 1, 37,
+            1, 0, 1, 8,
+            1, 8, 4, 20, 1, 1, 1425,
+        ]);
+        let face = face_with_tables(&[(b"GSUB", gsub(0)), (b"GPOS", gpos(0))]);
+
+        let glyphs = [h, a, h];
+        assert_eq!(
+            face.apply_gsub(&glyphs, &[liga], latn, None),
+            vec![GlyphID(500), h]
+        );
+        assert_eq!(
+            face.apply_gsub(&glyphs, &[smcp], latn, None),
+            vec![GlyphID(38), a, GlyphID(38)]
+        );
+        // Lookups apply in lookup list order, so smcp prevents the ligature
+        assert_eq!(
+            face.apply_gsub(&glyphs, &[liga, smcp], latn, Some(Tag::new(b"TRK "))),
+            vec![GlyphID(38), a, GlyphID(38)]
+        );
+        assert_eq!(
+            face.apply_gsub(&glyphs, &[liga], cyrl, None),
+            glyphs.to_vec()
+        );
+
+        let adjustment = |x_placement, x_advance| GlyphAdjustment {
+            x_placement,
+            x_advance,
+            ..GlyphAdjustment::default()
+        };
+        assert_eq!(
+            face.apply_gpos(&glyphs, &[kern, cpsp], cyrl, None),
+            vec![
+                adjustment(0.0, -50.0),
+                adjustment(10.0, 20.0),
+                adjustment(0.0, 0.0)
+            ]
+        );
+        assert_eq!(
+            face.apply_gpos(&glyphs, &[cpsp], latn, None),
+            vec![
+                adjustment(0.0, 0.0),
+                adjustment(0.0, 20.0),
+                adjustment(0.0, 0.0)
+            ]
+        );
+
+        // Glyphs 1000 and 1001 are marks, with attachment classes 1 and 2. Mark glyph set 0
+        // contains glyph 1001
+        #[rustfmt::skip]
+        let gdef = be(&[
+            1, 2, 14, 0, 0, 24, 34,
+            2, 1, 1000, 1001, 3,
+            1, 1000, 2, 1, 2,
+            1, 1, 0, 8, 1, 1, 1001,
+        ]);
+        let glyphs = [h, GlyphID(1000), a];
+        let ligature = vec![GlyphID(500), GlyphID(1000)];
+        let kerned = vec![
+            adjustment(0.0, -50.0),
+            adjustment(0.0, 0.0),
+            adjustment(10.0, 0.0),
+        ];
+        for &(flag, ignored) in &[
+            (0x0000, false),
+            (0x0008, true),
+            (0x0002, false),
+            (0x0100, false),
+            (0x0200, true),
+            (0x0010, true),
+        ] {
+            let face = face_with_tables(&[
+                (b"GSUB", gsub(flag)),
+                (b"GPOS", gpos(flag)),
+                (b"GDEF", gdef.clone()),
+            ]);
+            let substituted = face.apply_gsub(&glyphs, &[liga], latn, None);
+            let adjusted = face.apply_gpos(&glyphs, &[kern], latn, None);
+            if ignored {
+                assert_eq!(substituted, ligature);
+                assert_eq!(adjusted, kerned);
+            } else {
+                assert_eq!(substituted, glyphs.to_vec());
+                assert_eq!(adjusted, vec![GlyphAdjustment::default(); 3]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ligature_carets() {
+        let h = GlyphID(37);
+        let face = face_with_tables(&[]);
+        assert!(face.ligature_carets(h).is_empty());
+        let point_x = face.glyph_points(h).unwrap().nth(2).unwrap().x as f32;
+
+        // Carets at a coordinate, at an outline point, and at a coordinate with a device table
+        // adjusting by -1 pixel at 16 ppem
+        #[rustfmt::skip]
+        let gdef = be(&[
+            1, 0, 0, 0, 12, 0,
+            36, 1, 6,
+            3, 8, 12, 16,
+            1, 600,
+            2, 2,
+            3, 300, 6, 16, 17, 2, 0xf200,
+            1, 1, 37,
+        ]);
+        let face = face_with_tables(&[(b"GDEF", gdef)]);
+        assert_eq!(face.ligature_carets(h), vec![600.0, point_x, 300.0]);
+        assert!(face.ligature_carets(GlyphID(1425)).is_empty());
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        assert_eq!(
+            scaled.ligature_carets(h),
+            vec![600.0 / 128.0, point_x / 128.0, 300.0 / 128.0 - 1.0]
+        );
+        let scaled = face.scale(12.0, 12.0, 72, 72);
+        assert_eq!(scaled.ligature_carets(h)[2], 300.0 * 12.0 / 2048.0);
+    }
+
+    #[test]
+    fn test_color_layers() {
+        let h = GlyphID(37);
+        assert_eq!(face_with_tables(&[]).num_palettes(), 0);
+
+        // Glyphs 1424 and 1425 drawn as 'H' in the foreground color and in palette entry 0,
+        // which is red in palette 0 and white in palette 1
+        #[rustfmt::skip]
+        let colr = be(&[
+            0, 2, 0, 14, 0, 26, 2,
+            1424, 1, 1,
+            1425, 0, 1,
+            37, 0,
+            37, 0xffff,
+        ]);
+        #[rustfmt::skip]
+        let cpal = be(&[
+            0, 1, 2, 2, 0, 16, 0, 1,
+            0x0000, 0xffff,
+            0xffff, 0xffff,
+        ]);
+        let face = face_with_tables(&[(b"COLR", colr.clone()), (b"CPAL", cpal)]);
+        assert_eq!(face.num_palettes(), 2);
+        assert!(face.color_layers(h, 0).is_empty());
+        let red = Color {
+            red: 255,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        };
+        assert_eq!(
+            face.color_layers(GlyphID(1425), 0),
+            vec![ColorLayer {
+                glyph_id: h,
+                color: Some(red),
+            }]
+        );
+        assert_eq!(face.color_layers(GlyphID(1424), 0)[0].color, None);
+        assert_eq!(face.color_layers(GlyphID(1425), 2)[0].color, None);
+
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let plain = scaled.get_glyph(h).unwrap().render().unwrap();
+        let rows = |bitmap: &GlyphBitmap| bitmap.rows().flatten().cloned().collect::<Vec<_>>();
+        for &id in [h, GlyphID(1424)].iter() {
+            let flat = scaled.render_flattened(id, 0).unwrap();
+            assert_eq!((flat.left, flat.top), (plain.left, plain.top));
+            assert_eq!(rows(&flat), rows(&plain));
+        }
+        let flat = rows(&scaled.render_flattened(GlyphID(1425), 0).unwrap());
+        assert_eq!(flat.iter().max(), Some(&201));
+        assert!(flat
+            .iter()
+            .zip(rows(&plain))
+            .all(|(&f, p)| (f == 0) == (p == 0)));
+        let flat = rows(&scaled.render_flattened(GlyphID(1425), 1).unwrap());
+        assert!(flat.iter().all(|&c| c == 0));
+
+        // Version 1 palettes without type flags, usable with a dark background, and usable
+        // with a light background
+        let black = Color {
+            red: 0,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        };
+        let white = Color {
+            red: 255,
+            green: 255,
+            blue: 255,
+            alpha: 255,
+        };
+        assert_eq!(face.default_palette(black), 0);
+        let gray = Color {
+            red: 128,
+            green: 128,
+            blue: 128,
+            alpha: 255,
+        };
+        assert!((gray.luminance() - 0.2159).abs() < 1e-4);
+        assert_eq!((black.luminance(), white.luminance()), (0.0, 1.0));
+        #[rustfmt::skip]
+        let cpal = be(&[
+            1, 1, 3, 3, 0, 42, 0, 1, 2, 0, 30, 0, 0, 0, 0,
+            0, 0, 0, 2, 0, 1,
+            0x0000, 0xffff, 0xffff, 0xffff, 0x0000, 0x00ff,
+        ]);
+        let face = face_with_tables(&[(b"CPAL", cpal)]);
+        assert_eq!(face.default_palette(black), 1);
+        assert_eq!(face.default_palette(white), 2);
+        assert_eq!(face.default_palette(red), 1);
+
+        // Record offsets near the end of the address space are rejected without wrapping
+        let cpal = be(&[0, 1, 1, 1, 0xffff, 0xfff0, 0]);
+        let face = face_with_tables(&[(b"COLR", colr), (b"CPAL", cpal)]);
+        assert_eq!(face.color_layers(GlyphID(1425), 0)[0].color, None);
+        let colr = be(&[0, 1, 0xffff, 0xfff0, 0, 14, 0]);
+        let face = face_with_tables(&[(b"COLR", colr)]);
+        let colr_invalid = ValidationProblem::InvalidTable(Tag::new(b"COLR"));
+        assert!(face.validate().contains(&colr_invalid));
+    }
+
+    #[test]
+    fn test_feature_params() {
+        let fc = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(
+            face.stylistic_set_name(Tag::new(b"ss01")).as_deref(),
+            Some("Sans serif lowercase r")
+        );
+        assert_eq!(face.stylistic_set_name(Tag::new(b"calt")), None);
+        assert_eq!(face.size_params(), None);
+
+        let mut tables = face
+            .tables()
+            .map(|(tag, data)| (tag, data.to_vec()))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        // 12pt design size, without a size range
+        #[rustfmt::skip]
+        let gpos = be(&[
+            1, 0, 0, 10, 32,
+            1, 0x7369, 0x7a65, 8, 4, 0, 120, 0, 0, 0, 0,
+            0,
+        ]);
+        // Character variant for 'r', labelled with the ss01 name
+        #[rustfmt::skip]
+        let gsub = be(&[
+            1, 0, 0, 10, 40,
+            1, 0x6376, 0x3031, 8, 4, 0, 0, 256, 0, 0, 0, 0, 1, 0x0000, 0x7200,
+            0,
+        ]);
+        tables.insert(Tag::new(b"GPOS"), gpos);
+        tables.insert(Tag::new(b"GSUB"), gsub);
+        let face = Face::from_provider(&tables, ParseOptions::default()).unwrap();
+        assert_eq!(
+            face.size_params(),
+            Some(SizeParams {
+                design_size: 12.0,
+                subfamily_id: 0,
+                subfamily_name: None,
+                range_start: 0.0,
+                range_end: 0.0,
+            })
+        );
+        assert_eq!(
+            face.character_variant(Tag::new(b"cv01")),
+            Some(CharacterVariant {
+                label: Some("Sans serif lowercase r".to_owned()),
+                characters: vec!['r'],
+                ..CharacterVariant::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_variations() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+
+        // Weight axis from 100 to 900, where 650 maps to 0.25 rather than 0.5
+        #[rustfmt::skip]
+        let fvar = be(&[
+            1, 0, 16, 2, 1, 20, 0, 8,
+            0x7767, 0x6874, 100, 0, 400, 0, 900, 0, 0, 256,
+        ]);
+        #[rustfmt::skip]
+        let avar = be(&[
+            1, 0, 0, 1,
+            4, -16384, -16384, 0, 0, 8192, 4096, 16384, 16384,
+        ]);
+        // Variation store with a delta of -200 at maximum weight
+        #[rustfmt::skip]
+        let gdef = be(&[
+            1, 3, 0, 0, 0, 0, 0, 0, 18,
+            1, 0, 12, 1, 0, 22,
+            1, 1, 0, 16384, 16384,
+            1, 1, 1, 0, -200,
+        ]);
+        // Kerning of -100 with a VariationIndex device table
+        #[rustfmt::skip]
+        let gpos = be(&[
+            1, 0, 0, 10, 24,
+            1, 0x6b65, 0x726e, 8, 0, 1, 0,
+            1, 4,
+            2, 0, 1, 8,
+            1, 12, 0x44, 0, 1, 18, 1, 1, 37, 1, 1425, -100, 26,
+            0, 0, 0x8000,
+        ]);
+        let face = face_with_tables(&[
+            (b"fvar", fvar),
+            (b"avar", avar),
+            (b"GDEF", gdef),
+            (b"GPOS", gpos),
+        ]);
+        assert!(face.variation_coords().is_empty());
+        assert_eq!(face.kerning(h, a), -100);
+
+        let wght = Tag::new(b"wght");
+        let face = face.with_variations(&[(wght, 650.0)]);
+        assert_eq!(face.variation_coords(), &[0.25]);
+        assert_eq!(face.kerning(h, a), -150);
+        assert_eq!(
+            face.scale(12.0, 12.0, 72, 72).kerning(h, a),
+            -150.0 * 12.0 / 2048.0
+        );
+        let face = face.with_variations(&[(wght, 1000.0)]);
+        assert_eq!(face.variation_coords(), &[1.0]);
+        assert_eq!(face.kerning(h, a), -300);
+        let face = face.with_variations(&[(wght, 100.0)]);
+        assert_eq!(face.variation_coords(), &[-1.0]);
+        assert_eq!(face.kerning(h, a), -100);
+    }
+
+    #[test]
+    fn test_otl_common_tables() {
+        let fc = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let gdef = face.table(Tag::new(b"GDEF")).unwrap();
+        let class_def = types::subtable(gdef, 4).unwrap();
+        let entries = otl::class_def_entries(class_def).collect::<Vec<_>>();
+        assert_eq!(entries[0], (GlyphID(1), 1));
+        assert!(entries.contains(&(GlyphID(1660), 3)));
+        assert!(entries
+            .iter()
+            .all(|&(id, class)| otl::class_def(class_def, id) == class));
+
+        // Format 1 and format 2 Coverage tables
+        for coverage in &[
+            &[0, 1, 0, 3, 0, 5, 0, 9, 1, 0][..],
+            &[0, 2, 0, 1, 0, 5, 0, 7, 0, 0],
+        ] {
+            let glyphs = otl::coverage_glyphs(coverage).collect::<Vec<_>>();
+            for (idx, &id) in glyphs.iter().enumerate() {
+                assert_eq!(otl::coverage_index(coverage, id), Some(idx));
+            }
+            assert_eq!(otl::coverage_index(coverage, GlyphID(4)), None);
+        }
+    }
+
+    #[test]
+    fn test_glyph_class() {
+        let fc = FontCollection::new(get_path("FiraCode-Regular.otf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.glyph_class(GlyphID(1)), GlyphClass::Base);
+        assert_eq!(face.glyph_class(GlyphID(41)), GlyphClass::Unknown);
+        assert_eq!(face.glyph_class(GlyphID(276)), GlyphClass::Ligature);
+        assert_eq!(face.glyph_class(GlyphID(1660)), GlyphClass::Mark);
+        let marks = face.glyphs_with_class(GlyphClass::Mark);
+        assert!(marks.contains(&GlyphID(1659)));
+        assert!(marks
+            .iter()
+            .all(|&id| face.glyph_class(id) == GlyphClass::Mark));
+
+        // Hack has no GDEF table
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert_eq!(face.glyph_class(GlyphID(37)), GlyphClass::Unknown);
+    }
+
+    #[test]
+    fn test_script_metrics() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let sub = face.subscript_metrics().unwrap();
+        assert_eq!((sub.x_size, sub.y_offset), (1331.0, -153.0));
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let sup = scaled.superscript_metrics().unwrap();
+        assert_eq!((sup.y_size, sup.y_offset), (1228.0 / 128.0, 716.0 / 128.0));
+        let transform = scaled.superscript_transform().unwrap();
+        assert_eq!(transform.scale_x, 1331.0 / 2048.0);
+        assert_eq!(transform.apply(0.0, 16.0), (0.0, (1228.0 + 716.0) / 128.0));
+    }
+
+    #[test]
+    fn test_render_placement() {
+        // 16 ppem: 'H' has a left side bearing of 137 units, so it starts a pixel right of the
+        // pen, and sits on the baseline
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let h = scaled.get_glyph_id('H' as u32).unwrap();
+        let bitmap = scaled.get_glyph(h).unwrap().render().unwrap();
+        assert_eq!(bitmap.left, 1);
+        assert_eq!(bitmap.top as usize, bitmap.height - 2);
+    }
+
+    #[test]
+    fn test_bitmap_stride() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let h = scaled.get_glyph_id('H' as u32).unwrap();
+        let bitmap = scaled.get_glyph(h).unwrap().render().unwrap();
+        assert_eq!(bitmap.stride(), bitmap.width);
+        assert_eq!(bitmap.rows().count(), bitmap.height);
+        let aligned = bitmap.with_row_alignment(4);
+        assert_eq!(aligned.stride() % 4, 0);
+        assert!(aligned.stride() >= bitmap.width);
+        assert_eq!(aligned.as_slice().len(), aligned.stride() * bitmap.height);
+        assert!(aligned.rows().eq(bitmap.rows()));
+    }
+
+    #[test]
+    fn test_bitmap_trimmed() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let scaled = face.scale(12.0, 12.0, 96, 96);
+        let h = scaled.get_glyph_id('H' as u32).unwrap();
+        let bitmap = scaled.get_glyph(h).unwrap().render().unwrap();
+        let trimmed = bitmap.with_row_alignment(4).trimmed();
+        assert!(trimmed.width < bitmap.width && trimmed.height < bitmap.height);
+        let (dx, dy) = (trimmed.left - bitmap.left, bitmap.top - trimmed.top);
+        for (y, row) in trimmed.rows().enumerate() {
+            let original = &bitmap.row(y + dy as usize)[dx as usize..];
+            assert_eq!(row, &original[..trimmed.width]);
+        }
+        let edges = [trimmed.row(0), trimmed.row(trimmed.height - 1)];
+        assert!(edges.iter().all(|row| row.iter().any(|&c| c != 0)));
+        let space = scaled.get_glyph_id(' ' as u32).unwrap();
+        let blank = scaled.get_glyph(space).unwrap().render().unwrap().trimmed();
+        assert_eq!((blank.width, blank.height), (0, 0));
+    }
+
+    #[test]
+    fn test_antialiasing() {
+        let fc = FontCollection::new(get_path("Hack-Regular.ttf")).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let render = |antialiasing| {
+            let scaled = face
+                .scale(12.0, 12.0, 96, 96)
+                .with_antialiasing(antialiasing);
+            assert_eq!(scaled.antialiasing(), antialiasing);
+            let id = scaled.get_glyph_id('a' as u32).unwrap();
+            scaled.get_glyph(id).unwrap().render().unwrap()
+        };
+        let normal = render(Antialiasing::Normal);
+        let aliased = render(Antialiasing::None);
+        let high = render(Antialiasing::High);
+        for bitmap in &[&aliased, &high] {
+            assert_eq!(
+                (bitmap.width, bitmap.height, bitmap.left, bitmap.top),
+                (normal.width, normal.height, normal.left, normal.top)
+            );
+        }
+        assert!(aliased.as_slice().iter().all(|&c| c == 0 || c == 255));
+    }
+
+    #[test]
+    fn test_horizontal_metrics() {
+        // Hack has 1543 longHorMetric records for 1573 glyphs
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let metrics = |advance, left_side_bearing| HorizontalMetrics {
+            advance,
+            left_side_bearing,
+        };
+        assert_eq!(
+            face.horizontal_metrics(GlyphID(0)).unwrap(),
+            metrics(1233, 104)
+        );
+        assert_eq!(
+            face.horizontal_metrics(GlyphID(37)).unwrap(),
+            metrics(1233, 137)
+        );
+        assert_eq!(
+            face.horizontal_metrics(GlyphID(1542)).unwrap(),
+            metrics(1233, 454)
+        );
+        assert_eq!(
+            face.horizontal_metrics(GlyphID(1543)).unwrap(),
+            metrics(1233, 128)
+        );
+        assert_eq!(
+            face.horizontal_metrics(GlyphID(1560)).unwrap(),
+            metrics(1233, 124)
+        );
+        assert_eq!(
+            face.horizontal_metrics(GlyphID(1572)).unwrap(),
+            metrics(1233, 195)
+        );
+        assert!(face.horizontal_metrics(GlyphID(1573)).is_err());
+    }
+
+    #[test]
+    fn test_vertical_metrics() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        let face = face_with_tables(&[]);
+        assert!(matches!(
+            face.vertical_metrics(h),
+            Err(Error::NoVerticalMetrics)
+        ));
+        assert_eq!(face.vertical_origin(h), None);
+
+        // Two long metrics, then top side bearings for the other 1571 glyphs, all 0 but for
+        // glyph 2
+        let mut vhea = vec![0; 36];
+        vhea[35] = 2;
+        let mut vmtx = be(&[2048, 100, 2400, 200, 300]);
+        vmtx.resize(4 * 2 + 1571 * 2, 0);
+        let vorg = be(&[1, 0, 1800, 2, 37, 1900, 1425, 1700]);
+        let face = face_with_tables(&[
+            (b"vhea", vhea.clone()),
+            (b"vmtx", vmtx.clone()),
+            (b"VORG", vorg),
+        ]);
+        let metrics = |advance, top_side_bearing| VerticalMetrics {
+            advance,
+            top_side_bearing,
+        };
+        let vertical = |id| face.vertical_metrics(GlyphID(id)).unwrap();
+        assert_eq!(vertical(0), metrics(2048, 100));
+        assert_eq!(vertical(1), metrics(2400, 200));
+        assert_eq!(vertical(2), metrics(2400, 300));
+        assert_eq!(vertical(1572), metrics(2400, 0));
+        assert!(face.vertical_metrics(GlyphID(1573)).is_err());
+        assert_eq!(face.vertical_origin(h), Some(1900));
+        assert_eq!(face.vertical_origin(a), Some(1700));
+        assert_eq!(face.vertical_origin(GlyphID(5)), Some(1800));
+
+        // Truncated tables are treated as missing, and reported by validation
+        vmtx.truncate(vmtx.len() - 2);
+        let face = face_with_tables(&[
+            (b"vhea", vhea),
+            (b"vmtx", vmtx),
+            (b"VORG", be(&[1, 0, 1800, 2, 37, 1900])),
+        ]);
+        assert!(face.vertical_metrics(h).is_err());
+        assert_eq!(face.vertical_origin(h), None);
+        let invalid = face
+            .validate()
+            .into_iter()
+            .filter_map(|problem| match problem {
+                ValidationProblem::InvalidTable(tag) => Some(tag),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(invalid, vec![Tag::new(b"vmtx"), Tag::new(b"VORG")]);
+    }
+
+    #[test]
+    fn test_math() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        assert!(face_with_tables(&[]).math().is_none());
+
+        let mut constants = vec![0; 107];
+        constants[0] = 80;
+        constants[3] = 40000;
+        constants[4] = -100;
+        constants[6] = 250;
+        constants[106] = 60;
+        // Italics correction and top accent attachment for 'H', 'A' as an extended shape, and
+        // a top right kern for 'H' with one correction height
+        #[rustfmt::skip]
+        let glyph_info = be(&[
+            8, 22, 36, 42,
+            8, 1, 50, 0, 1, 1, 37,
+            8, 1, 600, 0, 1, 1, 37,
+            1, 1, 1425,
+            12, 1, 18, 0, 0, 0, 1, 1, 37,
+            1, 100, 0, -20, 0, 30, 0,
+        ]);
+        let mut math = be(&[1, 0, 10, 224, 0]);
+        math.extend(be(&constants));
+        math.extend(glyph_info);
+        let face = face_with_tables(&[(b"MATH", math.clone())]);
+        let table = face.math().unwrap();
+        assert_eq!(table.constant(MathConstant::ScriptPercentScaleDown), 80);
+        assert_eq!(
+            table.constant(MathConstant::DisplayOperatorMinHeight),
+            40000
+        );
+        assert_eq!(table.constant(MathConstant::MathLeading), -100);
+        assert_eq!(table.constant(MathConstant::AxisHeight), 250);
+        assert_eq!(
+            table.constant(MathConstant::RadicalDegreeBottomRaisePercent),
+            60
+        );
+        assert_eq!(table.italics_correction(h), Some(50));
+        assert_eq!(table.italics_correction(a), None);
+        assert_eq!(table.top_accent_attachment(h), Some(600));
+        assert!(table.is_extended_shape(a));
+        assert!(!table.is_extended_shape(h));
+        assert_eq!(table.kern(h, MathKernCorner::TopRight, 50), Some(-20));
+        assert_eq!(table.kern(h, MathKernCorner::TopRight, 100), Some(30));
+        assert_eq!(table.kern(h, MathKernCorner::TopLeft, 50), None);
+        assert_eq!(table.kern(a, MathKernCorner::TopRight, 50), None);
+
+        // MathConstants cut short
+        math.truncate(100);
+        assert!(face_with_tables(&[(b"MATH", math)]).math().is_none());
+    }
+
+    #[test]
+    fn test_math_stretch() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        // Vertical variants of 'H' with advances 500 and 800, and an assembly of a bottom part,
+        // an extender and a top part, with a minimum connector overlap of 10
+        #[rustfmt::skip]
+        let variants = be(&[
+            10, 12, 0, 1, 0, 18,
+            1, 1, 37,
+            12, 2, 38, 500, 39, 800,
+            5, 0, 3,
+            40, 0, 100, 300, 0,
+            41, 100, 100, 200, 1,
+            42, 100, 0, 300, 0,
+        ]);
+        let mut math = be(&[1, 0, 10, 0, 224]);
+        math.extend(vec![0; 214]);
+        math.extend(variants);
+        let face = face_with_tables(&[(b"MATH", math)]);
+        let table = face.math().unwrap();
+        let stretch = |target| table.stretch(h, MathDirection::Vertical, target).unwrap();
+        let assembly = |parts: &[(u32, i32)]| {
+            StretchedGlyph::Assembly(
+                parts
+                    .iter()
+                    .map(|&(glyph, offset)| AssembledPart {
+                        glyph: GlyphID(glyph),
+                        offset,
+                    })
+                    .collect(),
+            )
+        };
+        assert_eq!(stretch(400), StretchedGlyph::Variant(GlyphID(38)));
+        assert_eq!(stretch(800), StretchedGlyph::Variant(GlyphID(39)));
+        // Two extenders reach 970 units, and the 70 excess is spread over the three joints
+        assert_eq!(
+            stretch(900),
+            assembly(&[(40, 0), (41, 267), (41, 434), (42, 601)])
+        );
+        assert_eq!(
+            stretch(1000),
+            assembly(&[(40, 0), (41, 250), (41, 400), (41, 550), (42, 700)])
+        );
+        match stretch(65535) {
+            StretchedGlyph::Assembly(parts) => assert_eq!(parts.len(), 344),
+            stretched => panic!("expected assembly, got {:?}", stretched),
+        }
+        assert_eq!(table.stretch(h, MathDirection::Horizontal, 100), None);
+        assert_eq!(table.stretch(a, MathDirection::Vertical, 100), None);
+    }
+
+    #[test]
+    fn test_meta() {
+        assert!(face_with_tables(&[]).design_languages().is_empty());
+
+        // dlng and slng maps, with the slng data running past the end of the table
+        #[rustfmt::skip]
+        let mut meta = be(&[
+            0, 1, 0, 0, 0, 0, 0, 2,
+            0x646c, 0x6e67, 0, 40, 0, 11,
+            0x736c, 0x6e67, 0, 51, 0, 6,
+        ]);
+        meta.extend_from_slice(b"Latn, Cyrl,en,ru");
+        let face = face_with_tables(&[(b"meta", meta.clone())]);
+        assert_eq!(face.design_languages(), vec!["Latn", "Cyrl"]);
+        assert_eq!(face.supported_languages(), Vec::<&str>::new());
+        meta.push(b',');
+        let face = face_with_tables(&[(b"meta", meta.clone())]);
+        assert_eq!(face.supported_languages(), vec!["en", "ru"]);
+
+        // Data maps cut short
+        meta.truncate(30);
+        let face = face_with_tables(&[(b"meta", meta)]);
+        assert!(face.design_languages().is_empty());
+        assert!(face
+            .validate()
+            .contains(&ValidationProblem::InvalidTable(Tag::new(b"meta"))));
+    }
+
+    #[test]
+    fn test_device_advance() {
+        let (h, a) = (GlyphID(37), GlyphID(1425));
+        let scaled = |face: &Face<'static>, dpi| face.scale(12.0, 12.0, dpi, dpi).device_advance(h);
+        assert_eq!(scaled(&face_with_tables(&[]), 96), None);
+
+        // hdmx with a 16 ppem record where 'H' is 11 pixels wide, and LTSH saying 'H' scales
+        // linearly from 20 ppem and 'A' from 10 ppem
+        let mut hdmx = be(&[0, 1, 0, 1576]);
+        hdmx.resize(8 + 1576, 0);
+        hdmx[8] = 16;
+        hdmx[8 + 2 + 37] = 11;
+        let mut ltsh = be(&[0, 1573]);
+        ltsh.resize(4 + 1573, 1);
+        ltsh[4 + 37] = 20;
+        ltsh[4 + 1425] = 10;
+        ltsh[4 + 5] = 0;
+        let face = face_with_tables(&[(b"hdmx", hdmx.clone()), (b"LTSH", ltsh)]);
+        assert_eq!(scaled(&face, 96), Some(11));
+        assert_eq!(scaled(&face, 72), None);
+        let device_advance = |size, id| face.scale(size, size, 72, 72).device_advance(id);
+        assert_eq!(device_advance(12.0, a), Some(7));
+        assert_eq!(device_advance(12.0, GlyphID(5)), None);
+        assert_eq!(device_advance(11.5, a), None);
+
+        // Device records cut short
+        hdmx.truncate(1000);
+        let face = face_with_tables(&[(b"hdmx", hdmx)]);
+        assert_eq!(scaled(&face, 96), None);
+        assert!(face
+            .validate()
+            .contains(&ValidationProblem::InvalidTable(Tag::new(b"hdmx"))));
+    }
+
+    #[test]
+    fn test_jstf() {
+        assert!(face_with_tables(&[]).jstf().is_none());
+
+        // arab script with a kashida extender glyph, a default language system with one
+        // priority level, and an empty URD language system
+        #[rustfmt::skip]
+        let jstf = be(&[
+            1, 0, 1, 0x6172, 0x6162, 12,
+            12, 16, 1, 0x5552, 0x4420, 50,
+            1, 500,
+            1, 4,
+            20, 0, 0, 0, 0, 0, 0, 0, 0, 26,
+            2, 3, 4,
+            1, 4,
+            0,
+        ]);
+        let face = face_with_tables(&[(b"JSTF", jstf.clone())]);
+        let table = face.jstf().unwrap();
+        assert!(table.script(Tag::new(b"latn")).is_none());
+        let script = table.script(Tag::new(b"arab")).unwrap();
+        assert_eq!(script.extender_glyphs(), vec![GlyphID(500)]);
+        let priority = JstfPriority {
+            gsub_shrinkage_enable: vec![3, 4],
+            extension_max_lookups: 1,
+            ..JstfPriority::default()
+        };
+        assert_eq!(
+            script.lang_sys(None).unwrap().priorities(),
+            vec![priority.clone()]
+        );
+        let urdu = script.lang_sys(Some(Tag::new(b"URD "))).unwrap();
+        assert!(urdu.priorities().is_empty());
+        let farsi = script.lang_sys(Some(Tag::new(b"FAR "))).unwrap();
+        assert_eq!(farsi.priorities(), vec![priority]);
+
+        // Script records cut short
+        let face = face_with_tables(&[(b"JSTF", jstf[..8].to_vec())]);
+        assert!(face.jstf().is_none());
+    }
+
+    #[test]
+    fn test_missing_optional_tables() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert!(face.has_charmap());
+        assert!(face.has_horizontal_metrics());
+        let mut builder = FontBuilder::from_face(&face);
+        builder.remove_table(Tag::new(b"cmap"));
+        builder.remove_table(Tag::new(b"hmtx"));
+        let data = builder.build();
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert!(!face.has_charmap());
+        assert!(!face.has_horizontal_metrics());
+        assert!(face.get_glyph_id('A' as u32).is_err());
+        let metrics = face.horizontal_metrics(GlyphID(37)).unwrap();
+        assert_eq!((metrics.advance, metrics.left_side_bearing), (1233, 0));
+        assert_eq!(face.validate(), vec![]);
+        let subset = face.pdf_subset(&[GlyphID(37)]).unwrap();
+        assert_eq!(subset.widths, vec![(0, 602), (37, 602)]);
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        let data = face.to_bytes();
+
+        let fc = FontCollection::new_from(&data).unwrap();
+        let copy = fc.get_face(0).unwrap();
+        assert_eq!(copy.validate(), vec![]);
+        // Table layout changes, so only checkSumAdjustment in head is different
+        for (tag, data) in face.tables() {
+            let copied = copy.table(tag).unwrap();
+            if tag == Tag::new(b"head") {
+                assert_eq!(copied[..8], data[..8]);
+                assert_eq!(copied[12..], data[12..]);
+            } else {
+                assert_eq!(copied, data);
+            }
+        }
+        assert_eq!(copy.to_bytes(), data);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        // Checked at compile time, so faces can be shared between threads
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FontCollection>();
+        assert_send_sync::<Face<'static>>();
+        assert_send_sync::<ScaledFace<'static, 'static>>();
+        assert_send_sync::<GlyphBitmap>();
+    }
+
+    #[test]
+    fn test_lazy_tables() {
+        let path = get_path("Hack-Regular.ttf");
+        let fc = FontCollection::new(&path).unwrap();
+        let face = fc.get_face(0).unwrap();
+        assert!(face.os2.get().is_none());
+        assert!(face.post.get().is_none());
+        assert_eq!(face.italic_angle(), Some(0.0));
+        assert!(face.post.get().unwrap().is_some());
+        assert!(face.os2.get().is_none());
     }
 
     #[test]
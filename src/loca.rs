@@ -1,14 +1,18 @@
 //! Glyph index to location
 
+use std::sync::OnceLock;
+
 use super::error::*;
 use super::head::IdxToLocFmt;
-use super::types::{get_u16_unchecked, get_u32_unchecked, GlyphID};
+use super::types::{get_u16, get_u16_unchecked, get_u32, get_u32_unchecked, GlyphID};
 
 /// Handle to loca table
 pub(super) struct Loca<'a> {
     num_glyphs: usize,
     idx_to_loc_fmt: IdxToLocFmt,
     data: &'a [u8],
+    /// Decoded (offset, length) pairs for each glyph, once precomputed
+    index: OnceLock<Vec<(u32, u32)>>,
 }
 
 impl<'a> std::fmt::Debug for Loca<'a> {
@@ -22,16 +26,16 @@ impl<'a> std::fmt::Debug for Loca<'a> {
 
 impl<'a> Loca<'a> {
     /// Check if we have enough data
-    pub(super) fn load(data: &[u8], num_glyphs: usize, fmt: IdxToLocFmt) -> Result<Loca> {
+    pub(super) fn load(data: &[u8], num_glyphs: usize, fmt: IdxToLocFmt) -> Result<Loca<'_>> {
         match fmt {
             IdxToLocFmt::Off16 => {
                 if data.len() < num_glyphs * 2 {
-                    return Err(Error::Invalid);
+                    return Err(Error::truncated(0, num_glyphs * 2));
                 }
             }
             IdxToLocFmt::Off32 => {
                 if data.len() < num_glyphs * 4 {
-                    return Err(Error::Invalid);
+                    return Err(Error::truncated(0, num_glyphs * 4));
                 }
             }
         }
@@ -39,17 +43,62 @@ impl<'a> Loca<'a> {
             num_glyphs: num_glyphs,
             idx_to_loc_fmt: fmt,
             data: data,
+            index: OnceLock::new(),
         })
     }
 
+    /// Decode offsets and lengths of all glyphs, so that later lookups don't have to
+    pub(super) fn precompute(&self) -> Result<()> {
+        if self.index.get().is_some() {
+            return Ok(());
+        }
+        let index = (0..self.num_glyphs as u32)
+            .map(|id| {
+                let range = self.decode_range(GlyphID(id))?;
+                Ok((range.start as u32, (range.end - range.start) as u32))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let _ = self.index.set(index);
+        Ok(())
+    }
+
     /// Get offset into glyf table for glyph ID
     pub(super) fn get_offset(&self, id: GlyphID) -> Result<usize> {
         if id.0 as usize >= self.num_glyphs {
             return Err(Error::GlyphIDOutOfBounds);
         }
+        if let Some(index) = self.index.get() {
+            return Ok(index[id.0 as usize].0 as usize);
+        }
         Ok(match self.idx_to_loc_fmt {
             IdxToLocFmt::Off16 => (get_u16_unchecked(self.data, id.0 as usize * 2) as usize) * 2,
             IdxToLocFmt::Off32 => get_u32_unchecked(self.data, id.0 as usize * 4) as usize,
         })
     }
+
+    /// Get range of glyph data in the glyf table for glyph ID. Empty ranges are glyphs without
+    /// outlines
+    pub(super) fn get_range(&self, id: GlyphID) -> Result<std::ops::Range<usize>> {
+        match self.index.get() {
+            Some(index) => {
+                let &(start, len) = index.get(id.0 as usize).ok_or(Error::GlyphIDOutOfBounds)?;
+                Ok(start as usize..(start + len) as usize)
+            }
+            None => self.decode_range(id),
+        }
+    }
+
+    fn decode_range(&self, id: GlyphID) -> Result<std::ops::Range<usize>> {
+        let start = self.get_offset(id)?;
+        let idx = id.0 as usize + 1;
+        let end = match self.idx_to_loc_fmt {
+            IdxToLocFmt::Off16 => get_u16(self.data, idx * 2)? as usize * 2,
+            IdxToLocFmt::Off32 => get_u32(self.data, idx * 4)? as usize,
+        };
+        if end < start {
+            Err(Error::malformed("glyph locations out of order"))
+        } else {
+            Ok(start..end)
+        }
+    }
 }
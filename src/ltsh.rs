@@ -0,0 +1,29 @@
+//! Linear threshold table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_u16, get_u16_unchecked, GlyphID};
+
+/// Handle to LTSH table
+pub(super) struct Ltsh<'a>(&'a [u8]);
+
+impl<'a> Ltsh<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Ltsh<'_>> {
+        let len = 4 + get_u16(data, 2)? as usize;
+        if data.len() < len {
+            Err(Error::truncated(0, len))
+        } else {
+            Ok(Ltsh(data))
+        }
+    }
+
+    /// Check whether a glyph's hinted advance scales linearly at the given ppem
+    pub(super) fn is_linear(&self, ppem: u8, glyph_id: GlyphID) -> bool {
+        let num_glyphs = get_u16_unchecked(self.0, 2) as u32;
+        if glyph_id.0 >= num_glyphs {
+            return false;
+        }
+        let y_pel = self.0[4 + glyph_id.0 as usize];
+        y_pel != 0 && ppem >= y_pel
+    }
+}
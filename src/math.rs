@@ -0,0 +1,409 @@
+//! Mathematical typesetting table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::otl::coverage_index;
+use super::types::{get_i16, get_i16_unchecked, get_u16, get_u16_unchecked, subtable, GlyphID};
+
+/// Global constants for laying out mathematical formulas. Device table adjustments to the
+/// values are ignored
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MathConstant {
+    ScriptPercentScaleDown,
+    ScriptScriptPercentScaleDown,
+    DelimitedSubFormulaMinHeight,
+    DisplayOperatorMinHeight,
+    MathLeading,
+    AxisHeight,
+    AccentBaseHeight,
+    FlattenedAccentBaseHeight,
+    SubscriptShiftDown,
+    SubscriptTopMax,
+    SubscriptBaselineDropMin,
+    SuperscriptShiftUp,
+    SuperscriptShiftUpCramped,
+    SuperscriptBottomMin,
+    SuperscriptBaselineDropMax,
+    SubSuperscriptGapMin,
+    SuperscriptBottomMaxWithSubscript,
+    SpaceAfterScript,
+    UpperLimitGapMin,
+    UpperLimitBaselineRiseMin,
+    LowerLimitGapMin,
+    LowerLimitBaselineDropMin,
+    StackTopShiftUp,
+    StackTopDisplayStyleShiftUp,
+    StackBottomShiftDown,
+    StackBottomDisplayStyleShiftDown,
+    StackGapMin,
+    StackDisplayStyleGapMin,
+    StretchStackTopShiftUp,
+    StretchStackBottomShiftDown,
+    StretchStackGapAboveMin,
+    StretchStackGapBelowMin,
+    FractionNumeratorShiftUp,
+    FractionNumeratorDisplayStyleShiftUp,
+    FractionDenominatorShiftDown,
+    FractionDenominatorDisplayStyleShiftDown,
+    FractionNumeratorGapMin,
+    FractionNumDisplayStyleGapMin,
+    FractionRuleThickness,
+    FractionDenominatorGapMin,
+    FractionDenomDisplayStyleGapMin,
+    SkewedFractionHorizontalGap,
+    SkewedFractionVerticalGap,
+    OverbarVerticalGap,
+    OverbarRuleThickness,
+    OverbarExtraAscender,
+    UnderbarVerticalGap,
+    UnderbarRuleThickness,
+    UnderbarExtraDescender,
+    RadicalVerticalGap,
+    RadicalDisplayStyleVerticalGap,
+    RadicalRuleThickness,
+    RadicalExtraAscender,
+    RadicalKernBeforeDegree,
+    RadicalKernAfterDegree,
+    RadicalDegreeBottomRaisePercent,
+}
+
+impl MathConstant {
+    /// Offset of the value within the MathConstants table. The table starts with four 16-bit
+    /// values, followed by MathValueRecords (value + device table offset), and ends with one
+    /// more 16-bit value
+    fn offset(self) -> usize {
+        match self as usize {
+            i if i < 4 => i * 2,
+            55 => 212,
+            i => 8 + (i - 4) * 4,
+        }
+    }
+}
+
+/// Corner of a glyph, for math kerning
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MathKernCorner {
+    TopRight,
+    TopLeft,
+    BottomRight,
+    BottomLeft,
+}
+
+/// Direction in which a glyph is to be stretched
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MathDirection {
+    Vertical,
+    Horizontal,
+}
+
+/// Pre-built size variant of a glyph
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MathVariant {
+    pub glyph: GlyphID,
+    /// Advance height (for vertical variants) or width (for horizontal variants)
+    pub advance: u16,
+}
+
+/// Part of a glyph assembly
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GlyphPart {
+    pub glyph: GlyphID,
+    /// Length of the connector at the start (bottom/left) of the part
+    pub start_connector_length: u16,
+    /// Length of the connector at the end (top/right) of the part
+    pub end_connector_length: u16,
+    /// Advance of the part in the direction of stretching
+    pub full_advance: u16,
+    /// Whether the part may be repeated (or skipped) to stretch the assembly
+    pub is_extender: bool,
+}
+
+/// Recipe for building a stretched glyph out of parts
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GlyphAssembly {
+    /// Italics correction of the assembled glyph, in font units
+    pub italics_correction: i16,
+    /// Parts, from bottom to top (vertical) or left to right (horizontal)
+    pub parts: Vec<GlyphPart>,
+}
+
+/// Part of an assembled glyph, positioned along the direction of stretching
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AssembledPart {
+    pub glyph: GlyphID,
+    /// Offset of the start of the part from the start of the assembled glyph, in font units
+    pub offset: i32,
+}
+
+/// Glyph stretched to (at least) a target size
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StretchedGlyph {
+    /// A single pre-built variant
+    Variant(GlyphID),
+    /// Parts to be drawn at the given offsets
+    Assembly(Vec<AssembledPart>),
+}
+
+/// Handle to MATH table
+pub struct Math<'a> {
+    constants: &'a [u8],
+    glyph_info: Option<&'a [u8]>,
+    variants: Option<&'a [u8]>,
+}
+
+impl<'a> std::fmt::Debug for Math<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Math")
+    }
+}
+
+impl<'a> Math<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<Math<'a>> {
+        if data.len() < 10 {
+            return Err(Error::truncated(0, 10));
+        }
+        let constants = subtable(data, 4).ok_or(Error::malformed("missing MathConstants table"))?;
+        if constants.len() < 214 {
+            return Err(Error::truncated(0, 214));
+        }
+        Ok(Math {
+            constants,
+            glyph_info: subtable(data, 6),
+            variants: subtable(data, 8),
+        })
+    }
+
+    /// Get value of a math constant. Values are in font units, except for the percentage
+    /// scale-down factors
+    pub fn constant(&self, constant: MathConstant) -> i32 {
+        let off = constant.offset();
+        match constant {
+            MathConstant::DelimitedSubFormulaMinHeight | MathConstant::DisplayOperatorMinHeight => {
+                get_u16_unchecked(self.constants, off) as i32
+            }
+            _ => get_i16_unchecked(self.constants, off) as i32,
+        }
+    }
+
+    /// Get italics correction for a glyph, in font units
+    pub fn italics_correction(&self, id: GlyphID) -> Option<i16> {
+        self.glyph_info
+            .and_then(|info| subtable(info, 0))
+            .and_then(|table| value_for_glyph(table, id))
+    }
+
+    /// Get horizontal position of the top accent attachment point for a glyph, in font units
+    pub fn top_accent_attachment(&self, id: GlyphID) -> Option<i16> {
+        self.glyph_info
+            .and_then(|info| subtable(info, 2))
+            .and_then(|table| value_for_glyph(table, id))
+    }
+
+    /// Check if the glyph is an "extended shape" - one that should be treated as a tall
+    /// glyph for script positioning
+    pub fn is_extended_shape(&self, id: GlyphID) -> bool {
+        self.glyph_info
+            .and_then(|info| subtable(info, 4))
+            .and_then(|coverage| coverage_index(coverage, id))
+            .is_some()
+    }
+
+    /// Get kerning value at the given corner of a glyph, for a given height, in font units
+    pub fn kern(&self, id: GlyphID, corner: MathKernCorner, height: i16) -> Option<i16> {
+        let table = self.glyph_info.and_then(|info| subtable(info, 6))?;
+        let idx = subtable(table, 0).and_then(|coverage| coverage_index(coverage, id))?;
+        let count = get_u16(table, 2).ok()? as usize;
+        if idx >= count {
+            return None;
+        }
+        let rec_off = 4 + idx * 8 + corner as usize * 2;
+        let kern = match get_u16(table, rec_off).ok()? as usize {
+            0 => return None,
+            off => table.get(off..)?,
+        };
+        // Find the first correction height that lies above the given height
+        let height_count = get_u16(kern, 0).ok()? as usize;
+        let mut i = 0;
+        while i < height_count && height >= get_i16(kern, 2 + i * 4).ok()? {
+            i += 1;
+        }
+        get_i16(kern, 2 + (height_count + i) * 4).ok()
+    }
+
+    /// Minimum overlap of connecting glyphs during glyph construction, in font units
+    pub fn min_connector_overlap(&self) -> u16 {
+        self.variants
+            .and_then(|variants| get_u16(variants, 0).ok())
+            .unwrap_or(0)
+    }
+
+    /// Get pre-built size variants of a glyph in the given direction, in increasing order of
+    /// size
+    pub fn variants(&self, id: GlyphID, direction: MathDirection) -> Vec<MathVariant> {
+        let construction = match self.glyph_construction(id, direction) {
+            Some(construction) => construction,
+            None => return Vec::new(),
+        };
+        let count = get_u16(construction, 2).unwrap_or(0) as usize;
+        (0..count)
+            .filter_map(|i| {
+                Some(MathVariant {
+                    glyph: GlyphID(get_u16(construction, 4 + i * 4).ok()? as u32),
+                    advance: get_u16(construction, 6 + i * 4).ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Get recipe for assembling an arbitrarily large version of a glyph in the given
+    /// direction, if the font provides one
+    pub fn assembly(&self, id: GlyphID, direction: MathDirection) -> Option<GlyphAssembly> {
+        let assembly = self
+            .glyph_construction(id, direction)
+            .and_then(|construction| subtable(construction, 0))?;
+        let count = get_u16(assembly, 4).ok()? as usize;
+        let mut parts = Vec::with_capacity(count);
+        for i in 0..count {
+            let off = 6 + i * 10;
+            parts.push(GlyphPart {
+                glyph: GlyphID(get_u16(assembly, off).ok()? as u32),
+                start_connector_length: get_u16(assembly, off + 2).ok()?,
+                end_connector_length: get_u16(assembly, off + 4).ok()?,
+                full_advance: get_u16(assembly, off + 6).ok()?,
+                is_extender: get_u16(assembly, off + 8).ok()? & 1 != 0,
+            });
+        }
+        Some(GlyphAssembly {
+            italics_correction: get_i16(assembly, 0).ok()?,
+            parts,
+        })
+    }
+
+    /// Get a version of the glyph that is at least `target` font units in size in the given
+    /// direction. The smallest sufficiently large pre-built variant is preferred. Otherwise,
+    /// the glyph is assembled from parts, repeating extenders as needed, and spreading
+    /// connector overlaps so that the assembly is as close to `target` as possible. If neither
+    /// reaches `target`, the largest available version is returned. Returns `None` if the
+    /// glyph cannot be stretched in the given direction
+    pub fn stretch(
+        &self,
+        id: GlyphID,
+        direction: MathDirection,
+        target: u16,
+    ) -> Option<StretchedGlyph> {
+        let variants = self.variants(id, direction);
+        if let Some(variant) = variants.iter().find(|v| v.advance >= target) {
+            return Some(StretchedGlyph::Variant(variant.glyph));
+        }
+        let assembly = match self.assembly(id, direction) {
+            Some(ref assembly) if !assembly.parts.is_empty() => assembly.clone(),
+            _ => return variants.last().map(|v| StretchedGlyph::Variant(v.glyph)),
+        };
+        let min_overlap = self.min_connector_overlap() as i32;
+        let target = target as i64;
+
+        // Find smallest number of extender repetitions that reaches the target with minimal
+        // overlaps. Each repetition adds the extenders' advances, less one overlap per extender
+        let (extenders, fixed): (Vec<&GlyphPart>, Vec<&GlyphPart>) =
+            assembly.parts.iter().partition(|part| part.is_extender);
+        let max_repeats = if extenders.is_empty() {
+            0
+        } else {
+            MAX_EXTENDER_PARTS / extenders.len()
+        };
+        let min_repeats = if fixed.is_empty() { 1 } else { 0 };
+        let fixed_size = fixed
+            .iter()
+            .map(|part| part.full_advance as i64)
+            .sum::<i64>()
+            - (fixed.len() as i64 - 1) * min_overlap as i64;
+        let step = extenders
+            .iter()
+            .map(|part| part.full_advance as i64 - min_overlap as i64)
+            .sum::<i64>();
+        let repeats = if step > 0 && target > fixed_size {
+            ((target - fixed_size + step - 1) / step) as usize
+        } else {
+            0
+        };
+        let repeats = repeats.max(min_repeats).min(max_repeats);
+        let mut parts = Vec::new();
+        for part in &assembly.parts {
+            let count = if part.is_extender { repeats } else { 1 };
+            parts.extend(std::iter::repeat_n(*part, count));
+        }
+        if parts.is_empty() {
+            return variants.last().map(|v| StretchedGlyph::Variant(v.glyph));
+        }
+
+        // Spread the excess size evenly over the joints, within connector limits
+        let joints = parts.len() as i32 - 1;
+        let excess = max_assembly_size(&parts, min_overlap) - target;
+        let extra = if joints > 0 && excess > 0 {
+            (excess / joints as i64).min(i32::MAX as i64) as i32
+        } else {
+            0
+        };
+        let mut offset = 0i32;
+        let mut ret = Vec::with_capacity(parts.len());
+        for i in 0..parts.len() {
+            if i > 0 {
+                let prev = &parts[i - 1];
+                let max_overlap = prev
+                    .end_connector_length
+                    .min(parts[i].start_connector_length) as i32;
+                let overlap = min_overlap
+                    .saturating_add(extra)
+                    .min(max_overlap.max(min_overlap));
+                offset = offset.saturating_add(prev.full_advance as i32 - overlap);
+            }
+            ret.push(AssembledPart {
+                glyph: parts[i].glyph,
+                offset,
+            });
+        }
+        Some(StretchedGlyph::Assembly(ret))
+    }
+
+    /// Get MathGlyphConstruction table for glyph
+    fn glyph_construction(&self, id: GlyphID, direction: MathDirection) -> Option<&'a [u8]> {
+        let variants = self.variants?;
+        let vert_count = get_u16(variants, 6).ok()? as usize;
+        let (coverage, count, offsets_off) = match direction {
+            MathDirection::Vertical => (subtable(variants, 2)?, vert_count, 10),
+            MathDirection::Horizontal => (
+                subtable(variants, 4)?,
+                get_u16(variants, 8).ok()? as usize,
+                10 + vert_count * 2,
+            ),
+        };
+        let idx = coverage_index(coverage, id)?;
+        if idx >= count {
+            return None;
+        }
+        subtable(variants, offsets_off + idx * 2)
+    }
+}
+
+/// Upper bound on the number of extender parts in an assembly, to guard against malicious data
+const MAX_EXTENDER_PARTS: usize = 1024;
+
+/// Size of assembly when using the minimum connector overlap between each part
+fn max_assembly_size(parts: &[GlyphPart], min_overlap: i32) -> i64 {
+    let total = parts
+        .iter()
+        .map(|part| part.full_advance as i64)
+        .sum::<i64>();
+    total - (parts.len() as i64 - 1).max(0) * min_overlap as i64
+}
+
+/// Look up a MathValueRecord in a table consisting of a coverage offset, a count, and an array
+/// of records indexed by coverage index
+fn value_for_glyph(table: &[u8], id: GlyphID) -> Option<i16> {
+    let idx = subtable(table, 0).and_then(|coverage| coverage_index(coverage, id))?;
+    let count = get_u16(table, 2).ok()? as usize;
+    if idx >= count {
+        return None;
+    }
+    get_i16(table, 4 + idx * 4).ok()
+}
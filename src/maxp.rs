@@ -15,9 +15,9 @@ impl<'a> std::fmt::Debug for Maxp<'a> {
 }
 
 impl<'a> Maxp<'a> {
-    pub(super) fn load(data: &[u8]) -> Result<Maxp> {
+    pub(super) fn load(data: &[u8]) -> Result<Maxp<'_>> {
         if data.len() < 6 {
-            Err(Error::Invalid)
+            Err(Error::truncated(0, 6))
         } else {
             Ok(Maxp(data))
         }
@@ -0,0 +1,170 @@
+//! Merging glyphs from multiple faces into one font
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::collections::BTreeMap;
+
+use super::error::*;
+use super::glyf::Components;
+use super::strip::strip_instructions;
+use super::subset::{build_cmap, build_hmtx, build_loca};
+use super::types::{get_i16_unchecked, get_u16_unchecked, get_u32_unchecked, GlyphID, Tag};
+use super::write::FontBuilder;
+use super::{Face, FaceTyp};
+
+/// Tables of the first face that index glyphs by position in a way that breaks when glyphs are
+/// appended, or that no longer apply to the merged font. Appended glyphs have no variation
+/// data, so the merged font is static and all variation tables are dropped together
+const DROPPED_TABLES: [&[u8; 4]; 13] = [
+    b"DSIG", b"hdmx", b"LTSH", b"vhea", b"vmtx", b"fvar", b"avar", b"STAT", b"gvar", b"cvar",
+    b"HVAR", b"VVAR", b"MVAR",
+];
+
+/// Merge glyphs and character mappings from `faces` into one font. The first face is the
+/// base: its glyph IDs are unchanged, and its other tables (name, OS/2, hinting, layout) are
+/// kept. Glyphs from later faces are appended in order, without hinting instructions. When
+/// several faces map the same codepoint, the earliest face wins. All faces must have
+/// TrueType outlines and the same units per em
+pub fn merge(faces: &[&Face]) -> Result<Vec<u8>> {
+    let base = faces.first().ok_or(Error::FaceIndexOutOfBounds)?;
+    let units_per_em = base.head.units_per_em();
+    let mut glyf_data = Vec::new();
+    let mut offsets = Vec::new();
+    let mut metrics = Vec::new();
+    let mut mapping = BTreeMap::new();
+    let mut bbox = (i16::MAX, i16::MAX, i16::MIN, i16::MIN);
+    let mut maxp_fields = [0u16; 13];
+    let mut unicode_ranges = [0u32; 4];
+    for (i, face) in faces.iter().enumerate() {
+        let (loca, glyf) = match face.typ {
+            FaceTyp::TrueType(ref loca, ref glyf) => (loca, glyf),
+            FaceTyp::CFF => return Err(Error::Unimplemented("merging CFF faces".to_owned())),
+        };
+        if face.head.units_per_em() != units_per_em {
+            return Err(Error::Unimplemented(
+                "merging faces with different units per em".to_owned(),
+            ));
+        }
+        let first_id = metrics.len() as u32;
+        let num_glyphs = face.maxp.num_glyphs() as u32;
+        if first_id + num_glyphs > 0xffff {
+            return Err(Error::TooManyGlyphs);
+        }
+        let hmtx = face.hmtx()?;
+        for id in (0..num_glyphs).map(GlyphID) {
+            offsets.push(glyf_data.len());
+            let start = glyf_data.len();
+            let data = glyf.data(loca.get_range(id)?)?;
+            if i == 0 {
+                glyf_data.extend_from_slice(data);
+            } else {
+                strip_instructions(data, &mut glyf_data)?;
+                let data = &mut glyf_data[start..];
+                if data.len() >= 10 && get_i16_unchecked(data, 0) < 0 {
+                    let components = Components::new(data).collect::<Vec<_>>();
+                    for (off, component) in components {
+                        let new_id = (component.0 + first_id) as u16;
+                        data[off..off + 2].copy_from_slice(&new_id.to_be_bytes());
+                    }
+                }
+            }
+            while glyf_data.len() % 4 != 0 {
+                glyf_data.push(0);
+            }
+            metrics.push((hmtx.advance(id)?, hmtx.lsb(id)?));
+        }
+        // Faces without a character map only contribute glyphs
+        if face.has_charmap() {
+            for (codepoint, id) in face.cmap()?.mappings()? {
+                if id.0 < num_glyphs {
+                    mapping.entry(codepoint).or_insert((id.0 + first_id) as u16);
+                }
+            }
+        }
+        bbox = (
+            bbox.0.min(face.head.xmin()),
+            bbox.1.min(face.head.ymin()),
+            bbox.2.max(face.head.xmax()),
+            bbox.3.max(face.head.ymax()),
+        );
+        if let Some(maxp) = face
+            .table(Tag::new(b"maxp"))
+            .filter(|data| data.len() >= 32)
+        {
+            for (j, field) in maxp_fields.iter_mut().enumerate() {
+                *field = (*field).max(get_u16_unchecked(maxp, 6 + j * 2));
+            }
+        }
+        if let Some(os2) = face
+            .table(Tag::new(b"OS/2"))
+            .filter(|data| data.len() >= 78)
+        {
+            for (j, range) in unicode_ranges.iter_mut().enumerate() {
+                *range |= get_u32_unchecked(os2, 42 + j * 4);
+            }
+        }
+    }
+    offsets.push(glyf_data.len());
+    let (loca_data, short_loca) = build_loca(&offsets);
+    let (hmtx_data, num_h_metrics) = build_hmtx(&metrics);
+
+    let mut head = base.table(Tag::new(b"head")).unwrap_or(&[]).to_vec();
+    for (j, &value) in [bbox.0, bbox.1, bbox.2, bbox.3].iter().enumerate() {
+        head[36 + j * 2..38 + j * 2].copy_from_slice(&value.to_be_bytes());
+    }
+    head[50..52].copy_from_slice(&(if short_loca { 0u16 } else { 1 }).to_be_bytes());
+    let mut hhea = base.table(Tag::new(b"hhea")).unwrap_or(&[]).to_vec();
+    let advance_max = metrics
+        .iter()
+        .map(|&(advance, _)| advance)
+        .max()
+        .unwrap_or(0);
+    hhea[10..12].copy_from_slice(&advance_max.to_be_bytes());
+    hhea[34..36].copy_from_slice(&(num_h_metrics as u16).to_be_bytes());
+    let mut maxp = base.table(Tag::new(b"maxp")).unwrap_or(&[]).to_vec();
+    maxp[4..6].copy_from_slice(&(metrics.len() as u16).to_be_bytes());
+    if maxp.len() >= 32 {
+        for (j, field) in maxp_fields.iter().enumerate() {
+            maxp[6 + j * 2..8 + j * 2].copy_from_slice(&field.to_be_bytes());
+        }
+    }
+
+    let mut builder = FontBuilder::from_face(base);
+    for tag in DROPPED_TABLES.iter() {
+        builder.remove_table(Tag::new(tag));
+    }
+    if let Some(os2) = base
+        .table(Tag::new(b"OS/2"))
+        .filter(|data| data.len() >= 78)
+    {
+        let mut os2 = os2.to_vec();
+        for (j, range) in unicode_ranges.iter().enumerate() {
+            os2[42 + j * 4..46 + j * 4].copy_from_slice(&range.to_be_bytes());
+        }
+        let first = mapping.keys().next().map_or(0, |&cp| cp.min(0xffff) as u16);
+        let last = mapping
+            .keys()
+            .next_back()
+            .map_or(0, |&cp| cp.min(0xffff) as u16);
+        os2[64..66].copy_from_slice(&first.to_be_bytes());
+        os2[66..68].copy_from_slice(&last.to_be_bytes());
+        builder.add_table(Tag::new(b"OS/2"), os2);
+    }
+    if let Some(post) = base
+        .table(Tag::new(b"post"))
+        .and_then(|post| post.get(..32))
+    {
+        // Glyph names are dropped. Version 3.0 has no glyph names
+        let mut post = post.to_vec();
+        post[0..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+        builder.add_table(Tag::new(b"post"), post);
+    }
+    builder
+        .add_table(Tag::new(b"head"), head)
+        .add_table(Tag::new(b"hhea"), hhea)
+        .add_table(Tag::new(b"maxp"), maxp)
+        .add_table(Tag::new(b"hmtx"), hmtx_data)
+        .add_table(Tag::new(b"loca"), loca_data)
+        .add_table(Tag::new(b"glyf"), glyf_data)
+        .add_table(Tag::new(b"cmap"), build_cmap(mapping.into_iter()));
+    Ok(builder.build())
+}
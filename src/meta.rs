@@ -0,0 +1,48 @@
+//! Metadata table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_tag, get_u32_unchecked, Tag};
+
+/// Handle to meta table
+pub(super) struct Meta<'a>(&'a [u8]);
+
+impl<'a> Meta<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Meta<'_>> {
+        if data.len() < 16 {
+            return Err(Error::truncated(0, 16));
+        }
+        let num_maps = get_u32_unchecked(data, 12) as usize;
+        if data.len() < 16 + num_maps * 12 {
+            Err(Error::truncated(0, 16 + num_maps * 12))
+        } else {
+            Ok(Meta(data))
+        }
+    }
+
+    /// Get metadata for tag
+    pub(super) fn data(&self, tag: Tag) -> Option<&'a [u8]> {
+        let num_maps = get_u32_unchecked(self.0, 12) as usize;
+        (0..num_maps)
+            .map(|i| 16 + i * 12)
+            .find(|&off| get_tag(self.0, off).ok() == Some(tag))
+            .and_then(|off| {
+                let data_off = get_u32_unchecked(self.0, off + 4) as usize;
+                let data_len = get_u32_unchecked(self.0, off + 8) as usize;
+                self.0.get(data_off..data_off.checked_add(data_len)?)
+            })
+    }
+
+    /// Get comma-separated ScriptLangTags (`dlng`, `slng`) for tag
+    pub(super) fn script_lang_tags(&self, tag: Tag) -> Vec<&'a str> {
+        self.data(tag)
+            .and_then(|data| std::str::from_utf8(data).ok())
+            .map(|s| {
+                s.split(',')
+                    .map(|lang| lang.trim())
+                    .filter(|lang| !lang.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,100 @@
+//! Naming table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::get_u16_unchecked;
+
+/// Name ID of the font family name
+pub(super) const FAMILY: u16 = 1;
+/// Name ID of the font subfamily (style) name
+pub(super) const SUBFAMILY: u16 = 2;
+/// Name ID of the full font name
+pub(super) const FULL_NAME: u16 = 4;
+/// Name ID of the PostScript name
+pub(super) const POSTSCRIPT_NAME: u16 = 6;
+/// Name ID of the typographic family name, for families with more than the four basic styles
+pub(super) const TYPOGRAPHIC_FAMILY: u16 = 16;
+/// Name ID of the typographic subfamily name
+pub(super) const TYPOGRAPHIC_SUBFAMILY: u16 = 17;
+
+/// Handle to name table
+pub(super) struct Name<'a>(&'a [u8]);
+
+impl<'a> Name<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Name<'_>> {
+        if data.len() < 6 {
+            return Err(Error::truncated(0, 6));
+        }
+        let count = get_u16_unchecked(data, 2) as usize;
+        if data.len() < 6 + count * 12 {
+            Err(Error::truncated(0, 6 + count * 12))
+        } else {
+            Ok(Name(data))
+        }
+    }
+
+    /// Get family name, preferring the typographic family name
+    pub(super) fn family(&self) -> Option<String> {
+        self.get(TYPOGRAPHIC_FAMILY).or_else(|| self.get(FAMILY))
+    }
+
+    /// Get style name, preferring the typographic subfamily name
+    pub(super) fn style(&self) -> Option<String> {
+        self.get(TYPOGRAPHIC_SUBFAMILY)
+            .or_else(|| self.get(SUBFAMILY))
+    }
+
+    /// Get name string with ID `name_id`. English names for Windows are preferred, then other
+    /// Unicode names, then Macintosh Roman names
+    pub(super) fn get(&self, name_id: u16) -> Option<String> {
+        let count = get_u16_unchecked(self.0, 2) as usize;
+        let storage = get_u16_unchecked(self.0, 4) as usize;
+        (0..count)
+            .map(|i| 6 + i * 12)
+            .filter(|&off| get_u16_unchecked(self.0, off + 6) == name_id)
+            .filter_map(|off| {
+                let platform = get_u16_unchecked(self.0, off);
+                let encoding = get_u16_unchecked(self.0, off + 2);
+                let language = get_u16_unchecked(self.0, off + 4);
+                let rank = match (platform, encoding, language) {
+                    (3, 1, 0x0409) | (3, 10, 0x0409) => 0,
+                    (3, 1, _) | (3, 10, _) | (0, _, _) => 1,
+                    (1, 0, 0) => 2,
+                    _ => return None,
+                };
+                Some((rank, platform, off))
+            })
+            .min_by_key(|&(rank, _, _)| rank)
+            .and_then(|(_, platform, off)| {
+                let len = get_u16_unchecked(self.0, off + 8) as usize;
+                let start = storage + get_u16_unchecked(self.0, off + 10) as usize;
+                let data = self.0.get(start..start + len)?;
+                if platform == 1 {
+                    Some(decode_mac_roman(data))
+                } else {
+                    Some(decode_utf16_be(data))
+                }
+            })
+    }
+}
+
+fn decode_utf16_be(data: &[u8]) -> String {
+    let units = data
+        .chunks_exact(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&units)
+}
+
+/// Decode Macintosh Roman. Only the ASCII half is supported, which covers names in practice
+fn decode_mac_roman(data: &[u8]) -> String {
+    data.iter()
+        .map(|&b| {
+            if b < 0x80 {
+                b as char
+            } else {
+                std::char::REPLACEMENT_CHARACTER
+            }
+        })
+        .collect()
+}
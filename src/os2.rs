@@ -0,0 +1,559 @@
+//! OS/2 and Windows metrics table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16_unchecked, get_u16_unchecked, get_u32_unchecked, Tag};
+
+/// Embedding licensing level, from the OS/2 `fsType` field
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EmbeddingLevel {
+    /// Font may be embedded and permanently installed on the remote system
+    Installable,
+    /// Font may be embedded, and the document may be edited
+    Editable,
+    /// Font may be embedded, but the document may only be previewed and printed
+    PreviewPrint,
+    /// Font must not be embedded without permission from the legal owner
+    Restricted,
+}
+
+/// Embedding permissions for a face
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EmbeddingPermissions {
+    /// Embedding licensing level
+    pub level: EmbeddingLevel,
+    /// Font must not be subsetted before embedding
+    pub no_subsetting: bool,
+    /// Only bitmaps contained in the font may be embedded
+    pub bitmap_only: bool,
+}
+
+/// Unicode blocks that can be declared in the OS/2 `ulUnicodeRange` fields. The discriminant is
+/// the bit number
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum UnicodeBlock {
+    /// Basic Latin
+    BasicLatin = 0,
+    /// Latin-1 Supplement
+    Latin1Supplement = 1,
+    /// Latin Extended-A
+    LatinExtendedA = 2,
+    /// Latin Extended-B
+    LatinExtendedB = 3,
+    /// IPA Extensions
+    IpaExtensions = 4,
+    /// Spacing Modifier Letters
+    SpacingModifierLetters = 5,
+    /// Combining Diacritical Marks
+    CombiningDiacriticalMarks = 6,
+    /// Greek and Coptic
+    GreekAndCoptic = 7,
+    /// Coptic
+    Coptic = 8,
+    /// Cyrillic
+    Cyrillic = 9,
+    /// Armenian
+    Armenian = 10,
+    /// Hebrew
+    Hebrew = 11,
+    /// Vai
+    Vai = 12,
+    /// Arabic
+    Arabic = 13,
+    /// NKo
+    Nko = 14,
+    /// Devanagari
+    Devanagari = 15,
+    /// Bengali
+    Bengali = 16,
+    /// Gurmukhi
+    Gurmukhi = 17,
+    /// Gujarati
+    Gujarati = 18,
+    /// Oriya
+    Oriya = 19,
+    /// Tamil
+    Tamil = 20,
+    /// Telugu
+    Telugu = 21,
+    /// Kannada
+    Kannada = 22,
+    /// Malayalam
+    Malayalam = 23,
+    /// Thai
+    Thai = 24,
+    /// Lao
+    Lao = 25,
+    /// Georgian
+    Georgian = 26,
+    /// Balinese
+    Balinese = 27,
+    /// Hangul Jamo
+    HangulJamo = 28,
+    /// Latin Extended Additional
+    LatinExtendedAdditional = 29,
+    /// Greek Extended
+    GreekExtended = 30,
+    /// General Punctuation
+    GeneralPunctuation = 31,
+    /// Superscripts and Subscripts
+    SuperscriptsAndSubscripts = 32,
+    /// Currency Symbols
+    CurrencySymbols = 33,
+    /// Combining Diacritical Marks for Symbols
+    CombiningDiacriticalMarksForSymbols = 34,
+    /// Letterlike Symbols
+    LetterlikeSymbols = 35,
+    /// Number Forms
+    NumberForms = 36,
+    /// Arrows
+    Arrows = 37,
+    /// Mathematical Operators
+    MathematicalOperators = 38,
+    /// Miscellaneous Technical
+    MiscellaneousTechnical = 39,
+    /// Control Pictures
+    ControlPictures = 40,
+    /// Optical Character Recognition
+    OpticalCharacterRecognition = 41,
+    /// Enclosed Alphanumerics
+    EnclosedAlphanumerics = 42,
+    /// Box Drawing
+    BoxDrawing = 43,
+    /// Block Elements
+    BlockElements = 44,
+    /// Geometric Shapes
+    GeometricShapes = 45,
+    /// Miscellaneous Symbols
+    MiscellaneousSymbols = 46,
+    /// Dingbats
+    Dingbats = 47,
+    /// CJK Symbols and Punctuation
+    CjkSymbolsAndPunctuation = 48,
+    /// Hiragana
+    Hiragana = 49,
+    /// Katakana
+    Katakana = 50,
+    /// Bopomofo
+    Bopomofo = 51,
+    /// Hangul Compatibility Jamo
+    HangulCompatibilityJamo = 52,
+    /// Phags-pa
+    PhagsPa = 53,
+    /// Enclosed CJK Letters and Months
+    EnclosedCjkLettersAndMonths = 54,
+    /// CJK Compatibility
+    CjkCompatibility = 55,
+    /// Hangul Syllables
+    HangulSyllables = 56,
+    /// Characters outside the Basic Multilingual Plane
+    NonPlane0 = 57,
+    /// Phoenician
+    Phoenician = 58,
+    /// CJK Unified Ideographs
+    CjkUnifiedIdeographs = 59,
+    /// Private Use Area (plane 0)
+    PrivateUseArea = 60,
+    /// CJK Strokes
+    CjkStrokes = 61,
+    /// Alphabetic Presentation Forms
+    AlphabeticPresentationForms = 62,
+    /// Arabic Presentation Forms-A
+    ArabicPresentationFormsA = 63,
+    /// Combining Half Marks
+    CombiningHalfMarks = 64,
+    /// Vertical Forms
+    VerticalForms = 65,
+    /// Small Form Variants
+    SmallFormVariants = 66,
+    /// Arabic Presentation Forms-B
+    ArabicPresentationFormsB = 67,
+    /// Halfwidth and Fullwidth Forms
+    HalfwidthAndFullwidthForms = 68,
+    /// Specials
+    Specials = 69,
+    /// Tibetan
+    Tibetan = 70,
+    /// Syriac
+    Syriac = 71,
+    /// Thaana
+    Thaana = 72,
+    /// Sinhala
+    Sinhala = 73,
+    /// Myanmar
+    Myanmar = 74,
+    /// Ethiopic
+    Ethiopic = 75,
+    /// Cherokee
+    Cherokee = 76,
+    /// Unified Canadian Aboriginal Syllabics
+    UnifiedCanadianAboriginalSyllabics = 77,
+    /// Ogham
+    Ogham = 78,
+    /// Runic
+    Runic = 79,
+    /// Khmer
+    Khmer = 80,
+    /// Mongolian
+    Mongolian = 81,
+    /// Braille Patterns
+    BraillePatterns = 82,
+    /// Yi Syllables
+    YiSyllables = 83,
+    /// Tagalog
+    Tagalog = 84,
+    /// Old Italic
+    OldItalic = 85,
+    /// Gothic
+    Gothic = 86,
+    /// Deseret
+    Deseret = 87,
+    /// Byzantine Musical Symbols
+    ByzantineMusicalSymbols = 88,
+    /// Mathematical Alphanumeric Symbols
+    MathematicalAlphanumericSymbols = 89,
+    /// Private Use Area (plane 15)
+    SupplementaryPrivateUseArea = 90,
+    /// Variation Selectors
+    VariationSelectors = 91,
+    /// Tags
+    Tags = 92,
+    /// Limbu
+    Limbu = 93,
+    /// Tai Le
+    TaiLe = 94,
+    /// New Tai Lue
+    NewTaiLue = 95,
+    /// Buginese
+    Buginese = 96,
+    /// Glagolitic
+    Glagolitic = 97,
+    /// Tifinagh
+    Tifinagh = 98,
+    /// Yijing Hexagram Symbols
+    YijingHexagramSymbols = 99,
+    /// Syloti Nagri
+    SylotiNagri = 100,
+    /// Linear B Syllabary
+    LinearBSyllabary = 101,
+    /// Ancient Greek Numbers
+    AncientGreekNumbers = 102,
+    /// Ugaritic
+    Ugaritic = 103,
+    /// Old Persian
+    OldPersian = 104,
+    /// Shavian
+    Shavian = 105,
+    /// Osmanya
+    Osmanya = 106,
+    /// Cypriot Syllabary
+    CypriotSyllabary = 107,
+    /// Kharoshthi
+    Kharoshthi = 108,
+    /// Tai Xuan Jing Symbols
+    TaiXuanJingSymbols = 109,
+    /// Cuneiform
+    Cuneiform = 110,
+    /// Counting Rod Numerals
+    CountingRodNumerals = 111,
+    /// Sundanese
+    Sundanese = 112,
+    /// Lepcha
+    Lepcha = 113,
+    /// Ol Chiki
+    OlChiki = 114,
+    /// Saurashtra
+    Saurashtra = 115,
+    /// Kayah Li
+    KayahLi = 116,
+    /// Rejang
+    Rejang = 117,
+    /// Cham
+    Cham = 118,
+    /// Ancient Symbols
+    AncientSymbols = 119,
+    /// Phaistos Disc
+    PhaistosDisc = 120,
+    /// Carian
+    Carian = 121,
+    /// Domino Tiles
+    DominoTiles = 122,
+}
+
+/// Set of Unicode blocks a face claims to support, from the OS/2 `ulUnicodeRange1`-`4` fields.
+/// This is a hint set by the font vendor, and is not guaranteed to match the `cmap` table
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct UnicodeRanges(u128);
+
+impl UnicodeRanges {
+    /// Raw bits, with `ulUnicodeRange1` in the least significant 32 bits
+    pub fn bits(&self) -> u128 {
+        self.0
+    }
+
+    /// Check if the given block is declared as supported
+    pub fn supports_block(&self, block: UnicodeBlock) -> bool {
+        self.0 & (1 << block as u32) != 0
+    }
+
+    /// Check if no block is declared as supported
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Windows code pages that can be declared in the OS/2 `ulCodePageRange` fields, along with the
+/// bit number for each
+const CODE_PAGE_BITS: [(u16, u32); 31] = [
+    (1252, 0),
+    (1250, 1),
+    (1251, 2),
+    (1253, 3),
+    (1254, 4),
+    (1255, 5),
+    (1256, 6),
+    (1257, 7),
+    (1258, 8),
+    (874, 16),
+    (932, 17),
+    (936, 18),
+    (949, 19),
+    (950, 20),
+    (1361, 21),
+    (869, 48),
+    (866, 49),
+    (865, 50),
+    (864, 51),
+    (863, 52),
+    (862, 53),
+    (861, 54),
+    (860, 55),
+    (857, 56),
+    (855, 57),
+    (852, 58),
+    (775, 59),
+    (737, 60),
+    (708, 61),
+    (850, 62),
+    (437, 63),
+];
+
+/// Set of code pages a face claims to be functional for, from the OS/2 `ulCodePageRange1` and
+/// `ulCodePageRange2` fields
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CodePages(u64);
+
+impl CodePages {
+    /// Raw bits, with `ulCodePageRange1` in the least significant 32 bits
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Check if the given Windows code page (e.g. 1251 for Cyrillic) is declared as supported.
+    /// Unknown code pages are reported as unsupported
+    pub fn supports_code_page(&self, code_page: u16) -> bool {
+        CODE_PAGE_BITS
+            .iter()
+            .find(|(cp, _)| *cp == code_page)
+            .is_some_and(|(_, bit)| self.0 & (1 << bit) != 0)
+    }
+
+    /// Check if the Macintosh (US Roman) character set is declared as supported
+    pub fn supports_macintosh(&self) -> bool {
+        self.0 & (1 << 29) != 0
+    }
+
+    /// Check if the OEM character set is declared as supported
+    pub fn supports_oem(&self) -> bool {
+        self.0 & (1 << 30) != 0
+    }
+
+    /// Check if the face is declared as a symbol font
+    pub fn is_symbol(&self) -> bool {
+        self.0 & (1 << 31) != 0
+    }
+}
+
+pub(super) struct Os2<'a>(&'a [u8]);
+
+impl<'a> std::fmt::Debug for Os2<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Os2")
+            .field("version", &self.version())
+            .field("fs_type", &self.fs_type())
+            .finish()
+    }
+}
+
+impl<'a> Os2<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Os2<'_>> {
+        if data.len() < 78 {
+            return Err(Error::truncated(0, 78));
+        }
+        let min_len = match get_u16_unchecked(data, 0) {
+            0 => 78,
+            1 => 86,
+            2..=4 => 96,
+            _ => 100,
+        };
+        if data.len() < min_len {
+            Err(Error::truncated(0, min_len))
+        } else {
+            Ok(Os2(data))
+        }
+    }
+
+    pub(super) fn version(&self) -> u16 {
+        get_u16_unchecked(self.0, 0)
+    }
+
+    pub(super) fn x_avg_char_width(&self) -> i16 {
+        get_i16_unchecked(self.0, 2)
+    }
+
+    pub(super) fn weight_class(&self) -> u16 {
+        get_u16_unchecked(self.0, 4)
+    }
+
+    pub(super) fn width_class(&self) -> u16 {
+        get_u16_unchecked(self.0, 6)
+    }
+
+    pub(super) fn fs_type(&self) -> u16 {
+        get_u16_unchecked(self.0, 8)
+    }
+
+    /// Subscript x size, y size, x offset, and y offset. The y offset is positive downwards
+    pub(super) fn subscript(&self) -> (i16, i16, i16, i16) {
+        (
+            get_i16_unchecked(self.0, 10),
+            get_i16_unchecked(self.0, 12),
+            get_i16_unchecked(self.0, 14),
+            get_i16_unchecked(self.0, 16),
+        )
+    }
+
+    /// Superscript x size, y size, x offset, and y offset. The y offset is positive upwards
+    pub(super) fn superscript(&self) -> (i16, i16, i16, i16) {
+        (
+            get_i16_unchecked(self.0, 18),
+            get_i16_unchecked(self.0, 20),
+            get_i16_unchecked(self.0, 22),
+            get_i16_unchecked(self.0, 24),
+        )
+    }
+
+    pub(super) fn strikeout_size(&self) -> i16 {
+        get_i16_unchecked(self.0, 26)
+    }
+
+    pub(super) fn strikeout_position(&self) -> i16 {
+        get_i16_unchecked(self.0, 28)
+    }
+
+    /// PANOSE classification bytes
+    pub(super) fn panose(&self) -> &'a [u8] {
+        &self.0[32..42]
+    }
+
+    /// Check if the PANOSE classification declares the face as monospaced
+    pub(super) fn is_panose_monospaced(&self) -> bool {
+        let panose = self.panose();
+        match panose[0] {
+            // Latin text - "proportion" digit
+            2 => panose[3] == 9,
+            // Latin hand written, Latin symbol - "spacing" digit
+            3 | 5 => panose[3] == 3,
+            _ => false,
+        }
+    }
+
+    pub(super) fn unicode_ranges(&self) -> UnicodeRanges {
+        let bits = (0..4).fold(0, |acc, i| {
+            acc | (get_u32_unchecked(self.0, 42 + i * 4) as u128) << (i * 32)
+        });
+        UnicodeRanges(bits)
+    }
+
+    pub(super) fn vendor_id(&self) -> Tag {
+        Tag(get_u32_unchecked(self.0, 58))
+    }
+
+    pub(super) fn fs_selection(&self) -> u16 {
+        get_u16_unchecked(self.0, 62)
+    }
+
+    /// Whether the `USE_TYPO_METRICS` bit is set in `fsSelection`
+    pub(super) fn use_typo_metrics(&self) -> bool {
+        self.fs_selection() & 0x0080 != 0
+    }
+
+    pub(super) fn typo_ascender(&self) -> i16 {
+        get_i16_unchecked(self.0, 68)
+    }
+
+    pub(super) fn typo_descender(&self) -> i16 {
+        get_i16_unchecked(self.0, 70)
+    }
+
+    pub(super) fn typo_line_gap(&self) -> i16 {
+        get_i16_unchecked(self.0, 72)
+    }
+
+    pub(super) fn win_ascent(&self) -> u16 {
+        get_u16_unchecked(self.0, 74)
+    }
+
+    pub(super) fn win_descent(&self) -> u16 {
+        get_u16_unchecked(self.0, 76)
+    }
+
+    /// Code page bits. Only present from version 1
+    pub(super) fn code_pages(&self) -> Option<CodePages> {
+        if self.version() >= 1 {
+            let lo = get_u32_unchecked(self.0, 78) as u64;
+            let hi = get_u32_unchecked(self.0, 82) as u64;
+            Some(CodePages(lo | hi << 32))
+        } else {
+            None
+        }
+    }
+
+    /// Height of lowercase letters above the baseline. Only present from version 2
+    pub(super) fn x_height(&self) -> Option<i16> {
+        if self.version() >= 2 {
+            Some(get_i16_unchecked(self.0, 86))
+        } else {
+            None
+        }
+    }
+
+    /// Height of capital letters above the baseline. Only present from version 2
+    pub(super) fn cap_height(&self) -> Option<i16> {
+        if self.version() >= 2 {
+            Some(get_i16_unchecked(self.0, 88))
+        } else {
+            None
+        }
+    }
+
+    /// Interpret `fsType` bits. If multiple level bits are set, the least restrictive wins
+    pub(super) fn embedding_permissions(&self) -> EmbeddingPermissions {
+        let fs_type = self.fs_type();
+        let level = if fs_type & 0x000f == 0 {
+            EmbeddingLevel::Installable
+        } else if fs_type & 0x0008 != 0 {
+            EmbeddingLevel::Editable
+        } else if fs_type & 0x0004 != 0 {
+            EmbeddingLevel::PreviewPrint
+        } else {
+            EmbeddingLevel::Restricted
+        };
+        EmbeddingPermissions {
+            level,
+            no_subsetting: fs_type & 0x0100 != 0,
+            bitmap_only: fs_type & 0x0200 != 0,
+        }
+    }
+}
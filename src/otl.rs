@@ -0,0 +1,341 @@
+//! Common OpenType layout tables: Coverage, ClassDef and Device tables, shared by GSUB, GPOS,
+//! GDEF, MATH and the subsetter
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::types::{get_u16, get_u32, subtable, GlyphID, Tag};
+
+/// Parameters of the GPOS `size` feature, for choosing between optical size variants of a
+/// family. Sizes are in points
+#[derive(Clone, Debug, PartialEq)]
+pub struct SizeParams {
+    /// Size the face was designed for
+    pub design_size: f32,
+    /// Identifies faces of a family that differ only in their intended size range. 0 if the
+    /// face doesn't belong to such a group, in which case the range is also 0
+    pub subfamily_id: u16,
+    /// Name of the subfamily for menus (e.g. "Display"), if the face has one
+    pub subfamily_name: Option<String>,
+    /// Exclusive lower end of the intended size range
+    pub range_start: f32,
+    /// Inclusive upper end of the intended size range
+    pub range_end: f32,
+}
+
+/// Parameters of a GSUB character variant feature (`cv01`-`cv99`), for user interfaces
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CharacterVariant {
+    /// Name of the feature
+    pub label: Option<String>,
+    /// Tooltip text describing the feature
+    pub tooltip: Option<String>,
+    /// Sample text showing the effect of the feature
+    pub sample_text: Option<String>,
+    /// Names of the feature's alternates, for features with more than one
+    pub parameter_labels: Vec<String>,
+    /// Characters the feature has alternates for
+    pub characters: Vec<char>,
+}
+
+/// Check if a GSUB or GPOS table has a feature with `tag` that has lookups
+pub(super) fn has_feature(table: &[u8], tag: Tag) -> bool {
+    let list = match table.get(get_u16(table, 6).map_or(usize::MAX, |off| off as usize)..) {
+        Some(list) => list,
+        None => return false,
+    };
+    let count = get_u16(list, 0).unwrap_or(0) as usize;
+    (0..count)
+        .map(|i| 2 + i * 6)
+        .filter(|&rec| get_u32(list, rec).ok() == Some(tag.0))
+        .filter_map(|rec| get_u16(list, rec + 4).ok())
+        .any(|feature_off| get_u16(list, feature_off as usize + 2).is_ok_and(|n| n > 0))
+}
+
+/// Get FeatureParams table of the first feature with `tag` in a GSUB or GPOS table, that has
+/// parameters. The offset to the parameters is relative to the Feature table, but some old
+/// fonts have `size` parameters relative to the FeatureList, so the table at that offset is
+/// returned as well
+pub(super) fn feature_params(table: &[u8], tag: Tag) -> Option<(&[u8], &[u8])> {
+    let list = table.get(get_u16(table, 6).ok()? as usize..)?;
+    let count = get_u16(list, 0).ok()? as usize;
+    (0..count)
+        .map(|i| 2 + i * 6)
+        .filter(|&rec| get_u32(list, rec).ok() == Some(tag.0))
+        .find_map(|rec| {
+            let feature_off = get_u16(list, rec + 4).ok()? as usize;
+            let params_off = get_u16(list, feature_off).ok()? as usize;
+            match params_off {
+                0 => None,
+                _ => Some((
+                    list.get(feature_off + params_off..)?,
+                    list.get(params_off..).unwrap_or(&[]),
+                )),
+            }
+        })
+}
+
+/// Get indices of lookups of features with tags in `features` in a GSUB or GPOS table, for
+/// `script` and `lang`, in lookup list order. The `DFLT` script is used if the table has no
+/// `script`, and the default language system if the script has no `lang`. The required feature
+/// of the language system is always included
+pub(super) fn lookup_indices(
+    table: &[u8],
+    script: Tag,
+    lang: Option<Tag>,
+    features: &[Tag],
+) -> Vec<usize> {
+    lookup_indices_opt(table, script, lang, features).unwrap_or_default()
+}
+
+fn lookup_indices_opt(
+    table: &[u8],
+    script: Tag,
+    lang: Option<Tag>,
+    features: &[Tag],
+) -> Option<Vec<usize>> {
+    let scripts = subtable(table, 4)?;
+    let feature_list = subtable(table, 6)?;
+    let script = tagged_record(scripts, 0, script)
+        .or_else(|| tagged_record(scripts, 0, Tag::new(b"DFLT")))?;
+    let lang_sys = lang
+        .and_then(|lang| tagged_record(script, 2, lang))
+        .or_else(|| subtable(script, 0))?;
+    let required = get_u16(lang_sys, 2).ok()?;
+    let count = get_u16(lang_sys, 4).ok()? as usize;
+    let mut indices = (0..count)
+        .filter_map(|i| get_u16(lang_sys, 6 + i * 2).ok())
+        .map(|feature| (feature, false))
+        .chain(Some((required, true)).filter(|_| required != 0xffff))
+        .filter_map(|(feature, required)| {
+            let rec = 2 + feature as usize * 6;
+            let tag = Tag(get_u32(feature_list, rec).ok()?);
+            if required || features.contains(&tag) {
+                subtable(feature_list, rec + 4)
+            } else {
+                None
+            }
+        })
+        .flat_map(|feature| {
+            let count = get_u16(feature, 2).unwrap_or(0) as usize;
+            (0..count).filter_map(move |i| get_u16(feature, 4 + i * 2).ok())
+        })
+        .map(|idx| idx as usize)
+        .collect::<Vec<_>>();
+    indices.sort_unstable();
+    indices.dedup();
+    Some(indices)
+}
+
+/// Get (lookup flag, mark filtering set index) of lookup at `idx` in a GSUB or GPOS lookup
+/// list. The mark filtering set is only present if the lookup flag says to use it
+pub(super) fn lookup_flags(lookups: &[u8], idx: usize) -> (u16, Option<u16>) {
+    let lookup = subtable(lookups, 2 + idx * 2).unwrap_or(&[]);
+    let flag = get_u16(lookup, 2).unwrap_or(0);
+    let count = get_u16(lookup, 4).unwrap_or(0) as usize;
+    let mark_set = Some(flag)
+        .filter(|flag| flag & 0x0010 != 0)
+        .and_then(|_| get_u16(lookup, 6 + count * 2).ok());
+    (flag, mark_set)
+}
+
+/// Find table with `tag` in an array of (tag, 16-bit offset) records, preceded by a count at
+/// `count_off`. Offsets are from the start of `data`
+fn tagged_record(data: &[u8], count_off: usize, tag: Tag) -> Option<&[u8]> {
+    let count = get_u16(data, count_off).ok()? as usize;
+    (0..count)
+        .map(|i| count_off + 2 + i * 6)
+        .find(|&rec| get_u32(data, rec).ok() == Some(tag.0))
+        .and_then(|rec| subtable(data, rec + 4))
+}
+
+/// Parse `size` feature parameters as (design size, subfamily ID, subfamily name ID, range
+/// start, range end), in decipoints. Returns `None` if the values are inconsistent
+pub(super) fn size_params(data: &[u8]) -> Option<(u16, u16, u16, u16, u16)> {
+    let params = (
+        get_u16(data, 0).ok()?,
+        get_u16(data, 2).ok()?,
+        get_u16(data, 4).ok()?,
+        get_u16(data, 6).ok()?,
+        get_u16(data, 8).ok()?,
+    );
+    let (design_size, subfamily_id, name_id, start, end) = params;
+    let valid = match subfamily_id {
+        0 => name_id == 0 && start == 0 && end == 0,
+        _ => start < design_size && design_size <= end && (256..=32767).contains(&name_id),
+    };
+    if design_size != 0 && valid {
+        Some(params)
+    } else {
+        None
+    }
+}
+
+/// Get index of glyph in Coverage table, if covered
+pub(super) fn coverage_index(data: &[u8], id: GlyphID) -> Option<usize> {
+    let count = get_u16(data, 2).ok()? as usize;
+    match get_u16(data, 0).ok()? {
+        1 => {
+            // Sorted array of glyph IDs
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let glyph = get_u16(data, 4 + mid * 2).ok()? as u32;
+                if glyph == id.0 {
+                    return Some(mid);
+                } else if glyph < id.0 {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            None
+        }
+        2 => {
+            // Sorted array of (start, end, start coverage index) ranges
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let rec_off = 4 + mid * 6;
+                let start = get_u16(data, rec_off).ok()? as u32;
+                let end = get_u16(data, rec_off + 2).ok()? as u32;
+                if id.0 < start {
+                    hi = mid;
+                } else if id.0 > end {
+                    lo = mid + 1;
+                } else {
+                    let start_idx = get_u16(data, rec_off + 4).ok()? as u32;
+                    return Some((start_idx + id.0 - start) as usize);
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Iterate over glyphs in Coverage table, in coverage index order
+pub(super) fn coverage_glyphs(data: &[u8]) -> impl Iterator<Item = GlyphID> + '_ {
+    let format = get_u16(data, 0).unwrap_or(0);
+    let count = get_u16(data, 2).unwrap_or(0) as usize;
+    let glyphs = (0..count)
+        .filter(move |_| format == 1)
+        .filter_map(move |i| get_u16(data, 4 + i * 2).ok())
+        .map(|id| id as u32..id as u32 + 1);
+    // Ranges are clipped to start after all earlier ones, so that overlapping ranges in a
+    // malformed table can't expand to more than 65536 glyphs
+    let mut next = 0;
+    let ranges = (0..count)
+        .filter(move |_| format == 2)
+        .filter_map(move |i| {
+            let start = get_u16(data, 4 + i * 6).ok()? as u32;
+            let end = get_u16(data, 6 + i * 6).ok()? as u32;
+            let range = start.max(next)..end + 1;
+            next = next.max(end + 1);
+            Some(range)
+        });
+    glyphs.chain(ranges).flatten().map(GlyphID)
+}
+
+/// Get class of glyph in ClassDef table. Glyphs not assigned a class are in class 0
+pub(super) fn class_def(data: &[u8], id: GlyphID) -> u16 {
+    class_def_opt(data, id).unwrap_or(0)
+}
+
+fn class_def_opt(data: &[u8], id: GlyphID) -> Option<u16> {
+    match get_u16(data, 0).ok()? {
+        1 => {
+            // Array of classes for consecutive glyph IDs
+            let start = get_u16(data, 2).ok()? as u32;
+            let count = get_u16(data, 4).ok()? as u32;
+            if id.0 < start || id.0 - start >= count {
+                return None;
+            }
+            get_u16(data, 6 + (id.0 - start) as usize * 2).ok()
+        }
+        2 => {
+            // Sorted array of (start, end, class) ranges
+            let count = get_u16(data, 2).ok()? as usize;
+            let (mut lo, mut hi) = (0, count);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let rec_off = 4 + mid * 6;
+                let start = get_u16(data, rec_off).ok()? as u32;
+                let end = get_u16(data, rec_off + 2).ok()? as u32;
+                if id.0 < start {
+                    hi = mid;
+                } else if id.0 > end {
+                    lo = mid + 1;
+                } else {
+                    return get_u16(data, rec_off + 4).ok();
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Iterate over (glyph, class) for glyphs assigned a class in ClassDef table, in glyph order.
+/// Glyphs explicitly assigned class 0 are included
+pub(super) fn class_def_entries(data: &[u8]) -> impl Iterator<Item = (GlyphID, u16)> + '_ {
+    let format = get_u16(data, 0).unwrap_or(0);
+    let (start, count) = match format {
+        1 => (
+            get_u16(data, 2).unwrap_or(0) as u32,
+            get_u16(data, 4).unwrap_or(0) as usize,
+        ),
+        2 => (0, get_u16(data, 2).unwrap_or(0) as usize),
+        _ => (0, 0),
+    };
+    let glyphs = (0..count)
+        .filter(move |_| format == 1)
+        .filter_map(move |i| {
+            let class = get_u16(data, 6 + i * 2).ok()?;
+            Some((start + i as u32..start + i as u32 + 1, class))
+        });
+    // Ranges are clipped as in `coverage_glyphs`
+    let mut next = 0;
+    let ranges = (0..count)
+        .filter(move |_| format == 2)
+        .filter_map(move |i| {
+            let rec_off = 4 + i * 6;
+            let start = get_u16(data, rec_off).ok()? as u32;
+            let end = get_u16(data, rec_off + 2).ok()? as u32;
+            let class = get_u16(data, rec_off + 4).ok()?;
+            let range = start.max(next)..end + 1;
+            next = next.max(end + 1);
+            Some((range, class))
+        });
+    glyphs
+        .chain(ranges)
+        .flat_map(|(ids, class)| ids.map(move |id| (GlyphID(id), class)))
+}
+
+/// Get (outer, inner) item variation store indices from Device table, if it is a
+/// VariationIndex table
+pub(super) fn variation_index(data: &[u8]) -> Option<(u16, u16)> {
+    match get_u16(data, 4).ok()? {
+        0x8000 => Some((get_u16(data, 0).ok()?, get_u16(data, 2).ok()?)),
+        _ => None,
+    }
+}
+
+/// Get adjustment in pixels from Device table at `ppem`. VariationIndex tables give 0
+pub(super) fn device_delta(data: &[u8], ppem: u16) -> i16 {
+    let (start, end, format) = match (get_u16(data, 0), get_u16(data, 2), get_u16(data, 4)) {
+        (Ok(start), Ok(end), Ok(format)) => (start, end, format),
+        _ => return 0,
+    };
+    if !(1..=3).contains(&format) || ppem < start || ppem > end {
+        return 0;
+    }
+    // Deltas are packed into words as signed 2, 4 or 8-bit values
+    let bits = 1 << format;
+    let per_word = 16 / bits;
+    let idx = (ppem - start) as usize;
+    let word = match get_u16(data, 6 + idx / per_word * 2) {
+        Ok(word) => word,
+        Err(_) => return 0,
+    };
+    let shift = 16 - bits * (idx % per_word + 1);
+    ((word << (16 - bits - shift)) as i16) >> (16 - bits)
+}
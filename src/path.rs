@@ -0,0 +1,105 @@
+//! Vector outlines of glyphs and runs of text
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::geom::{Point, Rect};
+use super::types::Affine;
+use std::fmt::Write;
+
+/// A path operation. Coordinates are in pixels, with y increasing downwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathOp {
+    /// Start a new contour at a point
+    MoveTo(f32, f32),
+    /// Straight line to a point
+    LineTo(f32, f32),
+    /// Quadratic bezier curve through a control point (first) to an end point (second)
+    QuadTo((f32, f32), (f32, f32)),
+}
+
+impl PathOp {
+    /// Convert to rster's path operation, for rasterizing. Outlines are built from rype's own
+    /// types, so the rasterizer can be swapped without changing them
+    pub(super) fn to_rster(self) -> rster::PathOp {
+        let point = |(x, y)| rster::Point::new(x, y);
+        match self {
+            PathOp::MoveTo(x, y) => rster::PathOp::Move(point((x, y))),
+            PathOp::LineTo(x, y) => rster::PathOp::Line(point((x, y))),
+            PathOp::QuadTo(c, p) => rster::PathOp::QuadBez(point(c), point(p)),
+        }
+    }
+}
+
+/// An outline made of closed contours. Each contour starts with a `MoveTo`, and ends back at
+/// its starting point
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    ops: Vec<PathOp>,
+}
+
+impl Path {
+    pub(super) fn new(ops: Vec<PathOp>) -> Path {
+        Path { ops }
+    }
+
+    /// Get path operations
+    pub fn ops(&self) -> &[PathOp] {
+        &self.ops
+    }
+
+    /// Check if path has no contours
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Get path with every point transformed by `affine`
+    pub fn transformed(&self, affine: &Affine) -> Path {
+        let point = |(x, y)| affine.apply(x, y);
+        let ops = self
+            .ops
+            .iter()
+            .map(|&op| match op {
+                PathOp::MoveTo(x, y) => {
+                    let (x, y) = point((x, y));
+                    PathOp::MoveTo(x, y)
+                }
+                PathOp::LineTo(x, y) => {
+                    let (x, y) = point((x, y));
+                    PathOp::LineTo(x, y)
+                }
+                PathOp::QuadTo(c, p) => PathOp::QuadTo(point(c), point(p)),
+            })
+            .collect();
+        Path { ops }
+    }
+
+    /// Get bounding box of the path's points, including control points, or `None` if it is
+    /// empty
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut points = self.ops.iter().flat_map(|&op| match op {
+            PathOp::MoveTo(x, y) | PathOp::LineTo(x, y) => vec![Point::new(x, y)],
+            PathOp::QuadTo((cx, cy), (x, y)) => vec![Point::new(cx, cy), Point::new(x, y)],
+        });
+        let first = points.next()?;
+        Some(
+            points.fold(Rect::new(first.x, first.y, first.x, first.y), |rect, p| {
+                rect.include(p)
+            }),
+        )
+    }
+
+    /// Get path as SVG path data, for the `d` attribute of a `<path>` element
+    pub fn to_svg(&self) -> String {
+        let mut ret = String::new();
+        for op in &self.ops {
+            if !ret.is_empty() {
+                ret.push(' ');
+            }
+            let _ = match op {
+                PathOp::MoveTo(x, y) => write!(ret, "M{} {}", x, y),
+                PathOp::LineTo(x, y) => write!(ret, "L{} {}", x, y),
+                PathOp::QuadTo((cx, cy), (x, y)) => write!(ret, "Q{} {} {} {}", cx, cy, x, y),
+            };
+        }
+        ret
+    }
+}
@@ -0,0 +1,124 @@
+//! Data for embedding faces in PDF documents
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::subset::subset;
+use super::types::{GlyphID, MetricsSource};
+use super::Face;
+
+const FIXED_PITCH: u32 = 1 << 0;
+const SERIF: u32 = 1 << 1;
+const SYMBOLIC: u32 = 1 << 2;
+const SCRIPT: u32 = 1 << 3;
+const NONSYMBOLIC: u32 = 1 << 5;
+const ITALIC: u32 = 1 << 6;
+
+/// Entries for a PDF font descriptor dictionary. Dimensions are in PDF glyph space units (1000
+/// units per em)
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdfFontDescriptor {
+    /// `Flags` entry
+    pub flags: u32,
+    /// `FontBBox` entry, as [xmin, ymin, xmax, ymax]
+    pub font_bbox: [i32; 4],
+    pub italic_angle: f32,
+    pub ascent: i32,
+    pub descent: i32,
+    pub cap_height: i32,
+    /// Fonts don't record dominant stem widths, so this is estimated from the weight class
+    pub stem_v: i32,
+}
+
+/// Data for embedding a subset of a face as a CIDFontType2 font, with `Identity-H` encoding.
+/// CIDs are glyph IDs in the original face, so shaped glyph IDs can be written out directly
+#[derive(Clone, Debug)]
+pub struct PdfSubset {
+    /// Subset TrueType font, for the `FontFile2` stream
+    pub font_file: Vec<u8>,
+    /// `CIDToGIDMap` stream data
+    pub cid_to_gid_map: Vec<u8>,
+    /// (CID, width) pairs for the glyphs in the subset, sorted by CID, for the `W` array
+    pub widths: Vec<(u16, i32)>,
+}
+
+/// Convert font units to PDF glyph space units
+fn to_pdf_units(face: &Face, value: f32) -> i32 {
+    (value * 1000.0 / face.head.units_per_em() as f32).round() as i32
+}
+
+pub(super) fn descriptor(face: &Face) -> PdfFontDescriptor {
+    let mut flags = 0;
+    if face.is_monospace() {
+        flags |= FIXED_PITCH;
+    }
+    let os2 = face.os2();
+    if let Some(panose) = os2.map(|os2| os2.panose()) {
+        match panose[0] {
+            2 if (2..=10).contains(&panose[1]) => flags |= SERIF,
+            3 => flags |= SCRIPT,
+            _ => (),
+        }
+    }
+    let symbol_cmap = face.cmap().is_ok_and(|cmap| {
+        cmap.subtables()
+            .any(|subtable| subtable.platform_id == 3 && subtable.encoding_id == 0)
+    });
+    flags |= if symbol_cmap { SYMBOLIC } else { NONSYMBOLIC };
+    let italic_angle = face.italic_angle().unwrap_or(0.0);
+    if italic_angle != 0.0 || face.head.mac_style() & 0x0002 != 0 {
+        flags |= ITALIC;
+    }
+    let metrics = face.line_metrics(MetricsSource::Auto);
+    let cap_height = face
+        .cap_height()
+        .map_or(metrics.ascender, |height| height as f32);
+    let weight_class = match os2 {
+        Some(os2) => os2.weight_class(),
+        None if face.head.mac_style() & 0x0001 != 0 => 700,
+        None => 400,
+    };
+    let weight = (weight_class.max(50) - 50) as f32 / 900.0;
+    PdfFontDescriptor {
+        flags,
+        font_bbox: [
+            to_pdf_units(face, face.head.xmin() as f32),
+            to_pdf_units(face, face.head.ymin() as f32),
+            to_pdf_units(face, face.head.xmax() as f32),
+            to_pdf_units(face, face.head.ymax() as f32),
+        ],
+        italic_angle,
+        ascent: to_pdf_units(face, metrics.ascender),
+        descent: to_pdf_units(face, metrics.descender),
+        cap_height: to_pdf_units(face, cap_height),
+        stem_v: (10.0 + 220.0 * weight * weight).round() as i32,
+    }
+}
+
+pub(super) fn widths(face: &Face, encoding: &[u32]) -> Result<Vec<i32>> {
+    let hmtx = face.hmtx()?;
+    encoding
+        .iter()
+        .map(|&codepoint| {
+            let id = face.get_glyph_id(codepoint)?;
+            Ok(to_pdf_units(face, hmtx.advance(id)? as f32))
+        })
+        .collect()
+}
+
+pub(super) fn pdf_subset(face: &Face, glyphs: &[GlyphID]) -> Result<PdfSubset> {
+    let (font_file, new_ids) = subset(face, &[], glyphs)?;
+    let hmtx = face.hmtx()?;
+    let max_cid = new_ids.keys().next_back().map_or(0, |id| id.0 as usize);
+    let mut cid_to_gid_map = vec![0; (max_cid + 1) * 2];
+    let mut widths = Vec::with_capacity(new_ids.len());
+    for (&old, &new) in &new_ids {
+        let cid = old.0 as usize;
+        cid_to_gid_map[cid * 2..cid * 2 + 2].copy_from_slice(&new.to_be_bytes());
+        widths.push((old.0 as u16, to_pdf_units(face, hmtx.advance(old)? as f32)));
+    }
+    Ok(PdfSubset {
+        font_file,
+        cid_to_gid_map,
+        widths,
+    })
+}
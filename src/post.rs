@@ -0,0 +1,164 @@
+//! PostScript information table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16_unchecked, get_u16, get_u16_unchecked, get_u32_unchecked, GlyphID};
+
+/// Handle to post table
+pub(super) struct Post<'a> {
+    data: &'a [u8],
+    names: Names<'a>,
+}
+
+/// Source of glyph names
+enum Names<'a> {
+    /// Version 1.0 - the standard Macintosh glyph order
+    Standard,
+    /// Version 2.0 - per-glyph indices into standard names, or Pascal strings in the table
+    Indexed {
+        indices: &'a [u8],
+        strings: Vec<&'a [u8]>,
+    },
+    /// Version 3.0 (or unsupported versions) - no glyph names
+    None,
+}
+
+impl<'a> std::fmt::Debug for Post<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Post")
+            .field("version", &format!("{:#010x}", self.version()))
+            .field("italic_angle", &self.italic_angle())
+            .field("is_fixed_pitch", &self.is_fixed_pitch())
+            .finish()
+    }
+}
+
+impl<'a> Post<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Post<'_>> {
+        if data.len() < 32 {
+            return Err(Error::truncated(0, 32));
+        }
+        let names = match get_u32_unchecked(data, 0) {
+            0x00010000 => Names::Standard,
+            0x00020000 => {
+                let num_glyphs = get_u16(data, 32)? as usize;
+                let mut off = 34 + num_glyphs * 2;
+                if data.len() < off {
+                    return Err(Error::truncated(0, off));
+                }
+                let indices = &data[34..off];
+                let mut strings = Vec::new();
+                while off < data.len() {
+                    let len = data[off] as usize;
+                    if off + 1 + len > data.len() {
+                        return Err(Error::truncated(off, 1 + len));
+                    }
+                    strings.push(&data[off + 1..off + 1 + len]);
+                    off += 1 + len;
+                }
+                Names::Indexed {
+                    indices,
+                    strings,
+                }
+            }
+            _ => Names::None,
+        };
+        Ok(Post {
+            data,
+            names,
+        })
+    }
+
+    pub(super) fn version(&self) -> u32 {
+        get_u32_unchecked(self.data, 0)
+    }
+
+    /// Italic angle in counter-clockwise degrees from the vertical
+    pub(super) fn italic_angle(&self) -> f32 {
+        get_u32_unchecked(self.data, 4) as i32 as f32 / 65536.0
+    }
+
+    pub(super) fn underline_position(&self) -> i16 {
+        get_i16_unchecked(self.data, 8)
+    }
+
+    pub(super) fn underline_thickness(&self) -> i16 {
+        get_i16_unchecked(self.data, 10)
+    }
+
+    pub(super) fn is_fixed_pitch(&self) -> bool {
+        get_u32_unchecked(self.data, 12) != 0
+    }
+
+    /// Get name of glyph, if available
+    pub(super) fn glyph_name(&self, id: GlyphID) -> Option<&'a str> {
+        match self.names {
+            Names::Standard => MAC_GLYPH_NAMES.get(id.0 as usize).cloned(),
+            Names::Indexed {
+                indices,
+                ref strings,
+            } => {
+                let idx_off = id.0 as usize * 2;
+                if idx_off + 2 > indices.len() {
+                    return None;
+                }
+                let idx = get_u16_unchecked(indices, idx_off) as usize;
+                if idx < MAC_GLYPH_NAMES.len() {
+                    Some(MAC_GLYPH_NAMES[idx])
+                } else {
+                    strings
+                        .get(idx - MAC_GLYPH_NAMES.len())
+                        .and_then(|s| std::str::from_utf8(s).ok())
+                }
+            }
+            Names::None => None,
+        }
+    }
+
+    /// Get glyph ID for glyph name, if available
+    pub(super) fn glyph_id_by_name(&self, name: &str) -> Option<GlyphID> {
+        let num_names = match self.names {
+            Names::Standard => MAC_GLYPH_NAMES.len(),
+            Names::Indexed { indices, .. } => indices.len() / 2,
+            Names::None => 0,
+        };
+        (0..num_names as u32)
+            .map(GlyphID)
+            .find(|&id| self.glyph_name(id) == Some(name))
+    }
+}
+
+/// Standard Macintosh ordering of glyph names
+static MAC_GLYPH_NAMES: [&str; 258] = [
+    ".notdef", ".null", "nonmarkingreturn", "space", "exclam", "quotedbl", "numbersign",
+    "dollar", "percent", "ampersand", "quotesingle", "parenleft", "parenright", "asterisk",
+    "plus", "comma", "hyphen", "period", "slash", "zero", "one", "two", "three", "four", "five",
+    "six", "seven", "eight", "nine", "colon", "semicolon", "less", "equal", "greater",
+    "question", "at", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O",
+    "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "bracketleft", "backslash",
+    "bracketright", "asciicircum", "underscore", "grave", "a", "b", "c", "d", "e", "f", "g",
+    "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y",
+    "z", "braceleft", "bar", "braceright", "asciitilde", "Adieresis", "Aring", "Ccedilla",
+    "Eacute", "Ntilde", "Odieresis", "Udieresis", "aacute", "agrave", "acircumflex",
+    "adieresis", "atilde", "aring", "ccedilla", "eacute", "egrave", "ecircumflex",
+    "edieresis", "iacute", "igrave", "icircumflex", "idieresis", "ntilde", "oacute",
+    "ograve", "ocircumflex", "odieresis", "otilde", "uacute", "ugrave", "ucircumflex",
+    "udieresis", "dagger", "degree", "cent", "sterling", "section", "bullet", "paragraph",
+    "germandbls", "registered", "copyright", "trademark", "acute", "dieresis", "notequal",
+    "AE", "Oslash", "infinity", "plusminus", "lessequal", "greaterequal", "yen", "mu",
+    "partialdiff", "summation", "product", "pi", "integral", "ordfeminine", "ordmasculine",
+    "Omega", "ae", "oslash", "questiondown", "exclamdown", "logicalnot", "radical", "florin",
+    "approxequal", "Delta", "guillemotleft", "guillemotright", "ellipsis",
+    "nonbreakingspace", "Agrave", "Atilde", "Otilde", "OE", "oe", "endash", "emdash",
+    "quotedblleft", "quotedblright", "quoteleft", "quoteright", "divide", "lozenge",
+    "ydieresis", "Ydieresis", "fraction", "currency", "guilsinglleft", "guilsinglright", "fi",
+    "fl", "daggerdbl", "periodcentered", "quotesinglbase", "quotedblbase", "perthousand",
+    "Acircumflex", "Ecircumflex", "Aacute", "Edieresis", "Egrave", "Iacute", "Icircumflex",
+    "Idieresis", "Igrave", "Oacute", "Ocircumflex", "apple", "Ograve", "Uacute",
+    "Ucircumflex", "Ugrave", "dotlessi", "circumflex", "tilde", "macron", "breve",
+    "dotaccent", "ring", "cedilla", "hungarumlaut", "ogonek", "caron", "Lslash", "lslash",
+    "Scaron", "scaron", "Zcaron", "zcaron", "brokenbar", "Eth", "eth", "Yacute", "yacute",
+    "Thorn", "thorn", "minus", "multiply", "onesuperior", "twosuperior", "threesuperior",
+    "onehalf", "onequarter", "threequarters", "franc", "Gbreve", "gbreve", "Idotaccent",
+    "Scedilla", "scedilla", "Cacute", "cacute", "Ccaron", "ccaron", "dcroat",
+];
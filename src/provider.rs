@@ -0,0 +1,47 @@
+//! Sources of table data for faces that aren't backed by a font file
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::types::Tag;
+
+/// Source of font table data. Implement this to back a `Face` with tables that aren't laid out
+/// in one contiguous font file, e.g. tables streamed over the network for incremental font
+/// transfer, or tables synthesized in tests. See `Face::from_provider`
+pub trait FontDataProvider {
+    /// Tags of the tables that are available
+    fn tags(&self) -> Vec<Tag>;
+
+    /// Get data for a table, if available
+    fn table(&self, tag: Tag) -> Option<&[u8]>;
+
+    /// Get sfnt version of the font. By default, this is 0x00010000 (TrueType outlines) if a
+    /// glyf table is available, and 'OTTO' (CFF outlines) otherwise
+    fn sfnt_version(&self) -> Tag {
+        if self.table(Tag::new(b"glyf")).is_some() {
+            Tag(0x00010000)
+        } else {
+            Tag::new(b"OTTO")
+        }
+    }
+}
+
+impl FontDataProvider for HashMap<Tag, Vec<u8>> {
+    fn tags(&self) -> Vec<Tag> {
+        self.keys().cloned().collect()
+    }
+
+    fn table(&self, tag: Tag) -> Option<&[u8]> {
+        self.get(&tag).map(|data| data.as_slice())
+    }
+}
+
+impl FontDataProvider for BTreeMap<Tag, Vec<u8>> {
+    fn tags(&self) -> Vec<Tag> {
+        self.keys().cloned().collect()
+    }
+
+    fn table(&self, tag: Tag) -> Option<&[u8]> {
+        self.get(&tag).map(|data| data.as_slice())
+    }
+}
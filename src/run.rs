@@ -0,0 +1,73 @@
+//! Placement of shaped glyph runs on a line, in visual order
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::gpos::GlyphAdjustment;
+use super::types::GlyphID;
+use super::ScaledFace;
+
+/// Direction in which the glyphs of a run advance
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Direction {
+    /// Left to right, as in Latin text
+    LeftToRight,
+    /// Right to left, as in Arabic and Hebrew text
+    RightToLeft,
+}
+
+/// Glyph placed on a line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionedGlyph {
+    /// Index of the glyph in the run, in logical order
+    pub index: usize,
+    /// Glyph to draw
+    pub glyph_id: GlyphID,
+    /// Horizontal position of the glyph origin from the left edge of the run, in pixels
+    pub x: f32,
+    /// Vertical position of the glyph origin from the baseline, in pixels, with y increasing
+    /// downwards
+    pub y: f32,
+}
+
+/// Place glyphs of a run, given in logical order, from left to right in visual order. The pen
+/// position is rounded according to the rounding mode after each glyph. For right-to-left runs,
+/// each glyph ends where the glyph before it in logical order starts, counting from the right
+/// edge of the run. Returns the placed glyphs and the width of the run
+pub(super) fn position(
+    face: &ScaledFace,
+    glyph_ids: &[GlyphID],
+    adjustments: &[GlyphAdjustment],
+    direction: Direction,
+) -> Result<(Vec<PositionedGlyph>, f32)> {
+    let mut advances = vec![0.0; glyph_ids.len()];
+    face.advances(glyph_ids, &mut advances)?;
+    let zero = GlyphAdjustment::default();
+    // Pen positions before each glyph in logical order, followed by the width of the run
+    let mut pens = Vec::with_capacity(glyph_ids.len() + 1);
+    let mut pen = 0.0;
+    pens.push(pen);
+    for (i, advance) in advances.iter().enumerate() {
+        let adjustment = adjustments.get(i).unwrap_or(&zero);
+        pen = face.round_position(pen + advance + adjustment.x_advance * face.scale_width);
+        pens.push(pen);
+    }
+    let width = pen;
+    let glyphs = glyph_ids.iter().enumerate().map(|(i, &glyph_id)| {
+        let adjustment = adjustments.get(i).unwrap_or(&zero);
+        let origin = match direction {
+            Direction::LeftToRight => pens[i],
+            Direction::RightToLeft => width - pens[i + 1],
+        };
+        PositionedGlyph {
+            index: i,
+            glyph_id,
+            x: origin + adjustment.x_placement * face.scale_width,
+            y: -adjustment.y_placement * face.scale_height,
+        }
+    });
+    let glyphs = match direction {
+        Direction::LeftToRight => glyphs.collect(),
+        Direction::RightToLeft => glyphs.rev().collect(),
+    };
+    Ok((glyphs, width))
+}
@@ -0,0 +1,111 @@
+//! Scanning directories for font files
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::path::{Path, PathBuf};
+
+use super::sniff::{sniff, FontFormat};
+use super::{FaceInfo, FontCollection};
+
+/// Font files are looked for this many directories deep, which also guards against symlink
+/// loops
+pub(super) const MAX_DEPTH: usize = 8;
+
+/// A face found by `scan_dir`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScannedFace {
+    /// Path of the font file
+    pub path: PathBuf,
+    /// Index of the face within the font file
+    pub index: usize,
+    pub info: FaceInfo,
+}
+
+/// Recursively scan directory `path` for font files, yielding metadata of each face. Files
+/// are recognized by their header rather than their extension, and each is only read once the
+/// iterator reaches it. Files and faces that fail to load are skipped. Entries of a directory
+/// are visited in order of their paths
+pub fn scan_dir<P: AsRef<Path>>(path: P) -> ScanDir {
+    ScanDir {
+        pending: vec![(path.as_ref().to_owned(), 0)],
+        current: None,
+    }
+}
+
+/// Iterator over faces in a directory, returned by `scan_dir`
+#[derive(Debug)]
+pub struct ScanDir {
+    /// Paths still to visit, with their depth below the scanned directory. The next one is
+    /// last
+    pending: Vec<(PathBuf, usize)>,
+    /// Font file being read, and the index of its next face
+    current: Option<(PathBuf, FontCollection, usize)>,
+}
+
+impl Iterator for ScanDir {
+    type Item = ScannedFace;
+
+    fn next(&mut self) -> Option<ScannedFace> {
+        loop {
+            if let Some((ref path, ref fc, ref mut index)) = self.current {
+                if *index < fc.num_faces() {
+                    *index += 1;
+                    if let Ok(info) = fc.face_info(*index - 1) {
+                        return Some(ScannedFace {
+                            path: path.clone(),
+                            index: *index - 1,
+                            info,
+                        });
+                    }
+                    continue;
+                }
+                self.current = None;
+            }
+            let (path, depth) = self.pending.pop()?;
+            if path.is_dir() {
+                if depth < MAX_DEPTH {
+                    self.push_entries(&path, depth + 1);
+                }
+            } else if is_font_file(&path) {
+                if let Ok(fc) = FontCollection::new(&path) {
+                    self.current = Some((path, fc, 0));
+                }
+            }
+        }
+    }
+}
+
+impl ScanDir {
+    /// Queue entries of directory `dir`, so that they are visited in order of their paths
+    fn push_entries(&mut self, dir: &Path, depth: usize) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect();
+        paths.sort_by(|a, b| b.cmp(a));
+        self.pending
+            .extend(paths.into_iter().map(|path| (path, depth)));
+    }
+}
+
+/// Check if the file at `path` looks like a font file rype can load, from its first bytes.
+/// Resource-fork suitcases can only be recognized from the whole file, so they are picked by
+/// their extension
+fn is_font_file(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut header = [0; 4];
+    let read = std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut header));
+    if read.is_err() {
+        return false;
+    }
+    match sniff(&header) {
+        FontFormat::TrueType | FontFormat::OpenTypeCff | FontFormat::Collection => true,
+        _ => path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("dfont")),
+    }
+}
@@ -0,0 +1,56 @@
+//! Font file format detection
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::types::get_u32;
+
+/// Container format of a font file
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FontFormat {
+    /// sfnt with TrueType outlines (*.ttf)
+    TrueType,
+    /// sfnt with CFF outlines (*.otf)
+    OpenTypeCff,
+    /// TrueType/OpenType Collection (*.ttc, *.otc)
+    Collection,
+    /// WOFF 1.0 compressed font
+    Woff,
+    /// WOFF 2.0 compressed font
+    Woff2,
+    /// Mac OS resource-fork font suitcase (*.dfont)
+    Dfont,
+    /// Unrecognized data
+    Unknown,
+}
+
+/// Guess the format of font data by looking at its header. This does not validate the rest of
+/// the data
+pub fn sniff(data: &[u8]) -> FontFormat {
+    let magic = match get_u32(data, 0) {
+        Ok(magic) => magic,
+        Err(_) => return FontFormat::Unknown,
+    };
+    match &magic.to_be_bytes() {
+        [0, 1, 0, 0] | b"true" => FontFormat::TrueType,
+        b"OTTO" => FontFormat::OpenTypeCff,
+        b"ttcf" => FontFormat::Collection,
+        b"wOFF" => FontFormat::Woff,
+        b"wOF2" => FontFormat::Woff2,
+        _ if is_resource_fork(data) => FontFormat::Dfont,
+        _ => FontFormat::Unknown,
+    }
+}
+
+/// Check if the data looks like a resource fork. The header consists of the offsets and lengths
+/// of the resource data and the resource map. The resource data conventionally starts at 256
+pub(super) fn is_resource_fork(data: &[u8]) -> bool {
+    let get = |off| get_u32(data, off).map(|v| v as u64);
+    match (get(0), get(4), get(8), get(12)) {
+        (Ok(data_off), Ok(map_off), Ok(data_len), Ok(map_len)) => {
+            data_off == 256
+                && data_off + data_len <= map_off
+                && map_len >= 28
+                && map_off + map_len <= data.len() as u64
+        }
+        _ => false,
+    }
+}
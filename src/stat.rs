@@ -0,0 +1,89 @@
+//! Style attributes table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_u16, get_u16_unchecked, get_u32, get_u32_unchecked, Tag};
+
+/// Value of a design axis, from an axis value table
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct AxisValue {
+    /// Name ID of the value's name, e.g. "Display"
+    pub(super) name_id: u16,
+    pub(super) nominal: f32,
+    /// Lower end of the range of values covered. Same as `nominal` for single values
+    pub(super) min: f32,
+    /// Upper end of the range of values covered. Same as `nominal` for single values
+    pub(super) max: f32,
+}
+
+/// Handle to STAT table
+pub(super) struct Stat<'a>(&'a [u8]);
+
+impl<'a> Stat<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Stat<'_>> {
+        if data.len() < 18 {
+            return Err(Error::truncated(0, 18));
+        }
+        let axis_size = get_u16_unchecked(data, 4) as usize;
+        let axis_count = get_u16_unchecked(data, 6) as usize;
+        let axes_off = get_u32_unchecked(data, 8) as usize;
+        if axis_count > 0 && axis_size < 8 {
+            Err(Error::malformed("design axis records are too small"))
+        } else if data.len() < axes_off + axis_count * axis_size {
+            Err(Error::truncated(axes_off, axis_count * axis_size))
+        } else {
+            Ok(Stat(data))
+        }
+    }
+
+    /// Get values of design axis with `tag` from the axis value tables
+    pub(super) fn axis_values(&self, tag: Tag) -> Vec<AxisValue> {
+        let axis_size = get_u16_unchecked(self.0, 4) as usize;
+        let axis_count = get_u16_unchecked(self.0, 6) as usize;
+        let axes_off = get_u32_unchecked(self.0, 8) as usize;
+        let axis = match (0..axis_count)
+            .find(|&i| get_u32_unchecked(self.0, axes_off + i * axis_size) == tag.0)
+        {
+            Some(axis) => axis as u16,
+            None => return Vec::new(),
+        };
+        let value_count = get_u16_unchecked(self.0, 12) as usize;
+        let values = match self.0.get(get_u32_unchecked(self.0, 14) as usize..) {
+            Some(values) => values,
+            None => return Vec::new(),
+        };
+        (0..value_count)
+            .filter_map(|i| values.get(get_u16(values, i * 2).ok()? as usize..))
+            .filter_map(|value| axis_value(value, axis))
+            .collect()
+    }
+}
+
+/// Parse axis value table, if it has a value for axis at index `axis`
+fn axis_value(data: &[u8], axis: u16) -> Option<AxisValue> {
+    let fixed = |off| get_u32(data, off).ok().map(|v| v as i32 as f32 / 65536.0);
+    let format = get_u16(data, 0).ok()?;
+    let name_id = get_u16(data, 6).ok()?;
+    let (nominal, min, max) = match format {
+        1 | 3 if get_u16(data, 2).ok()? == axis => {
+            let value = fixed(8)?;
+            (value, value, value)
+        }
+        2 if get_u16(data, 2).ok()? == axis => (fixed(8)?, fixed(12)?, fixed(16)?),
+        4 => {
+            // Combination of values on several axes
+            let count = get_u16(data, 2).ok()? as usize;
+            let value = (0..count)
+                .find(|&i| get_u16(data, 8 + i * 6).ok() == Some(axis))
+                .and_then(|i| fixed(10 + i * 6))?;
+            (value, value, value)
+        }
+        _ => return None,
+    };
+    Some(AxisValue {
+        name_id,
+        nominal,
+        min,
+        max,
+    })
+}
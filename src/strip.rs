@@ -0,0 +1,141 @@
+//! Removing tables, features and hinting from fonts
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::glyf::Components;
+use super::head::IdxToLocFmt;
+use super::types::{get_i16, get_u16, get_u32, GlyphID, Tag};
+use super::write::FontBuilder;
+use super::{Face, FaceTyp};
+
+/// What to remove from a face with `Face::strip`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StripOptions {
+    /// Tables to remove
+    pub tables: Vec<Tag>,
+    /// GSUB features to disable. Lookups used by these features are kept, but the features no
+    /// longer refer to them. Feature tables shared with features that are not being disabled
+    /// are left alone
+    pub gsub_features: Vec<Tag>,
+    /// Remove TrueType instructions from glyph outlines
+    pub glyph_instructions: bool,
+}
+
+impl StripOptions {
+    /// Options for removing all TrueType hinting: the fpgm, prep, cvt, hdmx, LTSH and VDMX
+    /// tables, and glyph instructions
+    pub fn hinting() -> StripOptions {
+        StripOptions {
+            tables: [b"fpgm", b"prep", b"cvt ", b"hdmx", b"LTSH", b"VDMX"]
+                .iter()
+                .map(|tag| Tag::new(tag))
+                .collect(),
+            gsub_features: Vec::new(),
+            glyph_instructions: true,
+        }
+    }
+}
+
+pub(super) fn strip(face: &Face, options: &StripOptions) -> Result<Vec<u8>> {
+    let mut builder = FontBuilder::from_face(face);
+    // Any change to the font invalidates its signature
+    builder.remove_table(Tag::new(b"DSIG"));
+    for &tag in &options.tables {
+        builder.remove_table(tag);
+    }
+    if !options.gsub_features.is_empty() {
+        if let Some(gsub) = builder.remove_table(Tag::new(b"GSUB")) {
+            builder.add_table(
+                Tag::new(b"GSUB"),
+                disable_features(gsub, &options.gsub_features)?,
+            );
+        }
+    }
+    if options.glyph_instructions {
+        if let FaceTyp::TrueType(ref loca, ref glyf) = face.typ {
+            let num_glyphs = face.maxp.num_glyphs() as u32;
+            let short_loca = match face.head.idx_to_loc_fmt()? {
+                IdxToLocFmt::Off16 => true,
+                IdxToLocFmt::Off32 => false,
+            };
+            let mut glyf_data = Vec::with_capacity(glyf.0.len());
+            let mut loca_data = Vec::new();
+            for id in (0..num_glyphs).map(GlyphID) {
+                push_offset(&mut loca_data, glyf_data.len(), short_loca);
+                strip_instructions(glyf.data(loca.get_range(id)?)?, &mut glyf_data)?;
+                while glyf_data.len() % if short_loca { 2 } else { 4 } != 0 {
+                    glyf_data.push(0);
+                }
+            }
+            push_offset(&mut loca_data, glyf_data.len(), short_loca);
+            builder
+                .add_table(Tag::new(b"glyf"), glyf_data)
+                .add_table(Tag::new(b"loca"), loca_data);
+        }
+    }
+    Ok(builder.build())
+}
+
+fn push_offset(loca: &mut Vec<u8>, offset: usize, short_loca: bool) {
+    if short_loca {
+        loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+    } else {
+        loca.extend_from_slice(&(offset as u32).to_be_bytes());
+    }
+}
+
+/// Append glyph data without instructions to `out`
+pub(super) fn strip_instructions(data: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    let num_contours = get_i16(data, 0)?;
+    if num_contours >= 0 {
+        // Simple glyph. Set instructionLength to 0 and drop the instructions
+        let len_off = 10 + num_contours as usize * 2;
+        let instructions_len = get_u16(data, len_off)? as usize;
+        let rest = data
+            .get(len_off + 2 + instructions_len..)
+            .ok_or(Error::truncated(len_off + 2, instructions_len))?;
+        out.extend_from_slice(&data[..len_off]);
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(rest);
+    } else {
+        // Composite glyph. Clear WE_HAVE_INSTRUCTIONS, and drop everything after components
+        let start = out.len();
+        let mut components = Components::new(data);
+        let flag_offs = components
+            .by_ref()
+            .map(|(off, _)| off - 2)
+            .collect::<Vec<_>>();
+        let end = components.end();
+        out.extend_from_slice(data.get(..end).ok_or(Error::truncated(0, end))?);
+        for off in flag_offs {
+            out[start + off] &= !0x01;
+        }
+    }
+    Ok(())
+}
+
+/// Disable GSUB features by clearing the lookup indices of their Feature tables
+fn disable_features(mut gsub: Vec<u8>, features: &[Tag]) -> Result<Vec<u8>> {
+    let list_off = get_u16(&gsub, 6)? as usize;
+    let count = get_u16(&gsub, list_off)? as usize;
+    let mut records = Vec::with_capacity(count);
+    for i in 0..count {
+        let rec_off = list_off + 2 + i * 6;
+        let tag = Tag(get_u32(&gsub, rec_off)?);
+        let feature_off = list_off + get_u16(&gsub, rec_off + 4)? as usize;
+        records.push((features.contains(&tag), feature_off));
+    }
+    for &(disable, feature_off) in &records {
+        let shared = records
+            .iter()
+            .any(|&(other_disable, other_off)| !other_disable && other_off == feature_off);
+        if disable && !shared {
+            get_u16(&gsub, feature_off + 2)?;
+            gsub[feature_off + 2..feature_off + 4].copy_from_slice(&[0, 0]);
+        }
+    }
+    Ok(gsub)
+}
@@ -0,0 +1,265 @@
+//! Font subsetting
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::error::*;
+use super::glyf::Components;
+use super::gsub::Gsub;
+use super::types::{get_i16_unchecked, GlyphID, Tag};
+use super::write::FontBuilder;
+use super::{Face, FaceTyp};
+
+/// Tables copied unchanged into the subset font. Tables that refer to glyph IDs and are not
+/// rewritten are dropped
+const PASSTHROUGH_TABLES: [&[u8; 4]; 6] = [b"OS/2", b"name", b"cvt ", b"fpgm", b"prep", b"gasp"];
+
+/// Build a font containing only the glyphs needed to render `codepoints`, and the glyphs in
+/// `glyphs`. Glyphs are renumbered, keeping their original order. Returns the font data, and
+/// the mapping from original to new glyph IDs
+pub(super) fn subset(
+    face: &Face,
+    codepoints: &[u32],
+    glyphs: &[GlyphID],
+) -> Result<(Vec<u8>, BTreeMap<GlyphID, u16>)> {
+    let (loca, glyf) = match face.typ {
+        FaceTyp::TrueType(ref loca, ref glyf) => (loca, glyf),
+        FaceTyp::CFF => return Err(Error::Unimplemented("CFF subsetting".to_owned())),
+    };
+    let num_glyphs = face.maxp.num_glyphs() as u32;
+
+    // Compute glyph closure. .notdef is always kept
+    let mut keep = BTreeSet::new();
+    keep.insert(GlyphID(0));
+    let mut mapping = BTreeMap::new();
+    for &codepoint in codepoints {
+        // Mappings to glyphs that don't exist are dropped
+        let id = face.get_glyph_id(codepoint)?;
+        if id.0 != 0 && id.0 < num_glyphs {
+            mapping.insert(codepoint, id);
+            keep.insert(id);
+        }
+    }
+    for &id in glyphs {
+        if id.0 >= num_glyphs {
+            return Err(Error::GlyphIDOutOfBounds);
+        }
+        keep.insert(id);
+    }
+    if let Some(Ok(gsub)) = face.table(Tag::new(b"GSUB")).map(Gsub::load) {
+        gsub.closure(&mut keep);
+        keep.retain(|id| id.0 < num_glyphs);
+    }
+    let mut pending = keep.iter().cloned().collect::<Vec<_>>();
+    while let Some(id) = pending.pop() {
+        let data = glyf.data(loca.get_range(id)?)?;
+        if data.len() >= 10 && get_i16_unchecked(data, 0) < 0 {
+            for (_, component) in Components::new(data) {
+                if component.0 >= num_glyphs {
+                    return Err(Error::GlyphIDOutOfBounds);
+                }
+                if keep.insert(component) {
+                    pending.push(component);
+                }
+            }
+        }
+    }
+    let new_ids = keep
+        .iter()
+        .enumerate()
+        .map(|(new, &old)| (old, new as u16))
+        .collect::<BTreeMap<_, _>>();
+
+    // glyf, loca, and hmtx
+    let hmtx = face.hmtx()?;
+    let mut glyf_data = Vec::new();
+    let mut offsets = Vec::with_capacity(keep.len() + 1);
+    let mut metrics = Vec::with_capacity(keep.len());
+    for &id in &keep {
+        offsets.push(glyf_data.len());
+        let start = glyf_data.len();
+        glyf_data.extend_from_slice(glyf.data(loca.get_range(id)?)?);
+        let data = &mut glyf_data[start..];
+        if data.len() >= 10 && get_i16_unchecked(data, 0) < 0 {
+            let components = Components::new(data).collect::<Vec<_>>();
+            for (off, component) in components {
+                data[off..off + 2].copy_from_slice(&new_ids[&component].to_be_bytes());
+            }
+        }
+        while glyf_data.len() % 4 != 0 {
+            glyf_data.push(0);
+        }
+        metrics.push((hmtx.advance(id)?, hmtx.lsb(id)?));
+    }
+    offsets.push(glyf_data.len());
+    let (loca_data, short_loca) = build_loca(&offsets);
+    let (hmtx_data, num_h_metrics) = build_hmtx(&metrics);
+
+    // Tables with glyph counts and formats to patch
+    let mut head = face.table(Tag::new(b"head")).unwrap_or(&[]).to_vec();
+    head[50..52].copy_from_slice(&(if short_loca { 0u16 } else { 1 }).to_be_bytes());
+    let mut hhea = face.table(Tag::new(b"hhea")).unwrap_or(&[]).to_vec();
+    hhea[34..36].copy_from_slice(&(num_h_metrics as u16).to_be_bytes());
+    let mut maxp = face.table(Tag::new(b"maxp")).unwrap_or(&[]).to_vec();
+    maxp[4..6].copy_from_slice(&(keep.len() as u16).to_be_bytes());
+
+    let mut builder = FontBuilder::new(face.sfnt_version);
+    builder
+        .add_table(Tag::new(b"head"), head)
+        .add_table(Tag::new(b"hhea"), hhea)
+        .add_table(Tag::new(b"maxp"), maxp)
+        .add_table(Tag::new(b"hmtx"), hmtx_data)
+        .add_table(Tag::new(b"loca"), loca_data)
+        .add_table(Tag::new(b"glyf"), glyf_data)
+        .add_table(
+            Tag::new(b"cmap"),
+            build_cmap(mapping.iter().map(|(&cp, id)| (cp, new_ids[id]))),
+        );
+    if let Some(post) = face
+        .table(Tag::new(b"post"))
+        .and_then(|post| post.get(..32))
+    {
+        // Version 3.0 has no glyph names
+        let mut post = post.to_vec();
+        post[0..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+        builder.add_table(Tag::new(b"post"), post);
+    }
+    for tag in PASSTHROUGH_TABLES.iter() {
+        let tag = Tag::new(tag);
+        if let Some(data) = face.table(tag) {
+            builder.add_table(tag, data.to_vec());
+        }
+    }
+    Ok((builder.build(), new_ids))
+}
+
+/// Build loca table from glyph offsets, including the end offset of the last glyph. Offsets
+/// must be even. Returns the table, and whether the short format was used
+pub(super) fn build_loca(offsets: &[usize]) -> (Vec<u8>, bool) {
+    let short_loca = offsets.last().is_none_or(|&end| end <= 0x1fffe);
+    let mut loca = Vec::new();
+    for &off in offsets {
+        if short_loca {
+            loca.extend_from_slice(&((off / 2) as u16).to_be_bytes());
+        } else {
+            loca.extend_from_slice(&(off as u32).to_be_bytes());
+        }
+    }
+    (loca, short_loca)
+}
+
+/// Build hmtx table from (advance, left side bearing) pairs. Returns the table, and the
+/// number of longHorMetric records
+pub(super) fn build_hmtx(metrics: &[(u16, i16)]) -> (Vec<u8>, usize) {
+    // Glyphs at the end with the same advance only need a left side bearing
+    let last_advance = metrics.last().map_or(0, |&(advance, _)| advance);
+    let num_h_metrics = metrics.len()
+        - metrics
+            .iter()
+            .rev()
+            .skip(1)
+            .take_while(|&&(advance, _)| advance == last_advance)
+            .count();
+    let mut hmtx = Vec::new();
+    for (i, &(advance, lsb)) in metrics.iter().enumerate() {
+        if i < num_h_metrics {
+            hmtx.extend_from_slice(&advance.to_be_bytes());
+        }
+        hmtx.extend_from_slice(&lsb.to_be_bytes());
+    }
+    (hmtx, num_h_metrics)
+}
+
+/// Most segments that fit in a format 4 subtable, whose length is 16 bits
+const MAX_FORMAT4_SEGMENTS: usize = (0xffff - 16) / 8;
+
+/// Build cmap table from sorted (codepoint, glyph ID) pairs. This has a format 4 subtable for
+/// the BMP, and a format 12 subtable if there are supplementary-plane codepoints. If the BMP
+/// mapping needs more segments than fit in format 4, the format 4 subtable only has the first
+/// ones, and the format 12 subtable has the full mapping. U+FFFF is never mapped in format 4,
+/// since its last segment must map U+FFFF to .notdef
+pub(super) fn build_cmap<I: Iterator<Item = (u32, u16)>>(mapping: I) -> Vec<u8> {
+    // Ranges of consecutive codepoints mapping to consecutive glyphs
+    let mut groups: Vec<(u32, u32, u16)> = Vec::new();
+    for (codepoint, id) in mapping {
+        match groups.last_mut() {
+            Some(&mut (start, ref mut end, start_id))
+                if *end + 1 == codepoint
+                    && u32::from(start_id) + codepoint - start == u32::from(id) =>
+            {
+                *end = codepoint
+            }
+            _ => groups.push((codepoint, codepoint, id)),
+        }
+    }
+
+    // Format 4, with the required 0xFFFF segment mapping to glyph 0 at the end
+    let mut segments = groups
+        .iter()
+        .filter(|&&(start, _, _)| start < 0xffff)
+        .map(|&(start, end, id)| (start as u16, end.min(0xfffe) as u16, id))
+        .collect::<Vec<_>>();
+    let split = segments.len() >= MAX_FORMAT4_SEGMENTS;
+    segments.truncate(MAX_FORMAT4_SEGMENTS - 1);
+    segments.push((0xffff, 0xffff, 0));
+    let seg_count = segments.len();
+    let mut format4 = Vec::new();
+    let search_range = 2 * (1usize << (usize::BITS - 1 - seg_count.leading_zeros()));
+    for &field in [
+        4,
+        (16 + seg_count * 8) as u16,
+        0,
+        (seg_count * 2) as u16,
+        search_range as u16,
+        (search_range / 2).trailing_zeros() as u16,
+        (seg_count * 2 - search_range) as u16,
+    ]
+    .iter()
+    {
+        format4.extend_from_slice(&field.to_be_bytes());
+    }
+    for &(_, end, _) in &segments {
+        format4.extend_from_slice(&end.to_be_bytes());
+    }
+    format4.extend_from_slice(&[0, 0]);
+    for &(start, _, _) in &segments {
+        format4.extend_from_slice(&start.to_be_bytes());
+    }
+    for &(start, _, id) in &segments {
+        format4.extend_from_slice(&id.wrapping_sub(start).to_be_bytes());
+    }
+    for _ in &segments {
+        format4.extend_from_slice(&[0, 0]);
+    }
+
+    let mut subtables = vec![(3u16, 1u16, format4)];
+    if split || groups.iter().any(|&(_, end, _)| end > 0xffff) {
+        let mut format12 = Vec::new();
+        format12.extend_from_slice(&12u16.to_be_bytes());
+        format12.extend_from_slice(&0u16.to_be_bytes());
+        format12.extend_from_slice(&((16 + groups.len() * 12) as u32).to_be_bytes());
+        format12.extend_from_slice(&0u32.to_be_bytes());
+        format12.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+        for &(start, end, id) in &groups {
+            format12.extend_from_slice(&start.to_be_bytes());
+            format12.extend_from_slice(&end.to_be_bytes());
+            format12.extend_from_slice(&u32::from(id).to_be_bytes());
+        }
+        subtables.push((3, 10, format12));
+    }
+
+    let mut cmap = Vec::new();
+    cmap.extend_from_slice(&0u16.to_be_bytes());
+    cmap.extend_from_slice(&(subtables.len() as u16).to_be_bytes());
+    let mut offset = 4 + subtables.len() * 8;
+    for &(platform_id, encoding_id, ref data) in &subtables {
+        cmap.extend_from_slice(&platform_id.to_be_bytes());
+        cmap.extend_from_slice(&encoding_id.to_be_bytes());
+        cmap.extend_from_slice(&(offset as u32).to_be_bytes());
+        offset += data.len();
+    }
+    for (_, _, data) in subtables {
+        cmap.extend_from_slice(&data);
+    }
+    cmap
+}
@@ -0,0 +1,202 @@
+//! Enumeration and matching of fonts installed on the system
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::path::{Path, PathBuf};
+
+use super::error::*;
+use super::scan::MAX_DEPTH;
+use super::FontCollection;
+
+/// Families tried, in order, for the generic "monospace" family
+const MONOSPACE: [&str; 8] = [
+    "DejaVu Sans Mono",
+    "Liberation Mono",
+    "Noto Sans Mono",
+    "Ubuntu Mono",
+    "Menlo",
+    "SF Mono",
+    "Consolas",
+    "Courier New",
+];
+
+/// Families tried, in order, for the generic "serif" family
+const SERIF: [&str; 6] = [
+    "DejaVu Serif",
+    "Liberation Serif",
+    "Noto Serif",
+    "Times New Roman",
+    "Times",
+    "Georgia",
+];
+
+/// Families tried, in order, for the generic "sans-serif" family
+const SANS_SERIF: [&str; 6] = [
+    "DejaVu Sans",
+    "Liberation Sans",
+    "Noto Sans",
+    "Helvetica",
+    "Arial",
+    "Segoe UI",
+];
+
+/// A face installed on the system
+#[derive(Clone, Debug, PartialEq)]
+pub struct SystemFont {
+    /// Path of the font file
+    pub path: PathBuf,
+    /// Index of the face within the font file
+    pub index: usize,
+    pub family: String,
+    pub weight: u16,
+    pub italic: bool,
+    pub monospace: bool,
+}
+
+impl SystemFont {
+    /// Load the font file containing this face. The face is at `self.index` in the collection
+    pub fn load(&self) -> Result<FontCollection> {
+        FontCollection::new(&self.path)
+    }
+}
+
+/// Enumerate faces installed on the system, by scanning the platform's font directories.
+/// Files that fail to load, and faces without a family name, are skipped
+pub fn system_fonts() -> Vec<SystemFont> {
+    let mut fonts = Vec::new();
+    for dir in font_dirs() {
+        visit(&dir, 0, &mut fonts);
+    }
+    fonts
+}
+
+/// Find the installed face that best matches `family`, `weight` (e.g. 400 for regular, 700
+/// for bold) and style. Besides family names, the generic families "monospace", "serif" and
+/// "sans-serif" are accepted
+pub fn find(family: &str, weight: u16, italic: bool) -> Option<SystemFont> {
+    best_match(&system_fonts(), family, weight, italic).cloned()
+}
+
+/// Pick the face from `fonts` that best matches `family`, `weight` and style. Within a family,
+/// matching the style takes priority over matching the weight
+pub(super) fn best_match<'a>(
+    fonts: &'a [SystemFont],
+    family: &str,
+    weight: u16,
+    italic: bool,
+) -> Option<&'a SystemFont> {
+    let distance = |font: &&SystemFont| {
+        (
+            font.italic != italic,
+            (font.weight as i32 - weight as i32).abs(),
+        )
+    };
+    let families: &[&str] = if family.eq_ignore_ascii_case("monospace") {
+        &MONOSPACE
+    } else if family.eq_ignore_ascii_case("serif") {
+        &SERIF
+    } else if family.eq_ignore_ascii_case("sans-serif") {
+        &SANS_SERIF
+    } else {
+        &[]
+    };
+    let in_family = |name: &str| {
+        fonts
+            .iter()
+            .filter(|font| font.family.eq_ignore_ascii_case(name))
+            .min_by_key(distance)
+    };
+    if families.is_empty() {
+        return in_family(family);
+    }
+    families
+        .iter()
+        .find_map(|name| in_family(name))
+        .or_else(|| {
+            if families == MONOSPACE {
+                fonts
+                    .iter()
+                    .filter(|font| font.monospace)
+                    .min_by_key(distance)
+            } else {
+                None
+            }
+        })
+}
+
+/// Directories fonts are installed in on this platform
+fn font_dirs() -> Vec<PathBuf> {
+    let env_dir = |var| std::env::var_os(var).map(PathBuf::from);
+    let home = env_dir("HOME");
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        dirs.extend(home.map(|home| home.join("Library/Fonts")));
+    } else if cfg!(windows) {
+        let windir = env_dir("WINDIR").unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+        dirs.push(windir.join("Fonts"));
+        dirs.extend(env_dir("LOCALAPPDATA").map(|dir| dir.join("Microsoft\\Windows\\Fonts")));
+    } else {
+        // XDG base directories, as used by fontconfig
+        let data_home = env_dir("XDG_DATA_HOME")
+            .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+        dirs.extend(data_home.map(|dir| dir.join("fonts")));
+        dirs.extend(home.map(|home| home.join(".fonts")));
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_owned());
+        dirs.extend(
+            data_dirs
+                .split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| Path::new(dir).join("fonts")),
+        );
+    }
+    dirs
+}
+
+/// Recursively add faces from font files in `dir`
+fn visit(dir: &Path, depth: usize, fonts: &mut Vec<SystemFont>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                visit(&path, depth + 1, fonts);
+            }
+            continue;
+        }
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("ttf") | Some("otf") | Some("ttc") | Some("otc") | Some("dfont") => (),
+            _ => continue,
+        }
+        let fc = match FontCollection::new(&path) {
+            Ok(fc) => fc,
+            Err(_) => continue,
+        };
+        for (index, face) in fc.faces().enumerate() {
+            let face = match face {
+                Ok(face) => face,
+                Err(_) => continue,
+            };
+            if let Some(family) = face.family_name() {
+                fonts.push(SystemFont {
+                    path: path.clone(),
+                    index,
+                    family,
+                    weight: face.weight(),
+                    italic: face.is_italic(),
+                    monospace: face.is_monospace(),
+                });
+            }
+        }
+    }
+}
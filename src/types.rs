@@ -1,13 +1,13 @@
 //! Types and accessors
 // (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
 
-use rster::Point;
+use super::geom::Point;
 use crate::error::*;
 
 /// Get u8 checked
 pub(super) fn get_u8(data: &[u8], off: usize) -> Result<u8> {
-    if off + 1 > data.len() {
-        Err(Error::Invalid)
+    if data.len().saturating_sub(off) < 1 {
+        Err(Error::truncated(off, 1))
     } else {
         Ok(data[off])
     }
@@ -15,8 +15,8 @@ pub(super) fn get_u8(data: &[u8], off: usize) -> Result<u8> {
 
 /// Get big-endian u16
 pub(super) fn get_u16(data: &[u8], off: usize) -> Result<u16> {
-    if off + 2 > data.len() {
-        Err(Error::Invalid)
+    if data.len().saturating_sub(off) < 2 {
+        Err(Error::truncated(off, 2))
     } else {
         Ok(((data[off] as u16) << 8) | (data[off + 1] as u16))
     }
@@ -24,8 +24,8 @@ pub(super) fn get_u16(data: &[u8], off: usize) -> Result<u16> {
 
 /// Get big-endian i16
 pub(super) fn get_i16(data: &[u8], off: usize) -> Result<i16> {
-    if off + 2 > data.len() {
-        Err(Error::Invalid)
+    if data.len().saturating_sub(off) < 2 {
+        Err(Error::truncated(off, 2))
     } else {
         Ok(((data[off] as i16) << 8) | (data[off + 1] as i16))
     }
@@ -33,8 +33,8 @@ pub(super) fn get_i16(data: &[u8], off: usize) -> Result<i16> {
 
 /// Get big-endian u32
 pub(super) fn get_u32(data: &[u8], off: usize) -> Result<u32> {
-    if off + 4 > data.len() {
-        Err(Error::Invalid)
+    if data.len().saturating_sub(off) < 4 {
+        Err(Error::truncated(off, 4))
     } else {
         Ok(((data[off] as u32) << 24)
             | ((data[off + 1] as u32) << 16)
@@ -43,6 +43,37 @@ pub(super) fn get_u32(data: &[u8], off: usize) -> Result<u32> {
     }
 }
 
+/// Get subtable at 16-bit offset stored at `off`. NULL offsets give `None`
+pub(super) fn subtable(data: &[u8], off: usize) -> Option<&[u8]> {
+    match get_u16(data, off).ok()? as usize {
+        0 => None,
+        off => data.get(off..),
+    }
+}
+
+/// Compute checksum of table data, as the sum of big-endian u32 words. The data is padded with
+/// zeroes to a multiple of 4 bytes
+pub(super) fn table_checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let word = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |word, (i, &b)| word | ((b as u32) << (24 - i * 8)));
+        sum.wrapping_add(word)
+    })
+}
+
+/// Initial value for `fnv1a`
+pub(super) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Continue 64-bit FNV-1a hash `hash` with `data`. Unlike std's hashers, this is stable across
+/// Rust versions and platforms
+pub(super) fn fnv1a(hash: u64, data: &[u8]) -> u64 {
+    data.iter().fold(hash, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
 /// Get big-endian u16 without checking. Could panic
 pub(super) fn get_u16_unchecked(data: &[u8], off: usize) -> u16 {
     ((data[off] as u16) << 8) | (data[off + 1] as u16)
@@ -60,14 +91,39 @@ pub(super) fn get_u32_unchecked(data: &[u8], off: usize) -> u32 {
         | ((data[off + 2] as u32) << 8)
         | (data[off + 3] as u32)
 }
-/// OpenType "tag"s are used to uniquely identify resources like tables etc.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-pub(super) struct Tag(pub(super) u32);
+/// OpenType "tag"s are used to uniquely identify resources like tables, scripts, languages,
+/// and features. A tag is four ASCII characters, stored as a big-endian `u32`
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Tag(pub u32);
 
 impl Tag {
-    /// Create tag from string representation
-    pub(super) fn from_str(s: &str) -> Tag {
-        Tag(get_u32(s.as_bytes(), 0).unwrap())
+    /// Create tag from its four bytes
+    pub const fn new(bytes: &[u8; 4]) -> Tag {
+        Tag(((bytes[0] as u32) << 24)
+            | ((bytes[1] as u32) << 16)
+            | ((bytes[2] as u32) << 8)
+            | (bytes[3] as u32))
+    }
+}
+
+impl std::str::FromStr for Tag {
+    type Err = Error;
+
+    /// Parse tag from string. Tags shorter than four characters are padded with spaces
+    fn from_str(s: &str) -> Result<Tag> {
+        let bytes = s.as_bytes();
+        if bytes.is_empty() || bytes.len() > 4 || bytes.iter().any(|b| !(0x20..=0x7e).contains(b)) {
+            return Err(Error::InvalidTag);
+        }
+        let mut buf = [b' '; 4];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Ok(Tag::new(&buf))
+    }
+}
+
+impl std::fmt::Debug for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Tag({:?})", self.to_string())
     }
 }
 
@@ -81,18 +137,255 @@ impl std::fmt::Display for Tag {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Tag {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 /// Get tag from big-endian data
 pub(super) fn get_tag(data: &[u8], off: usize) -> Result<Tag> {
     get_u32(data, off).map(|n| Tag(n))
 }
 
 /// Glyph ID that is available to consumers of the library
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct GlyphID(pub(super) u32);
 
-/// Affine transformation matrix
-#[derive(Clone, Debug)]
-pub(super) struct Affine {
+impl GlyphID {
+    /// The `.notdef` glyph, used for characters the face doesn't map
+    pub const NOTDEF: GlyphID = GlyphID(0);
+
+    /// Create glyph ID, e.g. from a PDF CID or another library's glyph index. The ID is
+    /// checked against the number of glyphs in the face when it is used
+    pub const fn new(id: u32) -> GlyphID {
+        GlyphID(id)
+    }
+
+    /// Get glyph ID as an integer
+    pub const fn to_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u16> for GlyphID {
+    fn from(id: u16) -> GlyphID {
+        GlyphID(id as u32)
+    }
+}
+
+impl From<GlyphID> for u32 {
+    fn from(id: GlyphID) -> u32 {
+        id.0
+    }
+}
+
+/// Position and thickness of a text decoration line (underline, strikeout), in font units
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecorationMetrics {
+    /// Position of the top of the line, relative to the baseline
+    pub position: i16,
+    /// Thickness of the line
+    pub thickness: i16,
+}
+
+/// Position and thickness of a text decoration line, in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScaledDecorationMetrics {
+    /// Position of the top of the line, relative to the baseline, with y increasing upwards
+    pub position: f32,
+    /// Thickness of the line
+    pub thickness: f32,
+}
+
+/// Advance of a space character, which may have been synthesized because the face has no glyph
+/// for it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpaceAdvance {
+    /// Horizontal advance
+    pub advance: f32,
+    /// Whether the advance was synthesized rather than taken from the face
+    pub synthesized: bool,
+}
+
+/// Recommended size and offset of subscripts or superscripts. Offsets are measured from the
+/// pen position and baseline of the surrounding text, with y increasing upwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScriptMetrics {
+    /// Horizontal em size of the script glyphs
+    pub x_size: f32,
+    /// Vertical em size of the script glyphs
+    pub y_size: f32,
+    /// Horizontal offset of the script's pen position
+    pub x_offset: f32,
+    /// Vertical offset of the script's baseline
+    pub y_offset: f32,
+}
+
+/// Transformation for synthesizing subscripts or superscripts from regular glyphs, when the
+/// face has no dedicated glyphs for them. Points are scaled about the origin, then offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScriptTransform {
+    /// Horizontal scale factor
+    pub scale_x: f32,
+    /// Vertical scale factor
+    pub scale_y: f32,
+    /// Horizontal offset, in the units of the transformed points
+    pub offset_x: f32,
+    /// Vertical offset, in the units of the transformed points, with y increasing upwards
+    pub offset_y: f32,
+}
+
+impl ScriptTransform {
+    /// Get transformation as an `Affine`
+    pub fn to_affine(&self) -> Affine {
+        Affine::scaling(self.scale_x, self.scale_y).translated(self.offset_x, self.offset_y)
+    }
+
+    /// Transform a point relative to the pen position on the baseline, with y increasing
+    /// upwards
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.scale_x + self.offset_x,
+            y * self.scale_y + self.offset_y,
+        )
+    }
+}
+
+/// Style of a face, for sorting and grouping faces in font pickers
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct FaceStyle {
+    /// Weight class, from 1 to 1000. 400 is regular and 700 is bold
+    pub weight: u16,
+    /// Width class, from 1 (ultra-condensed) to 9 (ultra-expanded). 5 is normal
+    pub stretch: u16,
+    /// Whether the face is italic or oblique
+    pub italic: bool,
+}
+
+/// Table to take ascender, descender, and line gap values from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MetricsSource {
+    /// OS/2 typographic metrics if `USE_TYPO_METRICS` is set, else OS/2 Windows metrics, else
+    /// hhea metrics
+    Auto,
+    /// OS/2 `sTypoAscender`, `sTypoDescender`, and `sTypoLineGap`
+    Typo,
+    /// OS/2 `usWinAscent` and `usWinDescent`. The line gap is whatever is left over from the
+    /// hhea line height
+    Win,
+    /// hhea `ascender`, `descender`, and `lineGap`
+    Hhea,
+}
+
+/// Vertical metrics for laying out lines of text. The descender is negative for descents below
+/// the baseline
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Distance from the baseline to the top of the line
+    pub ascender: f32,
+    /// Distance from the baseline to the bottom of the line
+    pub descender: f32,
+    /// Extra spacing between the bottom of one line and the top of the next
+    pub line_gap: f32,
+}
+
+impl LineMetrics {
+    /// Distance between consecutive baselines
+    pub fn line_height(&self) -> f32 {
+        self.ascender - self.descender + self.line_gap
+    }
+
+    /// Combine metrics of runs on one line, e.g. with different faces or sizes, into metrics of
+    /// the whole line. As in CSS, the line gap of each run is split evenly above and below it,
+    /// and the line extends to the highest and lowest of these. The strut, usually metrics of
+    /// the paragraph's primary face, is included as if it were a run, so lines are never
+    /// shorter than it. The half-leadings are folded into the ascender and descender of the
+    /// result, and its line gap is 0
+    pub fn combine<I>(runs: I, strut: Option<LineMetrics>) -> LineMetrics
+    where
+        I: IntoIterator<Item = LineMetrics>,
+    {
+        runs.into_iter()
+            .chain(strut)
+            .map(|metrics| LineMetrics {
+                ascender: metrics.ascender + metrics.line_gap / 2.0,
+                descender: metrics.descender - metrics.line_gap / 2.0,
+                line_gap: 0.0,
+            })
+            .fold(None, |line: Option<LineMetrics>, run| {
+                Some(match line {
+                    Some(line) => LineMetrics {
+                        ascender: line.ascender.max(run.ascender),
+                        descender: line.descender.min(run.descender),
+                        line_gap: 0.0,
+                    },
+                    None => run,
+                })
+            })
+            .unwrap_or(LineMetrics {
+                ascender: 0.0,
+                descender: 0.0,
+                line_gap: 0.0,
+            })
+    }
+}
+
+/// How scaled advances and glyph positions are snapped to the pixel grid
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Rounding {
+    /// Keep full fractional precision, for document renderers
+    #[default]
+    None,
+    /// Round down to whole pixels
+    Floor,
+    /// Round to the nearest whole pixel, e.g. for terminal cell advances
+    Round,
+}
+
+impl Rounding {
+    /// Apply rounding mode to a value in pixels
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Rounding::None => value,
+            Rounding::Floor => value.floor(),
+            Rounding::Round => value.round(),
+        }
+    }
+}
+
+/// Anti-aliasing quality of rendered glyphs
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Antialiasing {
+    /// No anti-aliasing. Pixels are fully covered if at least half covered, and empty
+    /// otherwise
+    None,
+    /// Analytic coverage computed at the target resolution
+    #[default]
+    Normal,
+    /// Analytic coverage computed at twice the target resolution in each direction and
+    /// averaged, which follows curves more closely at about four times the cost
+    High,
+}
+
+/// Options controlling how font data is parsed
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParseOptions {
+    /// Reject data that does not follow the spec. When `false` (the default), common defects
+    /// in real-world fonts are repaired where possible: tables running past the end of the
+    /// file are truncated, overlapping tables are allowed, and a truncated hmtx table is read
+    /// with fewer metrics
+    pub strict: bool,
+}
+
+/// 2x3 affine transformation matrix, mapping a point (x, y) to
+/// (a0 * x + b0 * y + c0, a1 * x + b1 * y + c1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
     a0: f32,
     b0: f32,
     c0: f32,
@@ -101,92 +394,115 @@ pub(super) struct Affine {
     c1: f32,
 }
 
-impl Affine {
-    pub(super) fn apply_point(&self, p: &Point) -> Point {
-        Point {
-            x: self.a0 * p.x + self.b0 * p.y + self.c0,
-            y: self.a1 * p.x + self.b1 * p.y + self.c1,
-        }
+impl Default for Affine {
+    fn default() -> Affine {
+        Affine::ident()
     }
+}
 
-    pub(super) fn ident() -> Affine {
+impl Affine {
+    /// Create transformation from matrix coefficients, in the order
+    /// `[a0, b0, c0, a1, b1, c1]`
+    pub fn new(coeffs: [f32; 6]) -> Affine {
         Affine {
-            a0: 1.0,
-            b0: 0.0,
-            c0: 0.0,
-            a1: 0.0,
-            b1: 1.0,
-            c1: 0.0,
+            a0: coeffs[0],
+            b0: coeffs[1],
+            c0: coeffs[2],
+            a1: coeffs[3],
+            b1: coeffs[4],
+            c1: coeffs[5],
         }
     }
 
-    pub(super) fn scaling(x: f32, y: f32) -> Affine {
-        Affine {
-            a0: x,
-            b0: 0.0,
-            c0: 0.0,
-            a1: 0.0,
-            b1: y,
-            c1: 0.0,
-        }
+    /// Get matrix coefficients, in the order `[a0, b0, c0, a1, b1, c1]`
+    pub fn coeffs(&self) -> [f32; 6] {
+        [self.a0, self.b0, self.c0, self.a1, self.b1, self.c1]
     }
 
-    pub(super) fn translation(x: f32, y: f32) -> Affine {
-        Affine {
-            a0: 1.0,
-            b0: 0.0,
-            c0: x,
-            a1: 0.0,
-            b1: 1.0,
-            c1: y,
-        }
+    /// Identity transformation
+    pub fn ident() -> Affine {
+        Affine::new([1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
     }
 
-    pub(super) fn rotation(angle: f32) -> Affine {
+    /// Scaling about the origin
+    pub fn scaling(x: f32, y: f32) -> Affine {
+        Affine::new([x, 0.0, 0.0, 0.0, y, 0.0])
+    }
+
+    /// Translation by (x, y)
+    pub fn translation(x: f32, y: f32) -> Affine {
+        Affine::new([1.0, 0.0, x, 0.0, 1.0, y])
+    }
+
+    /// Rotation about the origin by `angle` radians, counter-clockwise when y increases
+    /// upwards
+    pub fn rotation(angle: f32) -> Affine {
         let cos = angle.cos();
         let sin = angle.sin();
-        Affine {
-            a0: cos,
-            b0: -sin,
-            c0: 0.0,
-            a1: sin,
-            b1: cos,
-            c1: 0.0,
-        }
+        Affine::new([cos, -sin, 0.0, sin, cos, 0.0])
     }
 
-    pub(super) fn scaled(self, x: f32, y: f32) -> Affine {
+    /// Get transformation that applies `self`, then `next`
+    pub fn then(&self, next: &Affine) -> Affine {
         Affine {
-            a0: self.a0 * x,
-            b0: self.b0 * x,
-            c0: self.c0 * x,
-            a1: self.a1 * y,
-            b1: self.b1 * y,
-            c1: self.c1 * y,
+            a0: next.a0 * self.a0 + next.b0 * self.a1,
+            b0: next.a0 * self.b0 + next.b0 * self.b1,
+            c0: next.a0 * self.c0 + next.b0 * self.c1 + next.c0,
+            a1: next.a1 * self.a0 + next.b1 * self.a1,
+            b1: next.a1 * self.b0 + next.b1 * self.b1,
+            c1: next.a1 * self.c0 + next.b1 * self.c1 + next.c1,
         }
     }
 
-    pub(super) fn translated(self, x: f32, y: f32) -> Affine {
-        Affine {
-            a0: self.a0,
-            b0: self.b0,
-            c0: self.c0 * x,
-            a1: self.a1,
-            b1: self.b1,
-            c1: self.c1 * y,
-        }
+    /// Apply `self`, then scaling about the origin
+    pub fn scaled(self, x: f32, y: f32) -> Affine {
+        self.then(&Affine::scaling(x, y))
     }
 
-    pub(super) fn rotated(self, angle: f32) -> Affine {
-        let cos = angle.cos();
-        let sin = angle.sin();
-        Affine {
-            a0: self.a0 * cos - self.a1 * sin,
-            b0: self.b0 * cos - self.b1 * sin,
-            c0: self.c0 * cos - self.c1 * sin,
-            a1: self.a0 * sin + self.a1 * cos,
-            b1: self.a0 * sin + self.a1 * cos,
-            c1: self.a0 * sin + self.a1 * cos,
+    /// Apply `self`, then translation by (x, y)
+    pub fn translated(self, x: f32, y: f32) -> Affine {
+        self.then(&Affine::translation(x, y))
+    }
+
+    /// Apply `self`, then rotation about the origin by `angle` radians
+    pub fn rotated(self, angle: f32) -> Affine {
+        self.then(&Affine::rotation(angle))
+    }
+
+    /// Get inverse transformation, or `None` if the matrix is singular
+    pub fn invert(&self) -> Option<Affine> {
+        let det = self.a0 * self.b1 - self.b0 * self.a1;
+        if det == 0.0 || !det.is_finite() {
+            return None;
         }
+        let (a0, b0, a1, b1) = (self.b1 / det, -self.b0 / det, -self.a1 / det, self.a0 / det);
+        Some(Affine {
+            a0,
+            b0,
+            c0: -(a0 * self.c0 + b0 * self.c1),
+            a1,
+            b1,
+            c1: -(a1 * self.c0 + b1 * self.c1),
+        })
+    }
+
+    /// Transform a point
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a0 * x + self.b0 * y + self.c0,
+            self.a1 * x + self.b1 * y + self.c1,
+        )
+    }
+
+    /// Transform a vector, like the difference between two points. Translation doesn't affect
+    /// vectors
+    pub fn apply_vector(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a0 * x + self.b0 * y, self.a1 * x + self.b1 * y)
+    }
+
+    /// Transform a point
+    pub fn apply_point(&self, p: Point) -> Point {
+        let (x, y) = self.apply(p.x, p.y);
+        Point::new(x, y)
     }
 }
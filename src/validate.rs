@@ -0,0 +1,53 @@
+//! Sanity checks for font files, for QA pipelines
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::types::Tag;
+
+/// Expected value of the `magicNumber` field in the head table
+pub(super) const HEAD_MAGIC: u32 = 0x5F0F_3CF5;
+
+/// The whole-font checksum, including `checkSumAdjustment`, should add up to this
+pub(super) const CHECKSUM_MAGIC: u32 = 0xB1B0_AFBA;
+
+/// A problem found while validating a face
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ValidationProblem {
+    /// The head table has the wrong `magicNumber`
+    BadMagicNumber(u32),
+    /// The checksum stored in the table directory does not match the table data
+    TableChecksum {
+        tag: Tag,
+        stored: u32,
+        computed: u32,
+    },
+    /// `checkSumAdjustment` in the head table does not match the font data
+    ChecksumAdjustment { stored: u32, computed: u32 },
+    /// A table is present, but could not be parsed
+    InvalidTable(Tag),
+}
+
+impl std::fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationProblem::BadMagicNumber(magic) => {
+                write!(f, "bad head magic number: {:#010x}", magic)
+            }
+            ValidationProblem::TableChecksum {
+                tag,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "checksum mismatch for '{}' table: stored {:#010x}, computed {:#010x}",
+                tag, stored, computed
+            ),
+            ValidationProblem::ChecksumAdjustment { stored, computed } => write!(
+                f,
+                "checkSumAdjustment mismatch: stored {:#010x}, computed {:#010x}",
+                stored, computed
+            ),
+            ValidationProblem::InvalidTable(tag) => write!(f, "invalid '{}' table", tag),
+        }
+    }
+}
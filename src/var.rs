@@ -0,0 +1,128 @@
+//! Item variation store, shared by the variation data of GDEF, GPOS and other tables of
+//! variable fonts
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16, get_u16, get_u32};
+
+/// Convert F2DOT14 value to float
+pub(super) fn f2dot14(value: i16) -> f32 {
+    value as f32 / 16384.0
+}
+
+/// Handle to ItemVariationStore
+#[derive(Clone, Copy)]
+pub(super) struct ItemVariationStore<'a> {
+    data: &'a [u8],
+    regions: &'a [u8],
+}
+
+impl<'a> ItemVariationStore<'a> {
+    pub(super) fn load(data: &'a [u8]) -> Result<ItemVariationStore<'a>> {
+        if get_u16(data, 0)? != 1 {
+            return Err(Error::malformed("unsupported item variation store format"));
+        }
+        let regions_off = get_u32(data, 2)? as usize;
+        let regions = data
+            .get(regions_off..)
+            .ok_or(Error::truncated(regions_off, 4))?;
+        let len = 4 + get_u16(regions, 0)? as usize * get_u16(regions, 2)? as usize * 6;
+        if regions.len() < len {
+            return Err(Error::truncated(regions_off, len));
+        }
+        let count = get_u16(data, 6)? as usize;
+        if data.len() < 8 + count * 4 {
+            Err(Error::truncated(0, 8 + count * 4))
+        } else {
+            Ok(ItemVariationStore { data, regions })
+        }
+    }
+
+    /// Get scalar of region at `idx` for normalized coordinates `coords`. Axes beyond the end
+    /// of `coords` are at their default
+    fn region_scalar(&self, idx: usize, coords: &[f32]) -> f32 {
+        let axis_count = get_u16(self.regions, 0).unwrap_or(0) as usize;
+        if idx >= get_u16(self.regions, 2).unwrap_or(0) as usize {
+            return 0.0;
+        }
+        let mut scalar = 1.0;
+        for axis in 0..axis_count {
+            let off = 4 + (idx * axis_count + axis) * 6;
+            let (start, peak, end) = match (
+                get_i16(self.regions, off),
+                get_i16(self.regions, off + 2),
+                get_i16(self.regions, off + 4),
+            ) {
+                (Ok(start), Ok(peak), Ok(end)) => (f2dot14(start), f2dot14(peak), f2dot14(end)),
+                _ => return 0.0,
+            };
+            let coord = coords.get(axis).cloned().unwrap_or(0.0);
+            // Axes with no peak, invalid ranges, and ranges crossing zero don't affect the
+            // scalar
+            if peak == 0.0 || start > peak || peak > end || (start < 0.0 && end > 0.0) {
+                continue;
+            }
+            if coord == peak {
+                continue;
+            }
+            if coord <= start || coord >= end {
+                return 0.0;
+            }
+            scalar *= if coord < peak {
+                (coord - start) / (peak - start)
+            } else {
+                (end - coord) / (end - peak)
+            };
+        }
+        scalar
+    }
+
+    /// Get interpolated delta for item `inner` of ItemVariationData `outer`, at normalized
+    /// coordinates `coords`. Returns 0 for items that don't exist
+    pub(super) fn delta(&self, outer: u16, inner: u16, coords: &[f32]) -> f32 {
+        self.delta_opt(outer as usize, inner as usize, coords)
+            .unwrap_or(0.0)
+    }
+
+    fn delta_opt(&self, outer: usize, inner: usize, coords: &[f32]) -> Option<f32> {
+        if outer >= get_u16(self.data, 6).ok()? as usize {
+            return None;
+        }
+        let data = self
+            .data
+            .get(get_u32(self.data, 8 + outer * 4).ok()? as usize..)?;
+        let item_count = get_u16(data, 0).ok()? as usize;
+        let word_delta_count = get_u16(data, 2).ok()?;
+        let region_count = get_u16(data, 4).ok()? as usize;
+        if inner >= item_count {
+            return None;
+        }
+        // With LONG_WORDS set, "word" deltas are 32-bit and the rest 16-bit. Otherwise they
+        // are 16-bit and 8-bit
+        let long_words = word_delta_count & 0x8000 != 0;
+        let word_count = (word_delta_count & 0x7fff) as usize;
+        let (word_size, short_size) = if long_words { (4, 2) } else { (2, 1) };
+        let row_size =
+            word_count * word_size + region_count.saturating_sub(word_count) * short_size;
+        let row_off = 6 + region_count * 2 + inner * row_size;
+        let mut delta = 0.0;
+        let mut off = row_off;
+        for i in 0..region_count {
+            let value = match (i < word_count, long_words) {
+                (true, true) => get_u32(data, off).ok()? as i32,
+                (true, false) | (false, true) => get_i16(data, off).ok()? as i32,
+                (false, false) => *data.get(off)? as i8 as i32,
+            };
+            off += if i < word_count {
+                word_size
+            } else {
+                short_size
+            };
+            if value != 0 {
+                let region = get_u16(data, 6 + i * 2).ok()? as usize;
+                delta += value as f32 * self.region_scalar(region, coords);
+            }
+        }
+        Some(delta)
+    }
+}
@@ -0,0 +1,29 @@
+//! Vertical header table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::get_u16_unchecked;
+
+pub(super) struct Vhea<'a>(&'a [u8]);
+
+impl<'a> std::fmt::Debug for Vhea<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Vhea")
+            .field("num_of_long_ver_metrics", &self.num_of_long_ver_metrics())
+            .finish()
+    }
+}
+
+impl<'a> Vhea<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Vhea<'_>> {
+        if data.len() < 36 {
+            Err(Error::truncated(0, 36))
+        } else {
+            Ok(Vhea(data))
+        }
+    }
+
+    pub(super) fn num_of_long_ver_metrics(&self) -> u16 {
+        get_u16_unchecked(self.0, 34)
+    }
+}
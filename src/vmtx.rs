@@ -0,0 +1,64 @@
+//! Vertical metrics table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16_unchecked, get_u16_unchecked, GlyphID};
+
+/// Vertical metrics for a glyph, in font units
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VerticalMetrics {
+    /// Vertical advance height
+    pub advance: u16,
+    /// Distance from the vertical origin to the top of the glyph bounding box
+    pub top_side_bearing: i16,
+}
+
+/// Handle to vmtx table. The table consists of `num_of_long_ver_metrics` (advance, tsb) pairs,
+/// followed by top side bearings for the remaining glyphs, which share the last advance
+pub(super) struct Vmtx<'a> {
+    num_glyphs: usize,
+    num_of_long_ver_metrics: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Vmtx<'a> {
+    pub(super) fn load(
+        data: &[u8],
+        num_glyphs: usize,
+        num_of_long_ver_metrics: usize,
+    ) -> Result<Vmtx<'_>> {
+        if num_of_long_ver_metrics == 0 || num_of_long_ver_metrics > num_glyphs {
+            Err(Error::malformed("invalid number of vertical metrics"))
+        } else if data.len() < num_of_long_ver_metrics * 2 + num_glyphs * 2 {
+            Err(Error::truncated(
+                0,
+                num_of_long_ver_metrics * 2 + num_glyphs * 2,
+            ))
+        } else {
+            Ok(Vmtx {
+                num_glyphs,
+                num_of_long_ver_metrics,
+                data,
+            })
+        }
+    }
+
+    pub(super) fn get_metrics(&self, glyph_id: GlyphID) -> Result<VerticalMetrics> {
+        let idx = glyph_id.0 as usize;
+        if idx >= self.num_glyphs {
+            return Err(Error::GlyphIDOutOfBounds);
+        }
+        let nlong = self.num_of_long_ver_metrics;
+        if idx < nlong {
+            Ok(VerticalMetrics {
+                advance: get_u16_unchecked(self.data, idx * 4),
+                top_side_bearing: get_i16_unchecked(self.data, idx * 4 + 2),
+            })
+        } else {
+            Ok(VerticalMetrics {
+                advance: get_u16_unchecked(self.data, (nlong - 1) * 4),
+                top_side_bearing: get_i16_unchecked(self.data, nlong * 4 + (idx - nlong) * 2),
+            })
+        }
+    }
+}
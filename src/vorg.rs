@@ -0,0 +1,41 @@
+//! Vertical origin table
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use super::error::*;
+use super::types::{get_i16_unchecked, get_u16_unchecked, GlyphID};
+
+/// Handle to VORG table
+pub(super) struct Vorg<'a>(&'a [u8]);
+
+impl<'a> Vorg<'a> {
+    pub(super) fn load(data: &[u8]) -> Result<Vorg<'_>> {
+        if data.len() < 8 {
+            return Err(Error::truncated(0, 8));
+        }
+        let num_metrics = get_u16_unchecked(data, 6) as usize;
+        if data.len() < 8 + num_metrics * 4 {
+            Err(Error::truncated(0, 8 + num_metrics * 4))
+        } else {
+            Ok(Vorg(data))
+        }
+    }
+
+    /// Get y coordinate of the vertical origin of a glyph. Records are sorted by glyph ID
+    pub(super) fn vert_origin_y(&self, glyph_id: GlyphID) -> i16 {
+        let num_metrics = get_u16_unchecked(self.0, 6) as usize;
+        let (mut lo, mut hi) = (0, num_metrics);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let rec_off = 8 + mid * 4;
+            let id = get_u16_unchecked(self.0, rec_off) as u32;
+            if id == glyph_id.0 {
+                return get_i16_unchecked(self.0, rec_off + 2);
+            } else if id < glyph_id.0 {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        get_i16_unchecked(self.0, 4)
+    }
+}
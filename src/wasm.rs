@@ -0,0 +1,81 @@
+//! JavaScript bindings through `wasm-bindgen`
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use wasm_bindgen::prelude::*;
+
+use super::types::GlyphID;
+use super::{Face, FontCollection};
+
+/// A face loaded from a font file in memory, e.g. a `Uint8Array`
+#[wasm_bindgen]
+pub struct Font {
+    collection: FontCollection,
+    index: usize,
+}
+
+#[wasm_bindgen]
+impl Font {
+    /// Load face at `index` from font file data. The data is copied
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8], index: usize) -> Result<Font, JsError> {
+        let collection = FontCollection::new_from(data)?;
+        collection.get_face(index)?;
+        Ok(Font { collection, index })
+    }
+
+    /// Get face from the collection. Faces borrow the collection's data, so they can't be
+    /// stored alongside it, and are loaded again for each call
+    fn face(&self) -> Result<Face<'_>, JsError> {
+        Ok(self.collection.get_face(self.index)?)
+    }
+
+    /// Get glyph ID for a Unicode codepoint
+    #[wasm_bindgen(js_name = glyphId)]
+    pub fn glyph_id(&self, codepoint: u32) -> Result<u32, JsError> {
+        Ok(self.face()?.get_glyph_id(codepoint)?.0)
+    }
+
+    /// Get horizontal advance of a glyph in pixels, at `size` pixels per em
+    pub fn advance(&self, glyph_id: u32, size: f32) -> Result<f32, JsError> {
+        let face = self.face()?;
+        let scaled = face.scale(size, size, 72, 72);
+        Ok(scaled.advance(GlyphID(glyph_id))?)
+    }
+
+    /// Render a glyph at `size` pixels per em
+    pub fn render(&self, glyph_id: u32, size: f32) -> Result<Bitmap, JsError> {
+        let face = self.face()?;
+        let scaled = face.scale(size, size, 72, 72);
+        let bitmap = scaled.get_glyph(GlyphID(glyph_id))?.render()?;
+        Ok(Bitmap {
+            width: bitmap.width,
+            height: bitmap.height,
+            left: bitmap.left,
+            top: bitmap.top,
+            data: bitmap.rows().flatten().cloned().collect(),
+        })
+    }
+}
+
+/// A rendered glyph, with one byte of coverage per pixel
+#[wasm_bindgen]
+pub struct Bitmap {
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+    /// Offset in pixels from the pen position to the left edge of the bitmap
+    pub left: i32,
+    /// Offset in pixels from the baseline up to the top edge of the bitmap
+    pub top: i32,
+    data: Box<[u8]>,
+}
+
+#[wasm_bindgen]
+impl Bitmap {
+    /// Coverage values, row by row. Returned to JavaScript as a copy in a `Uint8Array`
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+}
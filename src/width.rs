@@ -0,0 +1,230 @@
+//! Display width of characters in terminal cells, following Unicode East Asian Width
+//! (UAX #11) and emoji presentation. These are the width decisions used by
+//! `ScaledFace::layout_grid`
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+/// Ranges of East Asian Wide (W) and Fullwidth (F) characters, sorted
+const WIDE: [(u32, u32); 19] = [
+    (0x1100, 0x115f),
+    (0x231a, 0x231b),
+    (0x2329, 0x232a),
+    (0x2e80, 0x303e),
+    (0x3041, 0x33ff),
+    (0x3400, 0x4dbf),
+    (0x4e00, 0x9fff),
+    (0xa000, 0xa4cf),
+    (0xa960, 0xa97f),
+    (0xac00, 0xd7a3),
+    (0xf900, 0xfaff),
+    (0xfe10, 0xfe19),
+    (0xfe30, 0xfe6f),
+    (0xff00, 0xff60),
+    (0xffe0, 0xffe6),
+    (0x16fe0, 0x18cff),
+    (0x1b000, 0x1b2ff),
+    (0x20000, 0x2fffd),
+    (0x30000, 0x3fffd),
+];
+
+/// Ranges of characters with default emoji presentation (Emoji_Presentation=Yes), sorted.
+/// These are also East Asian Wide
+const EMOJI_PRESENTATION: [(u32, u32); 80] = [
+    (0x231a, 0x231b),
+    (0x23e9, 0x23ec),
+    (0x23f0, 0x23f0),
+    (0x23f3, 0x23f3),
+    (0x25fd, 0x25fe),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267f, 0x267f),
+    (0x2693, 0x2693),
+    (0x26a1, 0x26a1),
+    (0x26aa, 0x26ab),
+    (0x26bd, 0x26be),
+    (0x26c4, 0x26c5),
+    (0x26ce, 0x26ce),
+    (0x26d4, 0x26d4),
+    (0x26ea, 0x26ea),
+    (0x26f2, 0x26f3),
+    (0x26f5, 0x26f5),
+    (0x26fa, 0x26fa),
+    (0x26fd, 0x26fd),
+    (0x2705, 0x2705),
+    (0x270a, 0x270b),
+    (0x2728, 0x2728),
+    (0x274c, 0x274c),
+    (0x274e, 0x274e),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27b0, 0x27b0),
+    (0x27bf, 0x27bf),
+    (0x2b1b, 0x2b1c),
+    (0x2b50, 0x2b50),
+    (0x2b55, 0x2b55),
+    (0x1f004, 0x1f004),
+    (0x1f0cf, 0x1f0cf),
+    (0x1f18e, 0x1f18e),
+    (0x1f191, 0x1f19a),
+    (0x1f1e6, 0x1f1ff),
+    (0x1f201, 0x1f201),
+    (0x1f21a, 0x1f21a),
+    (0x1f22f, 0x1f22f),
+    (0x1f232, 0x1f236),
+    (0x1f238, 0x1f23a),
+    (0x1f250, 0x1f251),
+    (0x1f300, 0x1f320),
+    (0x1f32d, 0x1f335),
+    (0x1f337, 0x1f37c),
+    (0x1f37e, 0x1f393),
+    (0x1f3a0, 0x1f3ca),
+    (0x1f3cf, 0x1f3d3),
+    (0x1f3e0, 0x1f3f0),
+    (0x1f3f4, 0x1f3f4),
+    (0x1f3f8, 0x1f43e),
+    (0x1f440, 0x1f440),
+    (0x1f442, 0x1f4fc),
+    (0x1f4ff, 0x1f53d),
+    (0x1f54b, 0x1f54e),
+    (0x1f550, 0x1f567),
+    (0x1f57a, 0x1f57a),
+    (0x1f595, 0x1f596),
+    (0x1f5a4, 0x1f5a4),
+    (0x1f5fb, 0x1f64f),
+    (0x1f680, 0x1f6c5),
+    (0x1f6cc, 0x1f6cc),
+    (0x1f6d0, 0x1f6d2),
+    (0x1f6d5, 0x1f6d7),
+    (0x1f6dc, 0x1f6df),
+    (0x1f6eb, 0x1f6ec),
+    (0x1f6f4, 0x1f6fc),
+    (0x1f7e0, 0x1f7eb),
+    (0x1f7f0, 0x1f7f0),
+    (0x1f90c, 0x1f93a),
+    (0x1f93c, 0x1f945),
+    (0x1f947, 0x1f9ff),
+    (0x1fa70, 0x1fa7c),
+    (0x1fa80, 0x1fa89),
+    (0x1fa8f, 0x1fac6),
+    (0x1face, 0x1fadc),
+    (0x1fadf, 0x1fae9),
+    (0x1faf0, 0x1faf8),
+];
+
+/// Ranges of combining marks and other characters that take no cells of their own, sorted
+const ZERO_WIDTH: [(u32, u32); 13] = [
+    (0x0300, 0x036f),
+    (0x0483, 0x0489),
+    (0x0591, 0x05bd),
+    (0x0610, 0x061a),
+    (0x064b, 0x065f),
+    (0x1ab0, 0x1aff),
+    (0x1dc0, 0x1dff),
+    (0x200b, 0x200f),
+    (0x20d0, 0x20ff),
+    (0xfe00, 0xfe0f),
+    (0xfe20, 0xfe2f),
+    (0xfeff, 0xfeff),
+    (0xe0100, 0xe01ef),
+];
+
+fn in_ranges(ranges: &[(u32, u32)], c: u32) -> bool {
+    ranges
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Check if `c` is East Asian Wide (W) or Fullwidth (F), including emoji with default emoji
+/// presentation. Ambiguous characters are treated as narrow
+pub fn is_wide(c: char) -> bool {
+    in_ranges(&WIDE, c as u32) || is_emoji_presentation(c)
+}
+
+/// Check if `c` is displayed as emoji by default, rather than as text
+pub fn is_emoji_presentation(c: char) -> bool {
+    in_ranges(&EMOJI_PRESENTATION, c as u32)
+}
+
+/// Check if `c` takes no cells of its own: control characters, combining marks, zero-width
+/// spaces and joiners, and variation selectors
+pub fn is_zero_width(c: char) -> bool {
+    let c = c as u32;
+    c < 0x20 || (0x7f..0xa0).contains(&c) || in_ranges(&ZERO_WIDTH, c)
+}
+
+/// Get number of cells `c` takes: 0 for zero-width characters, 2 for wide characters, and 1
+/// otherwise
+pub fn char_width(c: char) -> usize {
+    if is_zero_width(c) {
+        0
+    } else if is_wide(c) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Get number of cells `text` takes. Characters followed by VARIATION SELECTOR-16, which
+/// requests emoji presentation, take 2 cells. Emoji ZWJ sequences and pairs of regional
+/// indicators (flags) take 2 cells in all, as do emoji with skin tone modifiers. Tabs and
+/// other control characters take none
+pub fn str_width(text: &str) -> usize {
+    let mut width = 0;
+    // Width of the last cluster, whether it ends in a ZWJ joining the next character into it,
+    // and whether it is a lone regional indicator
+    let mut last = 0;
+    let mut joining = false;
+    let mut flag = false;
+    for c in text.chars() {
+        let c_width = char_width(c);
+        let modifies = is_emoji_modifier(c);
+        if c == EMOJI_SELECTOR && last == 1 {
+            width += 1;
+            last = 2;
+        } else if c_width == 0 {
+            if c.is_control() {
+                last = 0;
+                flag = false;
+            }
+        } else if joining || (flag && is_regional_indicator(c)) || (modifies && last == 2) {
+            width += c_width.saturating_sub(last);
+            last = last.max(c_width);
+            flag = false;
+        } else {
+            width += c_width;
+            last = c_width;
+            flag = is_regional_indicator(c);
+        }
+        joining = c == ZWJ && last == 2;
+    }
+    width
+}
+
+/// Check if `c` is a regional indicator symbol. Pairs of them are displayed as flags
+pub fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// Check if `c` is an emoji modifier, giving the skin tone of the emoji before it
+pub fn is_emoji_modifier(c: char) -> bool {
+    ('\u{1f3fb}'..='\u{1f3ff}').contains(&c)
+}
+
+/// Check if `c` is a variation selector, choosing a glyph variant of the character before it
+pub(super) fn is_variation_selector(c: char) -> bool {
+    ('\u{fe00}'..='\u{fe0f}').contains(&c) || ('\u{e0100}'..='\u{e01ef}').contains(&c)
+}
+
+/// ZERO WIDTH JOINER, joining emoji into a single ZWJ sequence
+pub(super) const ZWJ: char = '\u{200d}';
+
+/// VARIATION SELECTOR-16, requesting emoji presentation of the preceding character
+pub(super) const EMOJI_SELECTOR: char = '\u{fe0f}';
@@ -0,0 +1,98 @@
+//! Writing sfnt font files
+// (C) 2019 Srimanta Barua <srimanta.barua1@gmail.com>
+
+use std::collections::BTreeMap;
+
+use super::types::{table_checksum, Tag};
+use super::validate::CHECKSUM_MAGIC;
+use super::Face;
+
+/// Builder for sfnt (TrueType/OpenType) font files. Tables are written sorted by tag and padded
+/// to 4 bytes, and checksums and the head `checkSumAdjustment` field are computed when building
+#[derive(Clone, Debug)]
+pub struct FontBuilder {
+    sfnt_version: Tag,
+    tables: BTreeMap<Tag, Vec<u8>>,
+}
+
+impl FontBuilder {
+    /// Create builder for a font with no tables. `sfnt_version` should be 0x00010000 for
+    /// TrueType outlines, and 'OTTO' for CFF outlines
+    pub fn new(sfnt_version: Tag) -> FontBuilder {
+        FontBuilder {
+            sfnt_version,
+            tables: BTreeMap::new(),
+        }
+    }
+
+    /// Create builder with all tables from a face
+    pub fn from_face(face: &Face) -> FontBuilder {
+        let mut builder = FontBuilder::new(face.sfnt_version);
+        for (tag, data) in face.tables() {
+            builder.add_table(tag, data.to_vec());
+        }
+        builder
+    }
+
+    /// Add table to the font, replacing any existing table with the same tag
+    pub fn add_table(&mut self, tag: Tag, data: Vec<u8>) -> &mut FontBuilder {
+        self.tables.insert(tag, data);
+        self
+    }
+
+    /// Remove table from the font, returning its data if it was present
+    pub fn remove_table(&mut self, tag: Tag) -> Option<Vec<u8>> {
+        self.tables.remove(&tag)
+    }
+
+    /// Get data for a table in the font
+    pub fn table(&self, tag: Tag) -> Option<&[u8]> {
+        self.tables.get(&tag).map(|data| data.as_slice())
+    }
+
+    /// Write out the font file
+    pub fn build(&self) -> Vec<u8> {
+        let num_tables = self.tables.len();
+        let entry_selector = if num_tables == 0 {
+            0
+        } else {
+            usize::BITS - 1 - num_tables.leading_zeros()
+        };
+        let search_range = (1usize << entry_selector) * 16;
+        let mut data = Vec::new();
+        data.extend_from_slice(&self.sfnt_version.0.to_be_bytes());
+        data.extend_from_slice(&(num_tables as u16).to_be_bytes());
+        data.extend_from_slice(&(search_range as u16).to_be_bytes());
+        data.extend_from_slice(&(entry_selector as u16).to_be_bytes());
+        data.extend_from_slice(
+            &((num_tables * 16).saturating_sub(search_range) as u16).to_be_bytes(),
+        );
+        let mut offset = 12 + num_tables * 16;
+        let mut head_off = None;
+        for (&tag, table) in &self.tables {
+            let checksum = if tag == Tag::new(b"head") && table.len() >= 12 {
+                head_off = Some(offset);
+                table_checksum(table).wrapping_sub(table_checksum(&table[8..12]))
+            } else {
+                table_checksum(table)
+            };
+            data.extend_from_slice(&tag.0.to_be_bytes());
+            data.extend_from_slice(&checksum.to_be_bytes());
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += (table.len() + 3) & !3;
+        }
+        for table in self.tables.values() {
+            data.extend_from_slice(table);
+            while data.len() % 4 != 0 {
+                data.push(0);
+            }
+        }
+        if let Some(head_off) = head_off {
+            data[head_off + 8..head_off + 12].copy_from_slice(&[0; 4]);
+            let adjustment = CHECKSUM_MAGIC.wrapping_sub(table_checksum(&data));
+            data[head_off + 8..head_off + 12].copy_from_slice(&adjustment.to_be_bytes());
+        }
+        data
+    }
+}